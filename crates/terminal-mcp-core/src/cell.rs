@@ -4,7 +4,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Terminal color supporting ANSI, 256-color palette, and true RGB.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Color {
     /// Default terminal color
@@ -58,8 +58,119 @@ pub enum Color {
     },
 }
 
+/// The 16 ANSI colors as RGB, using xterm's default palette.
+const ANSI_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Names of the 16 ANSI colors, matching their serialized form.
+const ANSI_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright_black",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_blue",
+    "bright_magenta",
+    "bright_cyan",
+    "bright_white",
+];
+
+/// Channel levels of the 6x6x6 color cube (indices 16-231).
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Color {
+    /// Palette index for ANSI colors (0-15) and indexed colors.
+    ///
+    /// Returns None for `Default` and `Rgb`.
+    pub fn palette_index(self) -> Option<u8> {
+        let index = match self {
+            Color::Default | Color::Rgb { .. } => return None,
+            Color::Black => 0,
+            Color::Red => 1,
+            Color::Green => 2,
+            Color::Yellow => 3,
+            Color::Blue => 4,
+            Color::Magenta => 5,
+            Color::Cyan => 6,
+            Color::White => 7,
+            Color::BrightBlack => 8,
+            Color::BrightRed => 9,
+            Color::BrightGreen => 10,
+            Color::BrightYellow => 11,
+            Color::BrightBlue => 12,
+            Color::BrightMagenta => 13,
+            Color::BrightCyan => 14,
+            Color::BrightWhite => 15,
+            Color::Indexed(index) => index,
+        };
+        Some(index)
+    }
+
+    /// Human-readable label: an ANSI color name or `#rrggbb` hex.
+    ///
+    /// Indexed colors 0-15 use the ANSI names; other indexed colors and true
+    /// colors are resolved to hex. `Default` has no label.
+    pub fn label(self) -> Option<String> {
+        match self.palette_index() {
+            Some(index) if index < 16 => Some(ANSI_NAMES[index as usize].to_string()),
+            _ => self
+                .to_rgb()
+                .map(|(r, g, b)| format!("#{r:02x}{g:02x}{b:02x}")),
+        }
+    }
+
+    /// Resolve to RGB using the xterm palette.
+    ///
+    /// `Default` depends on the terminal theme and resolves to None.
+    pub fn to_rgb(self) -> Option<(u8, u8, u8)> {
+        if let Color::Rgb { r, g, b } = self {
+            return Some((r, g, b));
+        }
+
+        let index = self.palette_index()?;
+        Some(match index {
+            0..=15 => ANSI_PALETTE[index as usize],
+            16..=231 => {
+                let cube = index - 16;
+                (
+                    CUBE_LEVELS[(cube / 36) as usize],
+                    CUBE_LEVELS[(cube / 6 % 6) as usize],
+                    CUBE_LEVELS[(cube % 6) as usize],
+                )
+            }
+            _ => {
+                let level = 8 + 10 * (index - 232);
+                (level, level, level)
+            }
+        })
+    }
+}
+
 /// Text attributes for a terminal cell.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct CellAttributes {
     /// Bold/bright text
     pub bold: bool,
@@ -198,6 +309,39 @@ mod tests {
         assert_eq!(color, deserialized);
     }
 
+    #[test]
+    fn test_color_to_rgb() {
+        assert_eq!(Color::Default.to_rgb(), None);
+        assert_eq!(Color::Red.to_rgb(), Some((205, 0, 0)));
+        assert_eq!(Color::Indexed(9).to_rgb(), Color::BrightRed.to_rgb());
+        assert_eq!(Color::Indexed(196).to_rgb(), Some((255, 0, 0)));
+        assert_eq!(Color::Indexed(244).to_rgb(), Some((128, 128, 128)));
+        assert_eq!(Color::Rgb { r: 1, g: 2, b: 3 }.to_rgb(), Some((1, 2, 3)));
+    }
+
+    #[test]
+    fn test_color_label() {
+        assert_eq!(Color::Default.label(), None);
+        assert_eq!(Color::Red.label().as_deref(), Some("red"));
+        assert_eq!(Color::Indexed(12).label().as_deref(), Some("bright_blue"));
+        assert_eq!(Color::Indexed(196).label().as_deref(), Some("#ff0000"));
+        assert_eq!(
+            Color::Rgb {
+                r: 18,
+                g: 52,
+                b: 86
+            }
+            .label()
+            .as_deref(),
+            Some("#123456")
+        );
+        // Labels match the serialized names
+        assert_eq!(
+            serde_json::to_string(&Color::BrightMagenta).unwrap(),
+            format!("\"{}\"", Color::BrightMagenta.label().unwrap())
+        );
+    }
+
     #[test]
     fn test_cell_attributes_default() {
         let attrs = CellAttributes::default();
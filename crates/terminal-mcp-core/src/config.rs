@@ -1,8 +1,12 @@
 //! Configuration types for Terminal MCP Server.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+use crate::policy::{Policy, PolicySettings};
+use crate::redact::{RedactionSettings, Redactor};
+
 /// Server configuration loaded from YAML file.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
@@ -52,6 +56,9 @@ impl ServerConfig {
             .into());
         }
 
+        self.server.validate()?;
+        self.security.validate()?;
+
         // Validate custom patterns
         for pattern in &self.detection.custom_patterns {
             pattern.validate()?;
@@ -65,8 +72,12 @@ impl ServerConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ServerSettings {
-    /// Transport type (stdio, tcp, etc.)
+    /// Transport: stdio, http or unix
     pub transport: String,
+    /// Streamable HTTP transport settings
+    pub http: HttpSettings,
+    /// Unix domain socket transport settings
+    pub unix: UnixSocketSettings,
     /// Maximum number of concurrent sessions
     pub max_sessions: usize,
     /// Session timeout in seconds (0 = no timeout)
@@ -79,6 +90,8 @@ impl Default for ServerSettings {
     fn default() -> Self {
         Self {
             transport: "stdio".to_string(),
+            http: HttpSettings::default(),
+            unix: UnixSocketSettings::default(),
             max_sessions: 10,
             session_timeout: 3600,
             log_level: "info".to_string(),
@@ -86,6 +99,148 @@ impl Default for ServerSettings {
     }
 }
 
+impl ServerSettings {
+    /// Validate the transport and its settings.
+    pub fn validate(&self) -> crate::Result<()> {
+        match self.transport.as_str() {
+            "stdio" => Ok(()),
+            "http" => self.http.validate(),
+            "unix" => self.unix.validate(),
+            other => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("server.transport must be stdio, http or unix, got '{other}'"),
+            )
+            .into()),
+        }
+    }
+}
+
+/// Streamable HTTP transport settings.
+///
+/// One server process serves every client that connects, and all of them
+/// see the same terminal sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpSettings {
+    /// Address to listen on
+    pub bind: String,
+    /// URL path of the MCP endpoint
+    pub path: String,
+    /// Token clients must send as `Authorization: Bearer <token>`; generated
+    /// into `token_file` when unset
+    pub auth_token: Option<String>,
+    /// Where a generated token is written; defaults to
+    /// `terminal-mcp-http.token` in the runtime directory
+    pub token_file: Option<String>,
+    /// Browser origins allowed to connect; empty allows only localhost
+    pub allowed_origins: Vec<String>,
+    /// Host names clients may address besides the bind address, e.g. a
+    /// reverse proxy's
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            bind: "127.0.0.1:8765".to_string(),
+            path: "/mcp".to_string(),
+            auth_token: None,
+            token_file: None,
+            allowed_origins: vec![],
+            allowed_hosts: vec![],
+        }
+    }
+}
+
+impl HttpSettings {
+    /// File a generated token is written to.
+    pub fn token_path(&self) -> crate::Result<std::path::PathBuf> {
+        match &self.token_file {
+            Some(path) => Ok(path.into()),
+            None => Ok(crate::platform::runtime_dir()?.join("terminal-mcp-http.token")),
+        }
+    }
+
+    /// Check the bind address and path, and that non-loopback listeners
+    /// have a configured token rather than a generated one.
+    pub fn validate(&self) -> crate::Result<()> {
+        let invalid =
+            |message: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, message);
+        let addr: std::net::SocketAddr = self.bind.parse().map_err(|e| {
+            invalid(format!(
+                "server.http.bind '{}' is not an address: {e}",
+                self.bind
+            ))
+        })?;
+        if !self.path.starts_with('/') {
+            return Err(invalid(format!(
+                "server.http.path '{}' must start with '/'",
+                self.path
+            ))
+            .into());
+        }
+        if self.auth_token.as_deref().is_some_and(str::is_empty) {
+            return Err(invalid("server.http.auth_token must not be empty".to_string()).into());
+        }
+        if !addr.ip().is_loopback() && self.auth_token.is_none() {
+            return Err(invalid(format!(
+                "server.http.auth_token is required to listen on non-loopback address {addr}"
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Unix domain socket transport settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnixSocketSettings {
+    /// Socket path; defaults to `terminal-mcp.sock` in the runtime
+    /// directory (see [`crate::runtime_dir`])
+    pub path: Option<String>,
+    /// Octal file mode of the socket
+    pub mode: String,
+}
+
+impl Default for UnixSocketSettings {
+    fn default() -> Self {
+        Self {
+            path: None,
+            mode: "0600".to_string(),
+        }
+    }
+}
+
+impl UnixSocketSettings {
+    /// Path the socket is created at.
+    pub fn socket_path(&self) -> crate::Result<std::path::PathBuf> {
+        match &self.path {
+            Some(path) => Ok(path.into()),
+            None => Ok(crate::platform::runtime_dir()?.join("terminal-mcp.sock")),
+        }
+    }
+
+    /// File mode parsed from octal, e.g. `0660`.
+    pub fn file_mode(&self) -> crate::Result<u32> {
+        u32::from_str_radix(self.mode.trim_start_matches("0o"), 8)
+            .ok()
+            .filter(|mode| *mode <= 0o777)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("server.unix.mode '{}' is not an octal file mode", self.mode),
+                )
+                .into()
+            })
+    }
+
+    /// Check the mode.
+    pub fn validate(&self) -> crate::Result<()> {
+        self.file_mode().map(|_| ())
+    }
+}
+
 /// Security settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -94,6 +249,14 @@ pub struct SecuritySettings {
     pub allowed_commands: Vec<String>,
     /// Sandbox mode: none, container, namespace
     pub sandbox_mode: String,
+    /// Filesystem and network layout for the namespace sandbox
+    pub sandbox: SandboxSettings,
+    /// Resource limits applied to every session
+    pub limits: ResourceLimits,
+    /// Allow/deny rules for commands and typed input, and the audit log
+    pub policy: PolicySettings,
+    /// Secret redaction for screen text, output and recordings
+    pub redaction: RedactionSettings,
 }
 
 impl Default for SecuritySettings {
@@ -101,6 +264,10 @@ impl Default for SecuritySettings {
         Self {
             allowed_commands: vec![],
             sandbox_mode: "none".to_string(),
+            sandbox: SandboxSettings::default(),
+            limits: ResourceLimits::default(),
+            policy: PolicySettings::default(),
+            redaction: RedactionSettings::default(),
         }
     }
 }
@@ -118,6 +285,204 @@ impl SecuritySettings {
             .iter()
             .any(|allowed| allowed == command)
     }
+
+    /// Compile the command policy, including `allowed_commands`.
+    pub fn compile_policy(&self) -> crate::Result<Policy> {
+        Policy::new(&self.policy, &self.allowed_commands)
+    }
+
+    /// Compile the redaction rules.
+    pub fn compile_redactor(&self) -> crate::Result<Redactor> {
+        Redactor::new(&self.redaction)
+    }
+
+    /// Whether sessions run in the namespace sandbox.
+    pub fn namespace_sandbox(&self) -> bool {
+        self.sandbox_mode == "namespace"
+    }
+
+    /// Validate the sandbox mode and layout.
+    pub fn validate(&self) -> crate::Result<()> {
+        if !["none", "container", "namespace"].contains(&self.sandbox_mode.as_str()) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "security.sandbox_mode must be none, container or namespace, got '{}'",
+                    self.sandbox_mode
+                ),
+            )
+            .into());
+        }
+        self.sandbox.validate()?;
+        self.limits.validate()?;
+        self.compile_policy()?;
+        self.compile_redactor().map(|_| ())
+    }
+}
+
+/// Namespace sandbox settings.
+///
+/// The child gets a fresh root with only `read_only_paths` bind-mounted
+/// read-only (submounts included), a private `/tmp`, a minimal `/dev` and its
+/// own `/proc`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SandboxSettings {
+    /// Host paths mounted read-only at the same location; missing ones are skipped
+    pub read_only_paths: Vec<String>,
+    /// Host directory mounted read-write, and the default working directory
+    pub project_dir: Option<String>,
+    /// Keep host network access instead of an isolated loopback-only network
+    pub network: bool,
+}
+
+impl Default for SandboxSettings {
+    fn default() -> Self {
+        Self {
+            read_only_paths: ["/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc"]
+                .iter()
+                .map(|path| path.to_string())
+                .collect(),
+            project_dir: None,
+            network: false,
+        }
+    }
+}
+
+impl SandboxSettings {
+    /// Check that all paths are absolute.
+    pub fn validate(&self) -> crate::Result<()> {
+        let paths = self.read_only_paths.iter().chain(&self.project_dir);
+        for path in paths {
+            if !Path::new(path).is_absolute() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("sandbox path '{path}' must be absolute"),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Per-session resource limits; unset limits are not applied.
+///
+/// The rlimits apply to each process of the session, the cgroup limits to
+/// the session as a whole.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// CPU time per process in seconds (RLIMIT_CPU)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_seconds: Option<u64>,
+    /// Address space per process in MiB (RLIMIT_AS)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_space_mb: Option<u64>,
+    /// Open files per process (RLIMIT_NOFILE)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_files: Option<u64>,
+    /// Processes owned by the user, counted across the host (RLIMIT_NPROC)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processes: Option<u64>,
+    /// Output read per second; beyond it the application blocks on writes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_bytes_per_sec: Option<u64>,
+    /// Memory of the whole session in MiB (cgroup `memory.max`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// Processes in the whole session (cgroup `pids.max`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_pids: Option<u64>,
+    /// Delegated cgroup v2 directory to create session cgroups in (config only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub cgroup_parent: Option<String>,
+}
+
+impl ResourceLimits {
+    /// Whether no limit is set.
+    pub fn is_empty(&self) -> bool {
+        self.cpu_seconds.is_none()
+            && self.address_space_mb.is_none()
+            && self.open_files.is_none()
+            && self.processes.is_none()
+            && self.output_bytes_per_sec.is_none()
+            && !self.needs_cgroup()
+    }
+
+    /// Whether a session cgroup is needed.
+    pub fn needs_cgroup(&self) -> bool {
+        self.memory_mb.is_some() || self.max_pids.is_some()
+    }
+
+    /// Combine with limits requested for one session.
+    ///
+    /// Requests can only tighten limits: each limit is the lower of the two.
+    /// The cgroup parent always comes from `self`.
+    pub fn tightened(&self, requested: &ResourceLimits) -> ResourceLimits {
+        let min = |a: Option<u64>, b: Option<u64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        ResourceLimits {
+            cpu_seconds: min(self.cpu_seconds, requested.cpu_seconds),
+            address_space_mb: min(self.address_space_mb, requested.address_space_mb),
+            open_files: min(self.open_files, requested.open_files),
+            processes: min(self.processes, requested.processes),
+            output_bytes_per_sec: min(self.output_bytes_per_sec, requested.output_bytes_per_sec),
+            memory_mb: min(self.memory_mb, requested.memory_mb),
+            max_pids: min(self.max_pids, requested.max_pids),
+            cgroup_parent: self.cgroup_parent.clone(),
+        }
+    }
+
+    /// Check that cgroup limits have somewhere to live.
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.needs_cgroup() && self.cgroup_parent.is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "memory_mb and max_pids need security.limits.cgroup_parent",
+            )
+            .into());
+        }
+        if let Some(parent) = &self.cgroup_parent {
+            if !Path::new(parent).is_absolute() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("cgroup_parent '{parent}' must be absolute"),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A resource limit a session ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    /// A process used up its CPU time
+    CpuTime,
+    /// The session cgroup ran out of memory
+    Memory,
+    /// The session cgroup hit its process limit
+    Processes,
+    /// Output is being throttled
+    OutputRate,
+}
+
+impl std::fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LimitKind::CpuTime => "cpu_time",
+            LimitKind::Memory => "memory",
+            LimitKind::Processes => "processes",
+            LimitKind::OutputRate => "output_rate",
+        };
+        f.write_str(name)
+    }
 }
 
 /// Detection settings.
@@ -342,6 +707,95 @@ detection:
         assert!(!settings.is_command_allowed("/bin/sh"));
     }
 
+    #[test]
+    fn test_sandbox_settings() {
+        let yaml = r#"
+security:
+  sandbox_mode: namespace
+  sandbox:
+    read_only_paths: [/usr, /etc]
+    project_dir: /home/me/project
+"#;
+        let config = ServerConfig::from_yaml(yaml).unwrap();
+        assert!(config.security.namespace_sandbox());
+        assert_eq!(
+            config.security.sandbox.read_only_paths,
+            vec!["/usr", "/etc"]
+        );
+        assert!(!config.security.sandbox.network);
+
+        assert!(ServerConfig::from_yaml("security:\n  sandbox_mode: chroot\n").is_err());
+        let relative = "security:\n  sandbox:\n    project_dir: project\n";
+        assert!(ServerConfig::from_yaml(relative).is_err());
+    }
+
+    #[test]
+    fn test_http_settings() {
+        let yaml = r#"
+server:
+  transport: http
+  http:
+    bind: 0.0.0.0:9000
+    auth_token: s3cret
+    allowed_origins: [https://ide.example.com]
+"#;
+        let config = ServerConfig::from_yaml(yaml).unwrap();
+        assert_eq!(config.server.http.bind, "0.0.0.0:9000");
+        assert_eq!(config.server.http.path, "/mcp");
+
+        assert!(ServerConfig::from_yaml("server:\n  transport: tcp\n").is_err());
+        let open = "server:\n  transport: http\n  http:\n    bind: 0.0.0.0:9000\n";
+        assert!(ServerConfig::from_yaml(open).is_err());
+        let local = "server:\n  transport: http\n  http:\n    bind: '[::1]:9000'\n";
+        assert!(ServerConfig::from_yaml(local).is_ok());
+        let bad_bind = "server:\n  transport: http\n  http:\n    bind: localhost\n";
+        assert!(ServerConfig::from_yaml(bad_bind).is_err());
+    }
+
+    #[test]
+    fn test_unix_socket_settings() {
+        let yaml =
+            "server:\n  transport: unix\n  unix:\n    path: /run/tm.sock\n    mode: '0660'\n";
+        let config = ServerConfig::from_yaml(yaml).unwrap();
+        assert_eq!(
+            config.server.unix.socket_path().unwrap(),
+            Path::new("/run/tm.sock")
+        );
+        assert_eq!(config.server.unix.file_mode().unwrap(), 0o660);
+        assert_eq!(UnixSocketSettings::default().file_mode().unwrap(), 0o600);
+
+        let bad_mode = "server:\n  transport: unix\n  unix:\n    mode: '0999'\n";
+        assert!(ServerConfig::from_yaml(bad_mode).is_err());
+    }
+
+    #[test]
+    fn test_resource_limits() {
+        let config = ResourceLimits {
+            cpu_seconds: Some(60),
+            output_bytes_per_sec: Some(1 << 20),
+            cgroup_parent: Some("/sys/fs/cgroup/agents".to_string()),
+            ..Default::default()
+        };
+        let requested = ResourceLimits {
+            cpu_seconds: Some(600),
+            open_files: Some(256),
+            cgroup_parent: Some("/".to_string()),
+            ..Default::default()
+        };
+        let limits = config.tightened(&requested);
+        assert_eq!(limits.cpu_seconds, Some(60));
+        assert_eq!(limits.open_files, Some(256));
+        assert_eq!(limits.output_bytes_per_sec, Some(1 << 20));
+        assert_eq!(limits.cgroup_parent, config.cgroup_parent);
+
+        let yaml = "security:\n  limits:\n    memory_mb: 512\n";
+        assert!(ServerConfig::from_yaml(yaml).is_err());
+        let yaml =
+            "security:\n  limits:\n    memory_mb: 512\n    cgroup_parent: /sys/fs/cgroup/a\n";
+        let config = ServerConfig::from_yaml(yaml).unwrap();
+        assert!(config.security.limits.needs_cgroup());
+    }
+
     #[test]
     fn test_empty_pattern_name() {
         let pattern = CustomPatternConfig {
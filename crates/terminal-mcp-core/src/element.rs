@@ -3,7 +3,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{Bounds, Dimensions, Position};
+use crate::{Bounds, Dimensions, Position, TerminalEvents};
 
 /// Menu item within a menu element.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -179,6 +179,82 @@ impl Element {
     }
 }
 
+/// Cursor visual style, as set by DECSCUSR.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorStyle {
+    /// Block cursor (fills entire cell)
+    #[default]
+    Block,
+    /// Underline cursor (bottom of cell)
+    Underline,
+    /// Bar cursor (vertical line at left)
+    Bar,
+}
+
+/// Line discipline flags of the PTY, as set by the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TermiosFlags {
+    /// Typed characters are echoed back (ECHO)
+    pub echo: bool,
+    /// Input is delivered a line at a time (ICANON)
+    pub canonical: bool,
+    /// Ctrl+C and Ctrl+Z raise signals (ISIG)
+    pub signals: bool,
+}
+
+/// How typed input is handled by the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InputMode {
+    /// Line editing (shell, REPL): text is echoed and sent on Enter
+    Line,
+    /// Keys go straight to the application (full-screen TUI)
+    Raw,
+    /// Secret entry: typed text is not echoed, or is masked
+    Password,
+}
+
+/// A run of identically styled cells within one row.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct StyleRun {
+    /// First column of the run
+    pub start_col: u16,
+    /// Column just past the end of the run
+    pub end_col: u16,
+    /// Foreground color name or hex (absent for the default color)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fg: Option<String>,
+    /// Background color name or hex (absent for the default color)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bg: Option<String>,
+    /// Bold text
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub bold: bool,
+    /// Reverse video (typical for selections)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub reverse: bool,
+    /// Underlined text
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub underline: bool,
+    /// Dim text (typical for disabled items)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub dim: bool,
+}
+
+/// Styled runs of one screen row. Rows without styling are omitted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RowStyles {
+    /// Row index
+    pub row: u16,
+    /// Non-default runs, left to right
+    pub runs: Vec<StyleRun>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
 /// Terminal State Tree - structured snapshot of terminal content.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TerminalStateTree {
@@ -188,15 +264,40 @@ pub struct TerminalStateTree {
     pub dimensions: Dimensions,
     /// Current cursor position
     pub cursor: Position,
+    /// Whether the application shows the cursor (DECTCEM)
+    #[serde(default = "default_cursor_visible")]
+    pub cursor_visible: bool,
+    /// Cursor shape requested by the application
+    #[serde(default)]
+    pub cursor_style: CursorStyle,
     /// Snapshot timestamp (ISO 8601)
     pub timestamp: String,
     /// Detected UI elements
     pub elements: Vec<Element>,
     /// Raw text content (stripped of formatting)
     pub raw_text: String,
-    /// Raw ANSI buffer (optional, for debugging)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Screen re-encoded with ANSI SGR sequences (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ansi_buffer: Option<String>,
+    /// Screen rendered as a standalone HTML page (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
+    /// Per-row color and attribute runs (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub styles: Option<Vec<RowStyles>>,
+    /// Bells and notifications received so far
+    #[serde(default, skip_serializing_if = "TerminalEvents::is_empty")]
+    pub events: TerminalEvents,
+    /// PTY line discipline flags (unavailable in visual mode and on Windows)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub termios: Option<TermiosFlags>,
+    /// How typed input will be handled, if it can be told
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_mode: Option<InputMode>,
+}
+
+fn default_cursor_visible() -> bool {
+    true
 }
 
 impl TerminalStateTree {
@@ -292,10 +393,17 @@ mod tests {
             session_id: "sess1".to_string(),
             dimensions: Dimensions::new(24, 80),
             cursor: Position::new(0, 0),
+            cursor_visible: true,
+            cursor_style: CursorStyle::Block,
             timestamp: "2025-11-29T00:00:00Z".to_string(),
             elements: vec![button, input],
             raw_text: "".to_string(),
             ansi_buffer: None,
+            html: None,
+            styles: None,
+            events: Default::default(),
+            termios: None,
+            input_mode: None,
         };
 
         assert!(tst.find_element("btn1").is_some());
@@ -328,10 +436,17 @@ mod tests {
             session_id: "sess1".to_string(),
             dimensions: Dimensions::new(24, 80),
             cursor: Position::new(0, 0),
+            cursor_visible: true,
+            cursor_style: CursorStyle::Block,
             timestamp: "2025-11-29T00:00:00Z".to_string(),
             elements: vec![button1, button2, input],
             raw_text: "".to_string(),
             ansi_buffer: None,
+            html: None,
+            styles: None,
+            events: Default::default(),
+            termios: None,
+            input_mode: None,
         };
 
         let buttons = tst.elements_of_type("button");
@@ -357,4 +472,19 @@ mod tests {
         let deserialized: Element = serde_json::from_str(&json).unwrap();
         assert_eq!(menu, deserialized);
     }
+
+    #[test]
+    fn test_tst_cursor_defaults() {
+        let json = r#"{
+            "session_id": "s",
+            "dimensions": {"rows": 24, "cols": 80},
+            "cursor": {"row": 0, "col": 0},
+            "timestamp": "2025-11-29T00:00:00Z",
+            "elements": [],
+            "raw_text": ""
+        }"#;
+        let tst: TerminalStateTree = serde_json::from_str(json).unwrap();
+        assert!(tst.cursor_visible);
+        assert_eq!(tst.cursor_style, CursorStyle::Block);
+    }
 }
@@ -27,6 +27,10 @@ pub enum Error {
     #[error("Timeout waiting for condition after {0}ms")]
     WaitTimeout(u64),
 
+    /// Operation cancelled by the caller
+    #[error("Operation cancelled")]
+    Cancelled,
+
     /// Invalid key string
     #[error("Invalid key: {0}")]
     InvalidKey(String),
@@ -106,6 +110,11 @@ mod tests {
         assert_eq!(err.to_string(), "Command not allowed: rm -rf /");
     }
 
+    #[test]
+    fn test_cancelled_error() {
+        assert_eq!(Error::Cancelled.to_string(), "Operation cancelled");
+    }
+
     #[test]
     fn test_wait_timeout_error() {
         let err = Error::WaitTimeout(5000);
@@ -0,0 +1,80 @@
+//! Bell and notification events raised by terminal applications.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A desktop notification requested with OSC 9 or OSC 777.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Notification {
+    /// Title (OSC 777 only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Message body
+    pub body: String,
+    /// When the notification was received (RFC 3339)
+    pub timestamp: String,
+}
+
+/// A single event, as published while output is processed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TerminalEvent {
+    /// The application rang the bell (BEL)
+    Bell {
+        /// When the bell rang (RFC 3339)
+        timestamp: String,
+    },
+    /// The application sent a desktop notification
+    Notification(Notification),
+}
+
+/// Bells and notifications received over the life of a session.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TerminalEvents {
+    /// Number of bells rung
+    #[serde(default)]
+    pub bell_count: u64,
+    /// When the last bell rang (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_bell: Option<String>,
+    /// Number of notifications received, including ones no longer kept
+    #[serde(default)]
+    pub notification_count: u64,
+    /// Most recent notifications, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notifications: Vec<Notification>,
+}
+
+impl TerminalEvents {
+    /// Whether nothing has been received.
+    pub fn is_empty(&self) -> bool {
+        self.bell_count == 0 && self.notification_count == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_event_serialization() {
+        let event = TerminalEvent::Notification(Notification {
+            title: None,
+            body: "build done".to_string(),
+            timestamp: "2025-01-01T00:00:00+00:00".to_string(),
+        });
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["kind"], "notification");
+        assert_eq!(json["body"], "build done");
+        assert!(json.get("title").is_none());
+
+        let bell: TerminalEvent =
+            serde_json::from_str(r#"{"kind":"bell","timestamp":"t"}"#).unwrap();
+        assert_eq!(
+            bell,
+            TerminalEvent::Bell {
+                timestamp: "t".to_string()
+            }
+        );
+    }
+}
@@ -82,6 +82,75 @@ pub enum Key {
     Shift(Box<Key>),
     /// Ctrl + Alt + character
     CtrlAlt(char),
+    /// Any key with an arbitrary combination of modifiers (e.g. Ctrl+Shift+a, Ctrl+Enter)
+    Modified {
+        /// Unmodified key
+        key: Box<Key>,
+        /// Modifiers held down
+        modifiers: Modifiers,
+    },
+}
+
+/// Modifier keys held while pressing a key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Modifiers {
+    /// Shift held
+    #[serde(default)]
+    pub shift: bool,
+    /// Alt (Meta) held
+    #[serde(default)]
+    pub alt: bool,
+    /// Ctrl held
+    #[serde(default)]
+    pub ctrl: bool,
+}
+
+impl Modifiers {
+    /// Whether no modifier is held.
+    pub fn is_empty(&self) -> bool {
+        !(self.shift || self.alt || self.ctrl)
+    }
+
+    /// Modifier parameter as used by xterm and the kitty protocol (1 + bitmask).
+    pub fn param(&self) -> u8 {
+        1 + self.shift as u8 + (self.alt as u8) * 2 + (self.ctrl as u8) * 4
+    }
+
+    fn union(self, other: Modifiers) -> Modifiers {
+        Modifiers {
+            shift: self.shift || other.shift,
+            alt: self.alt || other.alt,
+            ctrl: self.ctrl || other.ctrl,
+        }
+    }
+}
+
+/// Keyboard reporting modes negotiated by the application running in a terminal.
+///
+/// The default value selects legacy xterm encodings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct KeyboardMode {
+    /// Active kitty keyboard protocol flags (`CSI > flags u`), 0 when disabled
+    pub kitty_flags: u8,
+    /// xterm modifyOtherKeys level (`CSI > 4 ; level m`), 0 when disabled
+    pub modify_other_keys: u8,
+}
+
+impl KeyboardMode {
+    /// Kitty flag: disambiguate escape codes.
+    pub const KITTY_DISAMBIGUATE: u8 = 0b1;
+    /// Kitty flag: report all keys as escape codes.
+    pub const KITTY_REPORT_ALL_KEYS: u8 = 0b1000;
+
+    /// Whether the kitty disambiguation flag is set.
+    pub fn kitty_disambiguate(&self) -> bool {
+        self.kitty_flags & Self::KITTY_DISAMBIGUATE != 0
+    }
+
+    /// Whether the kitty "report all keys as escape codes" flag is set.
+    pub fn kitty_report_all(&self) -> bool {
+        self.kitty_flags & Self::KITTY_REPORT_ALL_KEYS != 0
+    }
 }
 
 impl Key {
@@ -96,28 +165,47 @@ impl Key {
     pub fn parse(s: &str) -> Result<Self> {
         let s = s.trim();
 
-        // Handle modifiers
-        if let Some(rest) = s.strip_prefix("Ctrl+") {
-            let ch = rest
-                .chars()
-                .next()
-                .ok_or_else(|| Error::InvalidInput(format!("Invalid Ctrl+ key: {s}")))?;
-            return Ok(Key::Ctrl(ch.to_ascii_lowercase()));
+        // Handle modifiers (any order, e.g. "Ctrl+Shift+a")
+        let mut modifiers = Modifiers::default();
+        let mut rest = s;
+        loop {
+            if let Some(r) = rest.strip_prefix("Ctrl+") {
+                modifiers.ctrl = true;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("Alt+") {
+                modifiers.alt = true;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("Shift+") {
+                modifiers.shift = true;
+                rest = r;
+            } else {
+                break;
+            }
         }
 
-        if let Some(rest) = s.strip_prefix("Alt+") {
-            let ch = rest
-                .chars()
-                .next()
-                .ok_or_else(|| Error::InvalidInput(format!("Invalid Alt+ key: {s}")))?;
-            return Ok(Key::Alt(ch));
+        if modifiers.is_empty() {
+            return Self::parse_unmodified(s);
         }
-
-        if let Some(rest) = s.strip_prefix("Shift+") {
-            let inner = Key::parse(rest)?;
-            return Ok(Key::Shift(Box::new(inner)));
+        if rest.is_empty() {
+            return Err(Error::InvalidInput(format!("Invalid modified key: {s}")));
         }
 
+        let base = Self::parse_unmodified(rest)?;
+        let Modifiers { shift, alt, ctrl } = modifiers;
+        Ok(match (base, shift, alt, ctrl) {
+            (Key::Char(c), false, false, true) => Key::Ctrl(c.to_ascii_lowercase()),
+            (Key::Char(c), false, true, false) => Key::Alt(c),
+            (Key::Char(c), false, true, true) => Key::CtrlAlt(c.to_ascii_lowercase()),
+            (base, true, false, false) => Key::Shift(Box::new(base)),
+            (base, _, _, _) => Key::Modified {
+                key: Box::new(base),
+                modifiers,
+            },
+        })
+    }
+
+    /// Parse a key name without modifier prefixes.
+    fn parse_unmodified(s: &str) -> Result<Self> {
         // Handle named keys
         match s {
             "Enter" | "Return" => Ok(Key::Enter),
@@ -149,8 +237,9 @@ impl Key {
             "F12" => Ok(Key::F12),
             _ => {
                 // Single character
-                if s.len() == 1 {
-                    Ok(Key::Char(s.chars().next().unwrap()))
+                let mut chars = s.chars();
+                if let (Some(c), None) = (chars.next(), chars.next()) {
+                    Ok(Key::Char(c))
                 } else {
                     Err(Error::InvalidInput(format!("Unknown key: {s}")))
                 }
@@ -158,68 +247,221 @@ impl Key {
         }
     }
 
-    /// Convert key to terminal escape sequence bytes.
+    /// Convert key to terminal escape sequence bytes using legacy xterm encodings.
     pub fn to_escape_sequence(&self) -> Vec<u8> {
-        match self {
-            Key::Char(c) => c.to_string().into_bytes(),
-            Key::Enter => vec![0x0D], // CR
-            Key::Tab => vec![0x09],
-            Key::Escape => vec![0x1B],
-            Key::Backspace => vec![0x7F],
-            Key::Delete => b"\x1b[3~".to_vec(),
-            Key::Space => vec![0x20],
-            Key::Insert => b"\x1b[2~".to_vec(),
-            Key::Up => b"\x1b[A".to_vec(),
-            Key::Down => b"\x1b[B".to_vec(),
-            Key::Right => b"\x1b[C".to_vec(),
-            Key::Left => b"\x1b[D".to_vec(),
-            Key::Home => b"\x1b[H".to_vec(),
-            Key::End => b"\x1b[F".to_vec(),
-            Key::PageUp => b"\x1b[5~".to_vec(),
-            Key::PageDown => b"\x1b[6~".to_vec(),
-            Key::F1 => b"\x1bOP".to_vec(),
-            Key::F2 => b"\x1bOQ".to_vec(),
-            Key::F3 => b"\x1bOR".to_vec(),
-            Key::F4 => b"\x1bOS".to_vec(),
-            Key::F5 => b"\x1b[15~".to_vec(),
-            Key::F6 => b"\x1b[17~".to_vec(),
-            Key::F7 => b"\x1b[18~".to_vec(),
-            Key::F8 => b"\x1b[19~".to_vec(),
-            Key::F9 => b"\x1b[20~".to_vec(),
-            Key::F10 => b"\x1b[21~".to_vec(),
-            Key::F11 => b"\x1b[23~".to_vec(),
-            Key::F12 => b"\x1b[24~".to_vec(),
-            Key::Ctrl(c) => {
-                // Ctrl+A = 0x01, Ctrl+Z = 0x1A
-                let code = (*c as u8).to_ascii_lowercase() - b'a' + 1;
-                vec![code]
+        self.encode(KeyboardMode::default())
+    }
+
+    /// Convert key to bytes for a terminal in the given keyboard mode.
+    ///
+    /// When the application enabled the kitty keyboard protocol or
+    /// modifyOtherKeys, combinations that are ambiguous in the legacy encoding
+    /// (Ctrl+Enter, Ctrl+Shift+letter, a bare Escape, ...) are sent in their
+    /// disambiguated `CSI ... u` / `CSI 27 ; ... ~` forms.
+    pub fn encode(&self, mode: KeyboardMode) -> Vec<u8> {
+        let (base, mods) = self.normalize();
+
+        if let Some(code) = base.text_codepoint() {
+            if mode.kitty_report_all() || (mode.kitty_disambiguate() && base.kitty_ambiguous(mods))
+            {
+                return csi_u(code, mods);
             }
-            Key::Alt(c) => {
-                // Alt sends ESC prefix
-                let mut seq = vec![0x1B];
-                seq.extend(c.to_string().bytes());
-                seq
+            if mode.modify_other_keys > 0
+                && !mods.is_empty()
+                && (mode.modify_other_keys >= 2 || base.legacy_ambiguous(mods))
+                && !(mods
+                    == Modifiers {
+                        shift: true,
+                        ..Default::default()
+                    }
+                    && matches!(base, Key::Char(_) | Key::Space))
+            {
+                return format!("\x1b[27;{};{}~", mods.param(), code).into_bytes();
             }
+        }
+
+        base.legacy_sequence(mods)
+    }
+
+    /// Split the key into an unmodified base key and its modifiers.
+    fn normalize(&self) -> (Key, Modifiers) {
+        match self {
+            Key::Ctrl(c) => (
+                Key::Char(*c),
+                Modifiers {
+                    ctrl: true,
+                    ..Default::default()
+                },
+            ),
+            Key::Alt(c) => (
+                Key::Char(*c),
+                Modifiers {
+                    alt: true,
+                    ..Default::default()
+                },
+            ),
+            Key::CtrlAlt(c) => (
+                Key::Char(*c),
+                Modifiers {
+                    ctrl: true,
+                    alt: true,
+                    ..Default::default()
+                },
+            ),
             Key::Shift(inner) => {
-                // Shift modifies escape sequences
-                match inner.as_ref() {
-                    Key::Tab => b"\x1b[Z".to_vec(), // Shift+Tab
-                    Key::Up => b"\x1b[1;2A".to_vec(),
-                    Key::Down => b"\x1b[1;2B".to_vec(),
-                    Key::Right => b"\x1b[1;2C".to_vec(),
-                    Key::Left => b"\x1b[1;2D".to_vec(),
-                    _ => inner.to_escape_sequence(),
+                let (base, mods) = inner.normalize();
+                (
+                    base,
+                    mods.union(Modifiers {
+                        shift: true,
+                        ..Default::default()
+                    }),
+                )
+            }
+            Key::Modified { key, modifiers } => {
+                let (base, mods) = key.normalize();
+                (base, mods.union(*modifiers))
+            }
+            Key::Char(c) if c.is_ascii_uppercase() => (
+                Key::Char(c.to_ascii_lowercase()),
+                Modifiers {
+                    shift: true,
+                    ..Default::default()
+                },
+            ),
+            other => (other.clone(), Modifiers::default()),
+        }
+    }
+
+    /// Unicode codepoint for keys reported as `CSI code u` by the kitty protocol.
+    fn text_codepoint(&self) -> Option<u32> {
+        match self {
+            Key::Char(c) => Some(*c as u32),
+            Key::Space => Some(32),
+            Key::Enter => Some(13),
+            Key::Tab => Some(9),
+            Key::Backspace => Some(127),
+            Key::Escape => Some(27),
+            _ => None,
+        }
+    }
+
+    /// Whether the kitty disambiguate flag changes the encoding of this key.
+    fn kitty_ambiguous(&self, mods: Modifiers) -> bool {
+        match self {
+            Key::Escape => true,
+            Key::Char(_) | Key::Space => mods.ctrl || mods.alt,
+            _ => !mods.is_empty(),
+        }
+    }
+
+    /// Whether the legacy encoding loses information for this combination.
+    fn legacy_ambiguous(&self, mods: Modifiers) -> bool {
+        match self {
+            Key::Char(c) => mods.ctrl && (mods.shift || !c.is_ascii_alphabetic()),
+            Key::Tab => mods.ctrl || (mods.shift && mods.alt),
+            _ => mods.ctrl || mods.shift,
+        }
+    }
+
+    /// Legacy xterm encoding of an unmodified base key with modifiers applied.
+    fn legacy_sequence(&self, mods: Modifiers) -> Vec<u8> {
+        let m = mods.param();
+        let with_alt = |mut bytes: Vec<u8>| {
+            if mods.alt {
+                bytes.insert(0, 0x1B);
+            }
+            bytes
+        };
+        // Cursor and function keys: SS3/CSI final byte, or `CSI code ~`
+        let csi_final = |unmodified: &[u8], fin: char| {
+            if mods.is_empty() {
+                unmodified.to_vec()
+            } else {
+                format!("\x1b[1;{m}{fin}").into_bytes()
+            }
+        };
+        let csi_tilde = |code: u8| {
+            if mods.is_empty() {
+                format!("\x1b[{code}~").into_bytes()
+            } else {
+                format!("\x1b[{code};{m}~").into_bytes()
+            }
+        };
+
+        match self {
+            Key::Char(c) => {
+                let c = if mods.shift {
+                    c.to_ascii_uppercase()
+                } else {
+                    *c
+                };
+                if mods.ctrl {
+                    with_alt(vec![ctrl_code(c).unwrap_or(c as u8)])
+                } else {
+                    with_alt(c.to_string().into_bytes())
                 }
             }
-            Key::CtrlAlt(c) => {
-                // Ctrl+Alt sends ESC + Ctrl code
-                let code = (*c as u8).to_ascii_lowercase() - b'a' + 1;
-                vec![0x1B, code]
+            Key::Space => with_alt(vec![if mods.ctrl { 0x00 } else { 0x20 }]),
+            Key::Enter => with_alt(vec![0x0D]), // CR
+            Key::Tab if mods.shift => with_alt(b"\x1b[Z".to_vec()),
+            Key::Tab => with_alt(vec![0x09]),
+            Key::Escape => with_alt(vec![0x1B]),
+            Key::Backspace => with_alt(vec![if mods.ctrl { 0x08 } else { 0x7F }]),
+            Key::Up => csi_final(b"\x1b[A", 'A'),
+            Key::Down => csi_final(b"\x1b[B", 'B'),
+            Key::Right => csi_final(b"\x1b[C", 'C'),
+            Key::Left => csi_final(b"\x1b[D", 'D'),
+            Key::Home => csi_final(b"\x1b[H", 'H'),
+            Key::End => csi_final(b"\x1b[F", 'F'),
+            Key::F1 => csi_final(b"\x1bOP", 'P'),
+            Key::F2 => csi_final(b"\x1bOQ", 'Q'),
+            Key::F3 => csi_final(b"\x1bOR", 'R'),
+            Key::F4 => csi_final(b"\x1bOS", 'S'),
+            Key::Insert => csi_tilde(2),
+            Key::Delete => csi_tilde(3),
+            Key::PageUp => csi_tilde(5),
+            Key::PageDown => csi_tilde(6),
+            Key::F5 => csi_tilde(15),
+            Key::F6 => csi_tilde(17),
+            Key::F7 => csi_tilde(18),
+            Key::F8 => csi_tilde(19),
+            Key::F9 => csi_tilde(20),
+            Key::F10 => csi_tilde(21),
+            Key::F11 => csi_tilde(23),
+            Key::F12 => csi_tilde(24),
+            // normalize() never yields modified variants as a base key
+            Key::Ctrl(_) | Key::Alt(_) | Key::Shift(_) | Key::CtrlAlt(_) | Key::Modified { .. } => {
+                self.to_escape_sequence()
             }
         }
     }
 }
 
+/// Kitty protocol `CSI code ; modifiers u` sequence.
+fn csi_u(code: u32, mods: Modifiers) -> Vec<u8> {
+    if mods.is_empty() {
+        format!("\x1b[{code}u").into_bytes()
+    } else {
+        format!("\x1b[{code};{}u", mods.param()).into_bytes()
+    }
+}
+
+/// C0 control code produced by Ctrl+character (Ctrl+A = 0x01, Ctrl+[ = ESC, ...).
+fn ctrl_code(c: char) -> Option<u8> {
+    match c.to_ascii_lowercase() {
+        c @ 'a'..='z' => Some(c as u8 - b'a' + 1),
+        '@' | ' ' | '2' => Some(0x00),
+        '[' | '3' => Some(0x1B),
+        '\\' | '4' => Some(0x1C),
+        ']' | '5' => Some(0x1D),
+        '^' | '6' => Some(0x1E),
+        '_' | '/' | '7' => Some(0x1F),
+        '?' | '8' => Some(0x7F),
+        _ => None,
+    }
+}
+
 impl std::fmt::Display for Key {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -255,6 +497,18 @@ impl std::fmt::Display for Key {
             Key::Alt(c) => write!(f, "Alt+{c}"),
             Key::Shift(k) => write!(f, "Shift+{k}"),
             Key::CtrlAlt(c) => write!(f, "Ctrl+Alt+{c}"),
+            Key::Modified { key, modifiers } => {
+                if modifiers.ctrl {
+                    write!(f, "Ctrl+")?;
+                }
+                if modifiers.alt {
+                    write!(f, "Alt+")?;
+                }
+                if modifiers.shift {
+                    write!(f, "Shift+")?;
+                }
+                write!(f, "{key}")
+            }
         }
     }
 }
@@ -404,4 +658,102 @@ mod tests {
             let _ = key.to_escape_sequence();
         }
     }
+
+    #[test]
+    fn test_key_parse_combined_modifiers() {
+        assert_eq!(Key::parse("Ctrl+Alt+x").unwrap(), Key::CtrlAlt('x'));
+        assert_eq!(
+            Key::parse("Ctrl+Enter").unwrap(),
+            Key::Modified {
+                key: Box::new(Key::Enter),
+                modifiers: Modifiers {
+                    ctrl: true,
+                    ..Default::default()
+                },
+            }
+        );
+        let key = Key::parse("Ctrl+Shift+a").unwrap();
+        assert_eq!(key.to_string(), "Ctrl+Shift+a");
+        assert!(Key::parse("Ctrl+Shift+").is_err());
+    }
+
+    #[test]
+    fn test_key_legacy_modified_sequences() {
+        // Ctrl+Enter is indistinguishable from Enter in legacy mode
+        assert_eq!(
+            Key::parse("Ctrl+Enter").unwrap().to_escape_sequence(),
+            vec![0x0D]
+        );
+        assert_eq!(
+            Key::parse("Ctrl+Up").unwrap().to_escape_sequence(),
+            b"\x1b[1;5A"
+        );
+        assert_eq!(
+            Key::parse("Alt+F5").unwrap().to_escape_sequence(),
+            b"\x1b[15;3~"
+        );
+        assert_eq!(
+            Key::parse("Ctrl+[").unwrap().to_escape_sequence(),
+            vec![0x1B]
+        );
+    }
+
+    #[test]
+    fn test_key_encode_kitty_disambiguate() {
+        let mode = KeyboardMode {
+            kitty_flags: KeyboardMode::KITTY_DISAMBIGUATE,
+            ..Default::default()
+        };
+        assert_eq!(Key::Escape.encode(mode), b"\x1b[27u");
+        assert_eq!(Key::Ctrl('c').encode(mode), b"\x1b[99;5u");
+        assert_eq!(
+            Key::parse("Ctrl+Enter").unwrap().encode(mode),
+            b"\x1b[13;5u"
+        );
+        assert_eq!(
+            Key::parse("Ctrl+Shift+a").unwrap().encode(mode),
+            b"\x1b[97;6u"
+        );
+        // Plain text and unmodified Enter stay legacy
+        assert_eq!(Key::Char('a').encode(mode), b"a");
+        assert_eq!(Key::Enter.encode(mode), vec![0x0D]);
+        assert_eq!(Key::Up.encode(mode), b"\x1b[A");
+    }
+
+    #[test]
+    fn test_key_encode_kitty_report_all() {
+        let mode = KeyboardMode {
+            kitty_flags: KeyboardMode::KITTY_DISAMBIGUATE | KeyboardMode::KITTY_REPORT_ALL_KEYS,
+            ..Default::default()
+        };
+        assert_eq!(Key::Char('a').encode(mode), b"\x1b[97u");
+        assert_eq!(Key::Char('A').encode(mode), b"\x1b[97;2u");
+        assert_eq!(Key::Enter.encode(mode), b"\x1b[13u");
+    }
+
+    #[test]
+    fn test_key_encode_modify_other_keys() {
+        let level1 = KeyboardMode {
+            modify_other_keys: 1,
+            ..Default::default()
+        };
+        // Well-known control characters keep their legacy form at level 1
+        assert_eq!(Key::Ctrl('c').encode(level1), vec![0x03]);
+        assert_eq!(
+            Key::parse("Ctrl+Enter").unwrap().encode(level1),
+            b"\x1b[27;5;13~"
+        );
+        assert_eq!(
+            Key::parse("Ctrl+Shift+a").unwrap().encode(level1),
+            b"\x1b[27;6;97~"
+        );
+
+        let level2 = KeyboardMode {
+            modify_other_keys: 2,
+            ..Default::default()
+        };
+        assert_eq!(Key::Ctrl('c').encode(level2), b"\x1b[27;5;99~");
+        assert_eq!(Key::Shift(Box::new(Key::Char('a'))).encode(level2), b"A");
+        assert_eq!(Key::Escape.encode(level2), vec![0x1B]);
+    }
 }
@@ -10,6 +10,8 @@
 //! - Cell and color types for terminal grid
 //! - Element types for Terminal State Tree (TST)
 //! - Key types for input handling
+//! - Bell and notification events
+//! - Command policy and secret redaction
 //! - Error types
 //!
 //! ## Architecture
@@ -25,20 +27,29 @@ pub mod cell;
 pub mod config;
 pub mod element;
 pub mod error;
+pub mod event;
 pub mod geometry;
 pub mod key;
 pub mod platform;
+pub mod policy;
+pub mod redact;
 pub mod session;
 
 // Re-export commonly used types
 pub use cell::{Cell, CellAttributes, Color};
 pub use config::{
-    CaptureConfig, CustomPatternConfig, DetectionSettings, SecuritySettings, ServerConfig,
-    ServerSettings, TerminalSettings,
+    CaptureConfig, CustomPatternConfig, DetectionSettings, HttpSettings, LimitKind, ResourceLimits,
+    SandboxSettings, SecuritySettings, ServerConfig, ServerSettings, TerminalSettings,
+    UnixSocketSettings,
+};
+pub use element::{
+    CursorStyle, Element, InputMode, MenuItem, RowStyles, StyleRun, TerminalStateTree, TermiosFlags,
 };
-pub use element::{Element, MenuItem, TerminalStateTree};
 pub use error::{Error, Result};
+pub use event::{Notification, TerminalEvent, TerminalEvents};
 pub use geometry::{Bounds, Dimensions, Position};
-pub use key::Key;
-pub use platform::Platform;
+pub use key::{Key, KeyboardMode, Modifiers};
+pub use platform::{runtime_dir, Platform};
+pub use policy::{PendingLine, Policy, PolicyAction, PolicyDecision, PolicyRule, PolicySettings};
+pub use redact::{RedactStream, RedactionSettings, Redactor};
 pub use session::{SessionConfig, SessionId, SessionInfo, SessionStatus};
@@ -1,7 +1,10 @@
 //! Platform detection for cross-platform terminal emulator support.
 //!
 //! This module provides runtime platform detection to enable
-//! platform-specific terminal emulator selection.
+//! platform-specific terminal emulator selection, and the per-user runtime
+//! directory for sockets and token files.
+
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -113,10 +116,76 @@ impl std::fmt::Display for Platform {
     }
 }
 
+/// Directory for sockets and token files that only the current user can
+/// reach.
+///
+/// `$XDG_RUNTIME_DIR` when set; otherwise `terminal-mcp-<uid>` in the temp
+/// directory. The temp directory is world-writable, so that fallback is
+/// created with mode `0700` and refused unless the current user owns it and
+/// nobody else has access.
+pub fn runtime_dir() -> crate::Result<PathBuf> {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => Ok(dir.into()),
+        None => private_dir(&std::env::temp_dir()),
+    }
+}
+
+/// Create or check `terminal-mcp-<uid>` under `base`.
+#[cfg(unix)]
+fn private_dir(base: &Path) -> crate::Result<PathBuf> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let uid = unsafe { libc::geteuid() };
+    let dir = base.join(format!("terminal-mcp-{uid}"));
+    if let Err(e) = std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+        if e.kind() != std::io::ErrorKind::AlreadyExists {
+            return Err(e.into());
+        }
+    }
+    let metadata = std::fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.permissions().mode() & 0o077 != 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!(
+                "{} must be a directory owned by uid {uid} with mode 0700",
+                dir.display()
+            ),
+        )
+        .into());
+    }
+    Ok(dir)
+}
+
+/// The temp directory is already per-user.
+#[cfg(not(unix))]
+fn private_dir(base: &Path) -> crate::Result<PathBuf> {
+    Ok(base.to_path_buf())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    #[cfg(unix)]
+    fn test_private_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let base = std::env::temp_dir().join(format!("runtime-dir-{}", std::process::id()));
+        std::fs::create_dir_all(&base).unwrap();
+        let dir = private_dir(&base).unwrap();
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        assert_eq!(private_dir(&base).unwrap(), dir);
+
+        // Someone else could have swapped files in there
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(private_dir(&base).is_err());
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
     #[test]
     fn test_platform_detect() {
         let platform = Platform::detect();
@@ -0,0 +1,643 @@
+//! Command policy.
+//!
+//! A policy is an ordered list of allow/deny rules matched against the
+//! program, its arguments and the working directory. It is checked when a
+//! session starts and against command lines typed into shell sessions; the
+//! first matching rule decides, and the default action applies otherwise.
+
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// What a policy rule does with a matching command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    /// Let the command run
+    #[default]
+    Allow,
+    /// Refuse the command
+    Deny,
+}
+
+/// One policy rule; a rule without conditions matches every command.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicyRule {
+    /// Action taken when the rule matches
+    pub action: PolicyAction,
+    /// Glob for the program (`*`, `?`, `**`), matched against its resolved
+    /// path and, for patterns without `/`, against its file name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program: Option<String>,
+    /// Regex searched for in the arguments joined by spaces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args: Option<String>,
+    /// Glob for the working directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Explanation returned when the rule denies a command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Policy settings from the config file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicySettings {
+    /// Action when no rule matches
+    pub default_action: PolicyAction,
+    /// Rules, first match wins
+    pub rules: Vec<PolicyRule>,
+    /// Regexes for typed command lines that are always refused
+    pub denied_input: Vec<String>,
+    /// Programs whose typed input is checked as shell command lines
+    pub shells: Vec<String>,
+    /// JSONL file every decision and every input is appended to
+    pub audit_log: Option<String>,
+}
+
+impl Default for PolicySettings {
+    fn default() -> Self {
+        Self {
+            default_action: PolicyAction::Allow,
+            rules: vec![],
+            denied_input: vec![],
+            shells: [
+                "sh", "bash", "zsh", "fish", "dash", "ksh", "mksh", "ash", "tcsh", "csh", "nu",
+                "pwsh",
+            ]
+            .iter()
+            .map(|shell| shell.to_string())
+            .collect(),
+            audit_log: None,
+        }
+    }
+}
+
+/// Outcome of a policy check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PolicyDecision {
+    /// Whether the command may run
+    pub allowed: bool,
+    /// Index of the rule that decided, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<usize>,
+    /// Why the command was refused
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl PolicyDecision {
+    fn allow(rule: Option<usize>) -> Self {
+        Self {
+            allowed: true,
+            rule,
+            reason: None,
+        }
+    }
+
+    fn deny(rule: Option<usize>, reason: String) -> Self {
+        Self {
+            allowed: false,
+            rule,
+            reason: Some(reason),
+        }
+    }
+
+    /// Turn a refusal into [`Error::CommandNotAllowed`].
+    pub fn into_result(self) -> Result<()> {
+        match self.reason {
+            Some(reason) if !self.allowed => Err(Error::CommandNotAllowed(reason)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    action: PolicyAction,
+    program: Option<(Regex, bool)>,
+    args: Option<Regex>,
+    cwd: Option<Regex>,
+    reason: Option<String>,
+}
+
+/// A compiled command policy.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    default_action: PolicyAction,
+    allowed_commands: Vec<String>,
+    rules: Vec<CompiledRule>,
+    denied_input: Vec<Regex>,
+    shells: Vec<String>,
+}
+
+impl Policy {
+    /// Compile policy settings.
+    ///
+    /// A non-empty `allowed_commands` list is checked before the rules: a
+    /// program that is not on it, by name or path, is always refused.
+    pub fn new(settings: &PolicySettings, allowed_commands: &[String]) -> Result<Self> {
+        let regex = |pattern: &str| {
+            Regex::new(pattern)
+                .map_err(|e| Error::Config(format!("Invalid policy regex '{pattern}': {e}")))
+        };
+
+        let rules = settings
+            .rules
+            .iter()
+            .map(|rule| {
+                Ok(CompiledRule {
+                    action: rule.action,
+                    program: match &rule.program {
+                        Some(glob) => Some((regex(&glob_to_regex(glob))?, !glob.contains('/'))),
+                        None => None,
+                    },
+                    args: rule.args.as_deref().map(regex).transpose()?,
+                    cwd: match &rule.cwd {
+                        Some(glob) => Some(regex(&glob_to_regex(glob))?),
+                        None => None,
+                    },
+                    reason: rule.reason.clone(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        let denied_input = settings
+            .denied_input
+            .iter()
+            .map(|pattern| regex(pattern))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            default_action: settings.default_action,
+            allowed_commands: allowed_commands.to_vec(),
+            rules,
+            denied_input,
+            shells: settings.shells.clone(),
+        })
+    }
+
+    /// Check a program about to be started.
+    ///
+    /// The script of a shell started with `-c` is checked like typed input.
+    pub fn check_command(&self, program: &str, args: &[String], cwd: &Path) -> PolicyDecision {
+        let decision = self.check_program(program, args, cwd);
+        if !decision.allowed || !self.is_shell(program) {
+            return decision;
+        }
+        match shell_script(args) {
+            Some(script) => {
+                let script_decision = self.check_input(program, script, cwd);
+                if script_decision.allowed {
+                    decision
+                } else {
+                    script_decision
+                }
+            }
+            None => decision,
+        }
+    }
+
+    /// Match a program and its arguments against the rules.
+    fn check_program(&self, program: &str, args: &[String], cwd: &Path) -> PolicyDecision {
+        let name = file_name(program);
+        let path = resolve_program(program);
+        let path = path
+            .as_deref()
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|| program.to_string());
+
+        if !self.allowed_commands.is_empty()
+            && !self
+                .allowed_commands
+                .iter()
+                .any(|allowed| allowed == program || allowed == name || *allowed == path)
+        {
+            return PolicyDecision::deny(
+                None,
+                format!("'{program}' is not in security.allowed_commands"),
+            );
+        }
+
+        let joined = args.join(" ");
+        let cwd = cwd.to_string_lossy();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let program_matches = rule.program.as_ref().map_or(true, |(glob, by_name)| {
+                glob.is_match(&path) || (*by_name && glob.is_match(name))
+            });
+            if program_matches
+                && rule.args.as_ref().map_or(true, |re| re.is_match(&joined))
+                && rule.cwd.as_ref().map_or(true, |re| re.is_match(&cwd))
+            {
+                return match rule.action {
+                    PolicyAction::Allow => PolicyDecision::allow(Some(index)),
+                    PolicyAction::Deny => PolicyDecision::deny(
+                        Some(index),
+                        rule.reason.clone().unwrap_or_else(|| {
+                            format!("'{program}' is denied by policy rule {index}")
+                        }),
+                    ),
+                };
+            }
+        }
+
+        match self.default_action {
+            PolicyAction::Allow => PolicyDecision::allow(None),
+            PolicyAction::Deny => PolicyDecision::deny(
+                None,
+                format!("'{program}' is not allowed by any policy rule"),
+            ),
+        }
+    }
+
+    /// Check text typed into a session running `session_command`.
+    ///
+    /// Only shells are inspected. Each line is matched against
+    /// `denied_input`, and every command in it (split at `;`, `&&`, `||`,
+    /// `|` and `&`, after leading `VAR=value` assignments) against the rules.
+    pub fn check_input(&self, session_command: &str, text: &str, cwd: &Path) -> PolicyDecision {
+        if !self.is_shell(session_command) {
+            return PolicyDecision::allow(None);
+        }
+
+        for line in text.lines() {
+            if let Some(pattern) = self.denied_input.iter().find(|re| re.is_match(line)) {
+                return PolicyDecision::deny(
+                    None,
+                    format!("Input matches denied pattern '{}'", pattern.as_str()),
+                );
+            }
+            for words in split_command_line(line) {
+                let Some((program, args)) = words.split_first() else {
+                    continue;
+                };
+                let decision = self.check_command(program, args, cwd);
+                if !decision.allowed {
+                    return decision;
+                }
+            }
+        }
+        PolicyDecision::allow(None)
+    }
+
+    /// Whether typed input to `command` is checked as shell command lines.
+    pub fn is_shell(&self, command: &str) -> bool {
+        let name = file_name(command);
+        self.shells.iter().any(|shell| shell == name)
+    }
+}
+
+/// The command line being typed into a shell.
+///
+/// Input arrives in pieces over several tool calls, so it is collected here
+/// and each line is checked whole once it is submitted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PendingLine {
+    text: String,
+}
+
+impl PendingLine {
+    /// Text typed since the last submitted line.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Apply typed input and return the lines it submits.
+    ///
+    /// CR, LF and the kitty and modifyOtherKeys encodings of Enter submit
+    /// the line. Backspace and DEL delete a character, Ctrl+C and Ctrl+U
+    /// discard the line, and other escape sequences are ignored.
+    pub fn feed(&mut self, input: &str) -> Vec<String> {
+        let mut submitted = vec![];
+        let mut chars = input.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' | '\n' => submitted.push(std::mem::take(&mut self.text)),
+                '\x08' | '\x7f' => {
+                    self.text.pop();
+                }
+                '\x03' | '\x15' => self.text.clear(),
+                '\x1b' => {
+                    if escape_is_enter(&mut chars) {
+                        submitted.push(std::mem::take(&mut self.text));
+                    }
+                }
+                c if c.is_control() && c != '\t' => {}
+                c => self.text.push(c),
+            }
+        }
+        submitted
+    }
+}
+
+/// Consume an escape sequence after ESC, returning whether it encodes Enter.
+fn escape_is_enter(chars: &mut Peekable<Chars>) -> bool {
+    match chars.next() {
+        Some('[') => {
+            let mut params = String::new();
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    let params: Vec<&str> = params.split(';').collect();
+                    // CSI 13 u (kitty) or CSI 27 ; mods ; 13 ~ (modifyOtherKeys)
+                    return match c {
+                        'u' => params[0] == "13",
+                        '~' => params.len() == 3 && params[0] == "27" && params[2] == "13",
+                        _ => false,
+                    };
+                }
+                params.push(c);
+            }
+            false
+        }
+        Some('O') => {
+            // SS3 keypad Enter
+            chars.next() == Some('M')
+        }
+        _ => false,
+    }
+}
+
+/// Script of a shell invocation such as `sh -c '<script>'` or `bash -lc`.
+fn shell_script(args: &[String]) -> Option<&str> {
+    let flag = args.iter().position(|arg| {
+        arg.len() > 1 && arg.starts_with('-') && !arg.starts_with("--") && arg.contains('c')
+    })?;
+    args[flag + 1..]
+        .iter()
+        .find(|arg| !arg.starts_with('-'))
+        .map(String::as_str)
+}
+
+/// File name of a program path.
+fn file_name(program: &str) -> &str {
+    program.rsplit('/').next().unwrap_or(program)
+}
+
+/// Find a program on `PATH` the way the shell would.
+pub fn resolve_program(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        return Some(PathBuf::from(program));
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Anchored regex for a glob: `**` matches across `/`, `*` and `?` don't.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+/// Finish the current word, skipping `VAR=value` before the program.
+fn end_word(word: &mut String, in_word: &mut bool, words: &mut Vec<String>) {
+    if !*in_word {
+        return;
+    }
+    let assignment = words.is_empty()
+        && word.split_once('=').is_some_and(|(name, _)| {
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    if !assignment {
+        words.push(word.clone());
+    }
+    word.clear();
+    *in_word = false;
+}
+
+/// Split a shell command line into the words of each simple command.
+///
+/// Handles single and double quotes, backslash escapes, the separators
+/// `;`, `&&`, `||`, `|` and `&`, and drops leading `VAR=value` words. This
+/// is a screening aid, not a shell parser: expansions are left as they are.
+pub fn split_command_line(line: &str) -> Vec<Vec<String>> {
+    let mut commands = vec![];
+    let mut words: Vec<String> = vec![];
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => {
+                if let Some(next) = chars.next() {
+                    word.push(next);
+                }
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, '\\') => {
+                if let Some(next) = chars.next() {
+                    word.push(next);
+                    in_word = true;
+                }
+            }
+            (None, ';' | '|' | '&' | '\n') => {
+                end_word(&mut word, &mut in_word, &mut words);
+                if matches!(chars.peek(), Some(&next) if next == c && c != ';') {
+                    chars.next();
+                }
+                if !words.is_empty() {
+                    commands.push(std::mem::take(&mut words));
+                }
+            }
+            (None, c) if c.is_whitespace() => end_word(&mut word, &mut in_word, &mut words),
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    end_word(&mut word, &mut in_word, &mut words);
+    if !words.is_empty() {
+        commands.push(words);
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(action: PolicyAction, program: &str, args: Option<&str>) -> PolicyRule {
+        PolicyRule {
+            action,
+            program: Some(program.to_string()),
+            args: args.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        let re = Regex::new(&glob_to_regex("/usr/bin/*")).unwrap();
+        assert!(re.is_match("/usr/bin/vim"));
+        assert!(!re.is_match("/usr/bin/x/vim"));
+        let re = Regex::new(&glob_to_regex("/home/**")).unwrap();
+        assert!(re.is_match("/home/me/project"));
+        let re = Regex::new(&glob_to_regex("py?hon3.*")).unwrap();
+        assert!(re.is_match("python3.12"));
+        assert!(!re.is_match("python3"));
+    }
+
+    #[test]
+    fn test_split_command_line() {
+        assert_eq!(
+            split_command_line("FOO=1 ls -la 'my dir' && sudo rm -rf /; echo \"a;b\" | wc"),
+            vec![
+                vec!["ls", "-la", "my dir"],
+                vec!["sudo", "rm", "-rf", "/"],
+                vec!["echo", "a;b"],
+                vec!["wc"],
+            ]
+        );
+        assert_eq!(split_command_line("a\\ b c=d"), vec![vec!["a b", "c=d"]]);
+        assert!(split_command_line("   ").is_empty());
+    }
+
+    #[test]
+    fn test_check_command() {
+        let settings = PolicySettings {
+            rules: vec![
+                rule(PolicyAction::Deny, "rm", Some(r"(^|\s)-\w*r\w*\s+/(\s|$)")),
+                rule(PolicyAction::Allow, "/bin/*", None),
+                PolicyRule {
+                    action: PolicyAction::Deny,
+                    cwd: Some("/etc/**".to_string()),
+                    reason: Some("no work in /etc".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let policy = Policy::new(&settings, &[]).unwrap();
+        let home = Path::new("/home/me");
+
+        let decision = policy.check_command("rm", &["-rf".to_string(), "/".to_string()], home);
+        assert!(!decision.allowed);
+        assert_eq!(decision.rule, Some(0));
+        assert!(
+            policy
+                .check_command("rm", &["-rf".to_string(), "build".to_string()], home)
+                .allowed
+        );
+
+        assert_eq!(policy.check_command("/bin/ls", &[], home).rule, Some(1));
+        let decision = policy.check_command("/opt/tool", &[], Path::new("/etc/ssh"));
+        assert_eq!(decision.reason.as_deref(), Some("no work in /etc"));
+        assert!(matches!(
+            decision.into_result(),
+            Err(Error::CommandNotAllowed(_))
+        ));
+
+        let deny_all = PolicySettings {
+            default_action: PolicyAction::Deny,
+            ..Default::default()
+        };
+        let policy = Policy::new(&deny_all, &[]).unwrap();
+        assert!(!policy.check_command("/opt/tool", &[], home).allowed);
+    }
+
+    #[test]
+    fn test_allowed_commands() {
+        let policy = Policy::new(&PolicySettings::default(), &["bash".to_string()]).unwrap();
+        let cwd = Path::new("/");
+        assert!(policy.check_command("bash", &[], cwd).allowed);
+        assert!(policy.check_command("/some/where/bash", &[], cwd).allowed);
+        assert!(!policy.check_command("vim", &[], cwd).allowed);
+    }
+
+    #[test]
+    fn test_check_input() {
+        let settings = PolicySettings {
+            rules: vec![rule(PolicyAction::Deny, "sudo", None)],
+            denied_input: vec![r"rm\s+-rf\s+/(\s|$)".to_string()],
+            ..Default::default()
+        };
+        let policy = Policy::new(&settings, &[]).unwrap();
+        let cwd = Path::new("/tmp");
+
+        assert!(!policy.check_input("bash", "rm -rf /\n", cwd).allowed);
+        assert!(
+            !policy
+                .check_input("/bin/zsh", "ls && sudo reboot", cwd)
+                .allowed
+        );
+        assert!(policy.check_input("bash", "echo sudo\n", cwd).allowed);
+        // Not a shell: text is data, not commands
+        assert!(policy.check_input("vim", "sudo reboot", cwd).allowed);
+
+        // Scripts given to shells at start are checked too
+        let script = |script: &str| ["-c".to_string(), script.to_string()];
+        assert!(
+            !policy
+                .check_command("sh", &script("sudo reboot"), cwd)
+                .allowed
+        );
+        assert!(
+            !policy
+                .check_command("bash", &["-lc".to_string(), "rm -rf /".to_string()], cwd)
+                .allowed
+        );
+        assert!(
+            policy
+                .check_command("sh", &script("ls; echo ok"), cwd)
+                .allowed
+        );
+        assert!(
+            !policy
+                .check_input("bash", "bash -c 'sudo reboot'\n", cwd)
+                .allowed
+        );
+    }
+
+    #[test]
+    fn test_pending_line() {
+        let mut line = PendingLine::default();
+        assert!(line.feed("su").is_empty());
+        assert_eq!(line.feed("do reboot\r"), vec!["sudo reboot"]);
+        assert_eq!(line.text(), "");
+
+        assert!(line.feed("ls -lx\x7fa").is_empty());
+        assert_eq!(line.text(), "ls -la");
+        assert_eq!(line.feed("\x1b[D\x1b[C\r"), vec!["ls -la"]);
+
+        line.feed("rm -rf /\x15echo hi");
+        assert_eq!(line.feed("\x1b[13u"), vec!["echo hi"]);
+        line.feed("sudo id\x03");
+        assert_eq!(line.feed("\x1b[27;5;13~a\nb\n"), vec!["", "a", "b"]);
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        let settings = PolicySettings {
+            denied_input: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(Policy::new(&settings, &[]), Err(Error::Config(_))));
+    }
+}
@@ -1,6 +1,6 @@
 //! Terminal State Tree (TST) assembler.
 
-use terminal_mcp_core::{Dimensions, Position, TerminalStateTree};
+use terminal_mcp_core::{CursorStyle, Dimensions, Position, TerminalStateTree};
 
 use crate::detection::DetectedElement;
 
@@ -40,10 +40,17 @@ impl TSTAssembler {
             session_id,
             dimensions,
             cursor,
+            cursor_visible: true,
+            cursor_style: CursorStyle::Block,
             timestamp: chrono::Utc::now().to_rfc3339(),
             elements,
             raw_text,
             ansi_buffer: None,
+            html: None,
+            styles: None,
+            events: Default::default(),
+            termios: None,
+            input_mode: None,
         }
     }
 
@@ -3,7 +3,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use terminal_mcp_core::{Bounds, Element, Position};
+use terminal_mcp_core::{Bounds, CursorStyle, Element, Position};
 use terminal_mcp_emulator::Grid;
 
 /// Detection confidence level.
@@ -37,6 +37,12 @@ pub struct DetectionContext {
     /// Current cursor position
     pub cursor: Position,
 
+    /// Whether the application shows the cursor
+    pub cursor_visible: bool,
+
+    /// Cursor shape requested by the application
+    pub cursor_style: CursorStyle,
+
     /// Previous frame's elements (for tracking changes)
     pub previous_elements: Option<Vec<Element>>,
 
@@ -50,11 +56,18 @@ impl DetectionContext {
         Self {
             claimed_regions: Vec::new(),
             cursor,
+            cursor_visible: true,
+            cursor_style: CursorStyle::Block,
             previous_elements: None,
             ref_counter: RefIdGenerator::new(),
         }
     }
 
+    /// Whether the cursor is a visible bar, the usual shape for text entry.
+    pub fn is_text_cursor(&self) -> bool {
+        self.cursor_visible && self.cursor_style == CursorStyle::Bar
+    }
+
     /// Check if a region overlaps with any claimed regions.
     pub fn is_region_claimed(&self, bounds: &Bounds) -> bool {
         self.claimed_regions
@@ -164,6 +177,8 @@ impl DetectionPipeline {
     /// Run all detectors on the grid.
     pub fn detect(&self, grid: &Grid, cursor: Position) -> Vec<DetectedElement> {
         let mut context = DetectionContext::new(cursor);
+        context.cursor_visible = grid.cursor().visible;
+        context.cursor_style = grid.cursor().style;
         let mut all_elements = Vec::new();
 
         for detector in &self.detectors {
@@ -290,6 +290,21 @@ mod tests {
         assert_eq!(detected[0].confidence, Confidence::High);
     }
 
+    #[test]
+    fn test_border_detector_dec_line_drawing() {
+        // ncurses-style box drawn with the DEC special graphics charset
+        let text = "\x1b(0lqqqqqqqqk\x1b(B\r\n\x1b(0x\x1b(B Hello  \x1b(0x\x1b(B\r\n\x1b(0mqqqqqqqqj\x1b(B";
+        let grid = create_grid_with_text(5, 20, text);
+
+        let detector = BorderDetector::new();
+        let context = DetectionContext::new(terminal_mcp_core::Position::new(0, 0));
+        let detected = detector.detect(&grid, &context);
+
+        assert_eq!(detected.len(), 1);
+        assert_eq!(detected[0].bounds.width, 10);
+        assert_eq!(detected[0].bounds.height, 3);
+    }
+
     #[test]
     fn test_border_detector_with_title() {
         let text = "┌─ Title ─┐\r\n│  Content│\r\n└─────────┘\r\n";
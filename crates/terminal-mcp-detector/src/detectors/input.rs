@@ -226,17 +226,28 @@ impl ElementDetector for InputDetector {
         let cursor_row = context.cursor.row;
         let cursor_col = context.cursor.col;
 
-        // Strategy 1: Try to detect input at cursor position (highest confidence)
-        if let Some(input) = self.detect_input_at_cursor(grid, cursor_row, cursor_col) {
-            // Check if region is already claimed
-            if !context.is_region_claimed(&input.bounds) {
-                results.push(input);
-                return results;
+        // Strategy 1: Try to detect input at cursor position (highest confidence).
+        // Skipped when the cursor is hidden, since nothing is being typed there.
+        if context.cursor_visible {
+            if let Some(mut input) = self.detect_input_at_cursor(grid, cursor_row, cursor_col) {
+                // A bar cursor is what editors and line editors show while taking text
+                if context.is_text_cursor() {
+                    input.confidence = Confidence::High;
+                }
+                // Check if region is already claimed
+                if !context.is_region_claimed(&input.bounds) {
+                    results.push(input);
+                    return results;
+                }
             }
         }
 
         // Strategy 2: Try reverse video detection (for focused inputs)
-        if let Some(input) = self.detect_reverse_video_input(grid, cursor_row) {
+        if let Some(mut input) = self.detect_reverse_video_input(grid, cursor_row) {
+            // With a hidden cursor a highlighted row is more likely a menu selection
+            if !context.cursor_visible {
+                input.confidence = Confidence::Medium;
+            }
             if !context.is_region_claimed(&input.bounds) {
                 results.push(input);
             }
@@ -249,7 +260,7 @@ impl ElementDetector for InputDetector {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use terminal_mcp_core::{Dimensions, Position};
+    use terminal_mcp_core::{CursorStyle, Dimensions, Position};
     use terminal_mcp_emulator::{Grid, Parser};
 
     fn create_grid_with_text(rows: u16, cols: u16, text: &str) -> Grid {
@@ -343,4 +354,28 @@ mod tests {
             assert_eq!(*cursor_pos, 5); // Cursor at 'e' in user@example.com
         }
     }
+
+    #[test]
+    fn test_input_detector_hidden_cursor() {
+        let grid = create_grid_with_text(5, 40, "Username: john\r\n");
+        let detector = InputDetector::new();
+
+        let mut context = DetectionContext::new(Position::new(0, 14));
+        context.cursor_visible = false;
+        assert!(detector.detect(&grid, &context).is_empty());
+    }
+
+    #[test]
+    fn test_input_detector_bar_cursor_confidence() {
+        let grid = create_grid_with_text(5, 40, "[  search  ]\r\n");
+        let detector = InputDetector::new();
+
+        let mut context = DetectionContext::new(Position::new(0, 3));
+        let detected = detector.detect(&grid, &context);
+        assert_eq!(detected[0].confidence, Confidence::Medium);
+
+        context.cursor_style = CursorStyle::Bar;
+        let detected = detector.detect(&grid, &context);
+        assert_eq!(detected[0].confidence, Confidence::High);
+    }
 }
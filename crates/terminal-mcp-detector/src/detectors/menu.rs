@@ -273,31 +273,43 @@ impl MenuDetector {
         &self,
         grid: &Grid,
         region: &Bounds,
-        cursor: Position,
+        context: &DetectionContext,
     ) -> Option<DetectedElement> {
         // Try strategies in order of confidence
-        let result = self
+        let styled = self
             .detect_by_reverse_video(grid, region)
             .or_else(|| self.detect_by_background_color(grid, region))
-            .or_else(|| self.detect_by_prefix_marker(grid, region))
-            .or_else(|| self.detect_by_cursor(grid, region, cursor));
-
-        if let Some((selected_idx, items)) = result {
-            let ref_id = format!("menu_{}_{}", region.row, region.col);
-
-            Some(DetectedElement {
-                element: Element::Menu {
-                    ref_id,
-                    bounds: *region,
-                    items,
-                    selected: selected_idx,
-                },
+            .or_else(|| self.detect_by_prefix_marker(grid, region));
+
+        let ((selected_idx, items), confidence) = match styled {
+            Some(result) => (result, Confidence::High),
+            // A bar cursor is sitting in a text field, not on a menu item
+            None if context.is_text_cursor() => return None,
+            None => {
+                let result = self.detect_by_cursor(grid, region, context.cursor)?;
+                // TUIs hide the cursor while a menu has focus; a visible one
+                // inside plain text is just as likely a shell prompt
+                let confidence = if context.cursor_visible {
+                    Confidence::Medium
+                } else {
+                    Confidence::High
+                };
+                (result, confidence)
+            }
+        };
+
+        let ref_id = format!("menu_{}_{}", region.row, region.col);
+
+        Some(DetectedElement {
+            element: Element::Menu {
+                ref_id,
                 bounds: *region,
-                confidence: Confidence::High,
-            })
-        } else {
-            None
-        }
+                items,
+                selected: selected_idx,
+            },
+            bounds: *region,
+            confidence,
+        })
     }
 
     /// Find potential menu regions in the grid.
@@ -367,7 +379,7 @@ impl ElementDetector for MenuDetector {
             }
 
             // Try to detect menu in this region
-            if let Some(menu) = self.detect_menu_in_region(grid, &region, context.cursor) {
+            if let Some(menu) = self.detect_menu_in_region(grid, &region, context) {
                 results.push(menu);
             }
         }
@@ -379,7 +391,7 @@ impl ElementDetector for MenuDetector {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use terminal_mcp_core::Dimensions;
+    use terminal_mcp_core::{CursorStyle, Dimensions};
     use terminal_mcp_emulator::{Grid, Parser};
 
     fn create_grid_with_text(rows: u16, cols: u16, text: &str) -> Grid {
@@ -479,4 +491,30 @@ mod tests {
             assert_eq!(selected, &1); // Second item selected (where cursor is)
         }
     }
+
+    #[test]
+    fn test_menu_detector_hidden_cursor_raises_confidence() {
+        let text = "Item 1\r\nItem 2\r\nItem 3\r\n";
+        let grid = create_grid_with_text(10, 40, text);
+        let detector = MenuDetector::new();
+
+        let mut context = DetectionContext::new(Position::new(1, 0));
+        let detected = detector.detect(&grid, &context);
+        assert_eq!(detected[0].confidence, Confidence::Medium);
+
+        context.cursor_visible = false;
+        let detected = detector.detect(&grid, &context);
+        assert_eq!(detected[0].confidence, Confidence::High);
+    }
+
+    #[test]
+    fn test_menu_detector_ignores_bar_cursor() {
+        let text = "Item 1\r\nItem 2\r\nItem 3\r\n";
+        let grid = create_grid_with_text(10, 40, text);
+        let detector = MenuDetector::new();
+
+        let mut context = DetectionContext::new(Position::new(1, 0));
+        context.cursor_style = CursorStyle::Bar;
+        assert!(detector.detect(&grid, &context).is_empty());
+    }
 }
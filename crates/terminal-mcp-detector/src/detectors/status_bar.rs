@@ -150,7 +150,7 @@ impl ElementDetector for StatusBarDetector {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use terminal_mcp_core::Dimensions;
+    use terminal_mcp_core::{Cell, Dimensions};
     use terminal_mcp_emulator::{Grid, Parser};
 
     fn create_grid_with_text(rows: u16, cols: u16, text: &str) -> Grid {
@@ -233,14 +233,14 @@ mod tests {
         let last_row = 2;
         let status_text = "Status";
         for col in 0..20 {
-            if let Some(cell) = grid.cell_mut(last_row, col) {
-                cell.character = if col < 6 {
-                    status_text.chars().nth(col as usize % 6).unwrap_or(' ')
-                } else {
-                    ' '
-                };
-                cell.bg = Color::Blue; // Non-default background
-            }
+            let character = if col < 6 {
+                status_text.chars().nth(col as usize % 6).unwrap_or(' ')
+            } else {
+                ' '
+            };
+            let mut cell = Cell::new(character);
+            cell.bg = Color::Blue; // Non-default background
+            grid.set_cell(last_row, col, cell);
         }
 
         let detector = StatusBarDetector::new();
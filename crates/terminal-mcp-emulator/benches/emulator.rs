@@ -0,0 +1,155 @@
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use terminal_mcp_core::Dimensions;
+use terminal_mcp_emulator::{Grid, Parser};
+
+/// Colored log output, like a build or test run
+fn log_output(lines: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    for i in 0..lines {
+        let line = format!(
+            "\x1b[32m[INFO]\x1b[0m {i:>6} compiling crate_{i} (\x1b[1mtarget/debug\x1b[0m) ok\r\n"
+        );
+        out.extend_from_slice(line.as_bytes());
+    }
+    out
+}
+
+/// Grid with a full scrollback of colored output
+fn filled_grid(rows: u16, cols: u16, lines: usize) -> Grid {
+    let mut parser = Parser::new(Grid::new(Dimensions::new(rows, cols)));
+    parser.process(&log_output(lines));
+    parser.into_grid()
+}
+
+fn bench_scrolling_output(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scrolling_output");
+    let output = log_output(1_000);
+
+    for size in [(24, 80), (50, 200)].iter() {
+        let (rows, cols) = *size;
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{rows}x{cols}")),
+            &output,
+            |b, output| {
+                b.iter_batched(
+                    || Parser::new(Grid::new(Dimensions::new(rows, cols))),
+                    |mut parser| {
+                        parser.process(black_box(output));
+                        parser
+                    },
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Plain build log output, about `bytes` long
+fn plain_log(bytes: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes + 128);
+    let mut i = 0;
+    while out.len() < bytes {
+        let line = format!(
+            "   Compiling dependency-{i} v0.{}.{} (/home/ci/build/target/release/deps)\r\n",
+            i % 10,
+            i % 7
+        );
+        out.extend_from_slice(line.as_bytes());
+        i += 1;
+    }
+    out
+}
+
+fn bench_throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("throughput");
+    group.sample_size(20);
+
+    let inputs = [
+        ("plain_log", plain_log(4 << 20)),
+        ("colored_log", log_output(50_000)),
+    ];
+    for (name, input) in inputs.iter() {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), input, |b, input| {
+            b.iter_batched(
+                || Parser::new(Grid::new(Dimensions::new(50, 200))),
+                |mut parser| {
+                    parser.process(black_box(input));
+                    parser
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_scroll_up(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scroll_up");
+
+    for size in [(24, 80), (50, 200), (100, 300)].iter() {
+        let (rows, cols) = *size;
+        let mut grid = filled_grid(rows, cols, rows as usize);
+        group.bench_function(
+            BenchmarkId::new("full_screen", format!("{rows}x{cols}")),
+            |b| {
+                b.iter(|| grid.scroll_up(black_box(1)));
+            },
+        );
+
+        let mut grid = filled_grid(rows, cols, rows as usize);
+        grid.set_scroll_region(1, rows - 2);
+        group.bench_function(BenchmarkId::new("region", format!("{rows}x{cols}")), |b| {
+            b.iter(|| grid.scroll_up(black_box(1)));
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_insert_delete_lines(c: &mut Criterion) {
+    let mut grid = filled_grid(50, 200, 50);
+    grid.cursor_mut().position.row = 10;
+
+    c.bench_function("insert_delete_lines_50x200", |b| {
+        b.iter(|| {
+            grid.insert_lines(black_box(3));
+            grid.delete_lines(black_box(3));
+        });
+    });
+}
+
+fn bench_resize_reflow(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resize_reflow");
+    group.sample_size(20);
+
+    for lines in [1_000, 10_000].iter() {
+        group.bench_function(BenchmarkId::from_parameter(lines), |b| {
+            b.iter_batched(
+                || filled_grid(24, 80, *lines),
+                |mut grid| {
+                    grid.resize(black_box(Dimensions::new(30, 60)));
+                    grid
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_throughput,
+    bench_scrolling_output,
+    bench_scroll_up,
+    bench_insert_delete_lines,
+    bench_resize_reflow
+);
+criterion_main!(benches);
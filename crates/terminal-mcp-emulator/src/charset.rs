@@ -0,0 +1,169 @@
+//! Character set designation (G0–G3) and the DEC special graphics charset.
+
+/// A character set that can be designated into one of G0–G3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Charset {
+    /// US ASCII (`ESC ( B`)
+    #[default]
+    Ascii,
+    /// DEC special graphics / line drawing (`ESC ( 0`)
+    DecSpecialGraphics,
+    /// United Kingdom (`ESC ( A`): `#` is replaced by `£`
+    Uk,
+}
+
+impl Charset {
+    /// Charset selected by the final byte of a designation sequence.
+    pub fn from_designator(byte: u8) -> Option<Self> {
+        match byte {
+            b'B' => Some(Charset::Ascii),
+            b'0' => Some(Charset::DecSpecialGraphics),
+            b'A' => Some(Charset::Uk),
+            _ => None,
+        }
+    }
+
+    /// Map a printed character through this charset.
+    pub fn map(self, c: char) -> char {
+        match self {
+            Charset::Ascii => c,
+            Charset::Uk if c == '#' => '£',
+            Charset::Uk => c,
+            Charset::DecSpecialGraphics => dec_special_graphics(c),
+        }
+    }
+}
+
+/// Map a character from the DEC special graphics set to its Unicode equivalent.
+fn dec_special_graphics(c: char) -> char {
+    match c {
+        '_' => ' ',
+        '`' => '◆',
+        'a' => '▒',
+        'b' => '␉',
+        'c' => '␌',
+        'd' => '␍',
+        'e' => '␊',
+        'f' => '°',
+        'g' => '±',
+        'h' => '␤',
+        'i' => '␋',
+        'j' => '┘',
+        'k' => '┐',
+        'l' => '┌',
+        'm' => '└',
+        'n' => '┼',
+        'o' => '⎺',
+        'p' => '⎻',
+        'q' => '─',
+        'r' => '⎼',
+        's' => '⎽',
+        't' => '├',
+        'u' => '┤',
+        'v' => '┴',
+        'w' => '┬',
+        'x' => '│',
+        'y' => '≤',
+        'z' => '≥',
+        '{' => 'π',
+        '|' => '≠',
+        '}' => '£',
+        '~' => '·',
+        other => other,
+    }
+}
+
+/// G0–G3 designations plus locking and single shift state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CharsetState {
+    /// Charsets designated into G0–G3
+    slots: [Charset; 4],
+    /// Slot invoked into GL (0 after SI, 1 after SO)
+    gl: usize,
+    /// Slot invoked for the next character only (SS2/SS3)
+    single_shift: Option<usize>,
+}
+
+impl CharsetState {
+    /// Designate a charset into slot G0–G3.
+    pub fn designate(&mut self, slot: usize, charset: Charset) {
+        if let Some(s) = self.slots.get_mut(slot) {
+            *s = charset;
+        }
+    }
+
+    /// Invoke a slot into GL (SI = 0, SO = 1, LS2 = 2, LS3 = 3).
+    pub fn lock_shift(&mut self, slot: usize) {
+        if slot < self.slots.len() {
+            self.gl = slot;
+        }
+    }
+
+    /// Use a slot for the next printed character only (SS2 = 2, SS3 = 3).
+    pub fn single_shift(&mut self, slot: usize) {
+        if slot < self.slots.len() {
+            self.single_shift = Some(slot);
+        }
+    }
+
+    /// Whether printable ASCII currently prints unchanged.
+    pub fn is_ascii(&self) -> bool {
+        self.single_shift.is_none() && self.slots[self.gl] == Charset::Ascii
+    }
+
+    /// Translate a printed character through the active charset.
+    pub fn translate(&mut self, c: char) -> char {
+        let slot = self.single_shift.take().unwrap_or(self.gl);
+        self.slots[slot].map(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dec_special_graphics_box() {
+        let charset = Charset::DecSpecialGraphics;
+        let mapped: String = "lqqk".chars().map(|c| charset.map(c)).collect();
+        assert_eq!(mapped, "┌──┐");
+        assert_eq!(charset.map('A'), 'A');
+    }
+
+    #[test]
+    fn test_charset_state_is_ascii() {
+        let mut state = CharsetState::default();
+        assert!(state.is_ascii());
+        state.designate(1, Charset::DecSpecialGraphics);
+        assert!(state.is_ascii());
+        state.single_shift(1);
+        assert!(!state.is_ascii());
+        state.translate('q');
+        state.lock_shift(1);
+        assert!(!state.is_ascii());
+    }
+
+    #[test]
+    fn test_charset_state_shifts() {
+        let mut state = CharsetState::default();
+        state.designate(1, Charset::DecSpecialGraphics);
+        assert_eq!(state.translate('q'), 'q');
+
+        // SO invokes G1
+        state.lock_shift(1);
+        assert_eq!(state.translate('q'), '─');
+
+        // SI back to G0
+        state.lock_shift(0);
+        assert_eq!(state.translate('q'), 'q');
+    }
+
+    #[test]
+    fn test_charset_single_shift() {
+        let mut state = CharsetState::default();
+        state.designate(2, Charset::Uk);
+        state.single_shift(2);
+        assert_eq!(state.translate('#'), '£');
+        assert_eq!(state.translate('#'), '#');
+    }
+}
@@ -1,21 +1,14 @@
 //! Terminal grid state buffer and cursor tracking.
 
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
-
-use terminal_mcp_core::{Bounds, Cell, CellAttributes, Color, Dimensions, Position};
-
-/// Cursor visual style.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum CursorStyle {
-    /// Block cursor (fills entire cell)
-    Block,
-    /// Underline cursor (bottom of cell)
-    Underline,
-    /// Bar cursor (vertical line at left)
-    Bar,
-}
+use std::collections::VecDeque;
+use std::time::Instant;
+
+pub use terminal_mcp_core::CursorStyle;
+use terminal_mcp_core::{
+    Bounds, Cell, CellAttributes, Color, Dimensions, Position, RowStyles, StyleRun,
+};
+
+use crate::row::{PackedRow, Row, Style, StyleId, StyleTable, DEFAULT_STYLE};
 
 /// Cursor state.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -57,16 +50,17 @@ impl Cursor {
 /// Terminal grid state buffer.
 #[derive(Debug)]
 pub struct Grid {
-    /// Cell storage (row-major order)
-    cells: Vec<Cell>,
+    /// Screen rows, top to bottom
+    rows: VecDeque<Row>,
+    /// Styles referenced by the screen rows and the saved primary screen
+    styles: StyleTable,
     /// Grid dimensions
     dimensions: Dimensions,
     /// Cursor state
     cursor: Cursor,
-    /// Saved cursor (for save/restore operations)
-    saved_cursor: Option<Cursor>,
+    /// Saved cursor and rendition (for save/restore operations)
+    saved_cursor: Option<SavedCursor>,
     /// Scroll region (top, bottom) - 0-indexed, inclusive
-    #[allow(dead_code)] // TODO: Will be used in E2.1 VTE parser implementation
     scroll_region: Option<(u16, u16)>,
     /// Current cell attributes for new characters
     current_attrs: CellAttributes,
@@ -74,8 +68,43 @@ pub struct Grid {
     current_fg: Color,
     /// Current background color
     current_bg: Color,
-    /// Line wrap flags - true if row is continuation of previous row
-    line_wrapped: Vec<bool>,
+    /// Tab stops, one flag per column
+    tab_stops: Vec<bool>,
+    /// Rows scrolled off the top of the primary screen, oldest first
+    scrollback: VecDeque<PackedRow>,
+    /// Maximum number of scrollback rows kept
+    scrollback_limit: usize,
+    /// Primary screen rows while the alternate screen is active
+    saved_primary: Option<VecDeque<Row>>,
+    /// Counter bumped on every change to the screen or cursor
+    generation: u64,
+    /// Time of the most recent change
+    changed_at: Instant,
+}
+
+/// Default number of scrollback rows kept per grid.
+pub const DEFAULT_SCROLLBACK_LINES: usize = 10_000;
+
+/// An unpacked row being rewrapped by reflow.
+#[derive(Debug, Clone)]
+struct StoredRow {
+    cells: Vec<Cell>,
+    /// Continues the previous row (soft wrap)
+    wrapped: bool,
+}
+
+/// Cursor state saved by DECSC / `CSI s`.
+#[derive(Debug, Clone)]
+struct SavedCursor {
+    position: Position,
+    attrs: CellAttributes,
+    fg: Color,
+    bg: Color,
+}
+
+/// Default tab stops every 8 columns.
+fn default_tab_stops(cols: u16) -> Vec<bool> {
+    (0..cols).map(|col| col > 0 && col % 8 == 0).collect()
 }
 
 impl Grid {
@@ -83,9 +112,9 @@ impl Grid {
     ///
     /// All cells are initialized to default (empty space).
     pub fn new(dimensions: Dimensions) -> Self {
-        let cell_count = dimensions.cell_count();
         Self {
-            cells: vec![Cell::default(); cell_count],
+            rows: blank_rows(dimensions, 0),
+            styles: StyleTable::default(),
             dimensions,
             cursor: Cursor::default(),
             saved_cursor: None,
@@ -93,42 +122,126 @@ impl Grid {
             current_attrs: CellAttributes::default(),
             current_fg: Color::Default,
             current_bg: Color::Default,
-            line_wrapped: vec![false; dimensions.rows as usize],
+            tab_stops: default_tab_stops(dimensions.cols),
+            scrollback: VecDeque::new(),
+            scrollback_limit: DEFAULT_SCROLLBACK_LINES,
+            saved_primary: None,
+            generation: 0,
+            changed_at: Instant::now(),
         }
     }
 
-    /// Get cell at position (immutable).
+    /// Current generation. It increases whenever a cell, a wrap flag or the
+    /// cursor changes.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Check whether anything changed after `generation`.
+    pub fn changed_since(&self, generation: u64) -> bool {
+        self.generation > generation
+    }
+
+    /// Rows whose content changed after `generation`.
+    pub fn damaged_rows(&self, generation: u64) -> Vec<u16> {
+        (0..self.dimensions.rows)
+            .filter(|&row| self.rows[row as usize].generation > generation)
+            .collect()
+    }
+
+    /// Time of the most recent change.
+    pub fn last_changed(&self) -> Instant {
+        self.changed_at
+    }
+
+    /// Record a change that does not touch cell content, such as a cursor move.
+    pub fn touch(&mut self) {
+        self.generation += 1;
+        self.changed_at = Instant::now();
+    }
+
+    /// Record a change to the given rows.
+    fn damage(&mut self, rows: std::ops::Range<u16>) {
+        self.touch();
+        let end = (rows.end as usize).min(self.rows.len());
+        let start = (rows.start as usize).min(end);
+        for row in self.rows.range_mut(start..end) {
+            row.generation = self.generation;
+        }
+    }
+
+    /// Record a change to every row.
+    fn damage_all(&mut self) {
+        self.damage(0..self.dimensions.rows);
+    }
+
+    /// Get cell at position.
     ///
     /// Returns None if position is out of bounds.
-    pub fn cell(&self, row: u16, col: u16) -> Option<&Cell> {
+    pub fn cell(&self, row: u16, col: u16) -> Option<Cell> {
         if row < self.dimensions.rows && col < self.dimensions.cols {
-            let idx = row as usize * self.dimensions.cols as usize + col as usize;
-            self.cells.get(idx)
+            self.rows[row as usize].cell(col as usize, &self.styles)
         } else {
             None
         }
     }
 
-    /// Get mutable cell at position.
+    /// Set a cell, recording damage only if its content actually changes.
     ///
-    /// Returns None if position is out of bounds.
-    pub fn cell_mut(&mut self, row: u16, col: u16) -> Option<&mut Cell> {
+    /// Positions out of bounds are ignored.
+    pub fn set_cell(&mut self, row: u16, col: u16, cell: Cell) {
         if row < self.dimensions.rows && col < self.dimensions.cols {
-            let idx = row as usize * self.dimensions.cols as usize + col as usize;
-            self.cells.get_mut(idx)
-        } else {
-            None
+            let style = self.intern(Style::of(&cell));
+            if self.rows[row as usize].set(col as usize, cell.character, style) {
+                self.damage(row..row + 1);
+            }
         }
     }
 
-    /// Get entire row as a slice.
+    /// Index of a style in the style table, compacting the table when due.
+    fn intern(&mut self, style: Style) -> StyleId {
+        if let Some(id) = self.styles.find(style) {
+            return id;
+        }
+        if self.styles.is_due() {
+            let primary = self.saved_primary.iter_mut().flatten();
+            self.styles.compact(self.rows.iter_mut().chain(primary));
+        }
+        self.styles.intern(style)
+    }
+
+    /// Write printable ASCII at (row, col) in the current rendition.
+    ///
+    /// Text past the end of the row is dropped. Damage is recorded once for
+    /// the row, and only if a cell changed. Returns the number of cells written.
+    pub fn write_ascii(&mut self, row: u16, col: u16, text: &[u8]) -> usize {
+        if row >= self.dimensions.rows || col >= self.dimensions.cols {
+            return 0;
+        }
+        let style = self.intern(Style::new(
+            self.current_fg,
+            self.current_bg,
+            self.current_attrs,
+        ));
+        let line = &mut self.rows[row as usize];
+        let len = text.len().min(line.len() - col as usize);
+
+        let mut changed = false;
+        for (offset, &byte) in text[..len].iter().enumerate() {
+            changed |= line.set(col as usize + offset, byte as char, style);
+        }
+        if changed {
+            self.damage(row..row + 1);
+        }
+        len
+    }
+
+    /// Get a copy of an entire row.
     ///
     /// Returns None if row is out of bounds.
-    pub fn row(&self, row: u16) -> Option<&[Cell]> {
+    pub fn row(&self, row: u16) -> Option<Vec<Cell>> {
         if row < self.dimensions.rows {
-            let start = row as usize * self.dimensions.cols as usize;
-            let end = start + self.dimensions.cols as usize;
-            Some(&self.cells[start..end])
+            Some(self.rows[row as usize].cells(&self.styles))
         } else {
             None
         }
@@ -166,6 +279,99 @@ impl Grid {
         self.extract_text(&bounds)
     }
 
+    /// Re-encode the grid as text with minimal SGR escape sequences.
+    ///
+    /// Soft-wrapped rows are joined like [`Grid::to_plain_text`], trailing
+    /// blank cells are dropped, and styles are reset at the end of each line
+    /// so backgrounds do not bleed when the output is printed elsewhere.
+    pub fn to_ansi(&self) -> String {
+        let rows = self.dimensions.rows;
+        let default = Cell::default();
+        let mut out = String::new();
+        let mut current = default.clone();
+
+        for row in 0..rows {
+            if row > 0 && !self.is_line_wrapped(row) {
+                if !same_style(&current, &default) {
+                    out.push_str("\x1b[0m");
+                    current = default.clone();
+                }
+                out.push('\n');
+            }
+
+            let cells = self.rows[row as usize].cells(&self.styles);
+            // Keep trailing blanks on rows that continue onto the next one
+            let continues = row + 1 < rows && self.is_line_wrapped(row + 1);
+            let len = if continues {
+                cells.len()
+            } else {
+                cells
+                    .iter()
+                    .rposition(|c| *c != default)
+                    .map_or(0, |i| i + 1)
+            };
+
+            for cell in &cells[..len] {
+                if !same_style(&current, cell) {
+                    push_sgr(&mut out, cell);
+                    current = cell.clone();
+                }
+                out.push(cell.character);
+            }
+        }
+
+        if !same_style(&current, &default) {
+            out.push_str("\x1b[0m");
+        }
+        out
+    }
+
+    /// Collect the styled runs of each row.
+    ///
+    /// Only colors and bold, reverse, underline and dim are considered.
+    /// Blanks that would look the same in any foreground color do not break
+    /// a run, and runs are trimmed to their visible content.
+    pub fn style_rows(&self) -> Vec<RowStyles> {
+        let default = RunStyle::of(&Cell::default());
+        let mut rows = Vec::new();
+
+        for row in 0..self.dimensions.rows {
+            let mut runs: Vec<StyleRun> = Vec::new();
+            let mut current: Option<(RunStyle, u16, u16)> = None;
+
+            for col in 0..self.dimensions.cols {
+                let cell = self.cell(row, col).expect("cell within bounds");
+                let style = RunStyle::of(&cell);
+                let invisible = style.is_invisible_blank(&cell);
+
+                match current.as_mut() {
+                    Some(_) if invisible => {}
+                    Some((run, _, end)) if *run == style => *end = col + 1,
+                    _ => {
+                        if let Some((run, start, end)) = current.take() {
+                            if run != default {
+                                runs.push(run.to_run(start, end));
+                            }
+                        }
+                        if !invisible {
+                            current = Some((style, col, col + 1));
+                        }
+                    }
+                }
+            }
+            if let Some((run, start, end)) = current {
+                if run != default {
+                    runs.push(run.to_run(start, end));
+                }
+            }
+
+            if !runs.is_empty() {
+                rows.push(RowStyles { row, runs });
+            }
+        }
+        rows
+    }
+
     /// Get cursor reference.
     pub fn cursor(&self) -> &Cursor {
         &self.cursor
@@ -216,70 +422,171 @@ impl Grid {
         self.current_bg = color;
     }
 
-    /// Save current cursor state.
+    /// Save current cursor state and rendition (attributes and colors).
     pub fn save_cursor(&mut self) {
-        self.saved_cursor = Some(self.cursor.clone());
+        self.saved_cursor = Some(SavedCursor {
+            position: self.cursor.position,
+            attrs: self.current_attrs,
+            fg: self.current_fg,
+            bg: self.current_bg,
+        });
     }
 
-    /// Restore saved cursor state.
+    /// Restore saved cursor position and rendition.
+    ///
+    /// Visibility and shape are not part of the saved state. The saved state
+    /// is kept, so it can be restored more than once.
     pub fn restore_cursor(&mut self) {
-        if let Some(saved) = self.saved_cursor.take() {
-            self.cursor = saved;
+        if let Some(saved) = self.saved_cursor.clone() {
+            self.cursor.position = saved.position;
+            self.current_attrs = saved.attrs;
+            self.current_fg = saved.fg;
+            self.current_bg = saved.bg;
+            self.clamp_cursor();
         }
     }
 
+    /// Clamp the cursor to the grid bounds.
+    fn clamp_cursor(&mut self) {
+        let pos = &mut self.cursor.position;
+        pos.row = pos.row.min(self.dimensions.rows.saturating_sub(1));
+        pos.col = pos.col.min(self.dimensions.cols.saturating_sub(1));
+    }
+
     /// Resize grid, preserving content where possible.
     ///
-    /// Content from the top-left corner is preserved up to the smaller of
-    /// old and new dimensions. Cursor is clamped to new bounds.
+    /// The primary screen is reflowed: soft-wrapped lines are rewrapped to the
+    /// new width and the cursor moves with the text under it. Rows that no
+    /// longer fit go to scrollback, and growing taller pulls them back. The
+    /// alternate screen is cropped or padded, since applications redraw it.
     pub fn resize(&mut self, new_dimensions: Dimensions) {
-        let mut new_cells = vec![Cell::default(); new_dimensions.cell_count()];
-        let mut new_wrapped = vec![false; new_dimensions.rows as usize];
-
-        let copy_rows = self.dimensions.rows.min(new_dimensions.rows);
-        let copy_cols = self.dimensions.cols.min(new_dimensions.cols);
-
-        // Copy preserved content
-        for row in 0..copy_rows {
-            for col in 0..copy_cols {
-                let old_idx = row as usize * self.dimensions.cols as usize + col as usize;
-                let new_idx = row as usize * new_dimensions.cols as usize + col as usize;
-                new_cells[new_idx] = self.cells[old_idx].clone();
+        let degenerate = self.dimensions.cell_count() == 0 || new_dimensions.cell_count() == 0;
+
+        if degenerate {
+            crop_rows(&mut self.rows, new_dimensions);
+            self.saved_primary = None;
+        } else if let Some(primary) = self.saved_primary.take() {
+            // The saved cursor belongs to the primary screen (mode 1049)
+            crop_rows(&mut self.rows, new_dimensions);
+            let saved: Vec<Position> = self.saved_cursor.iter().map(|s| s.position).collect();
+
+            let (primary, saved) = self.reflow_primary(primary, new_dimensions, &saved);
+            self.saved_primary = Some(primary);
+            if let (Some(cursor), Some(&position)) = (&mut self.saved_cursor, saved.first()) {
+                cursor.position = position;
+            }
+        } else {
+            let rows = std::mem::take(&mut self.rows);
+            let mut cursors = vec![self.cursor.position];
+            cursors.extend(self.saved_cursor.iter().map(|s| s.position));
+
+            let (rows, cursors) = self.reflow_primary(rows, new_dimensions, &cursors);
+            self.rows = rows;
+            self.cursor.position = cursors[0];
+            if let (Some(cursor), Some(&position)) = (&mut self.saved_cursor, cursors.get(1)) {
+                cursor.position = position;
             }
-            // Copy wrap flags
-            new_wrapped[row as usize] = self.line_wrapped[row as usize];
         }
 
-        self.cells = new_cells;
-        self.line_wrapped = new_wrapped;
+        if let Some(saved) = &mut self.saved_cursor {
+            let pos = &mut saved.position;
+            pos.row = pos.row.min(new_dimensions.rows.saturating_sub(1));
+            pos.col = pos.col.min(new_dimensions.cols.saturating_sub(1));
+        }
+        self.tab_stops.resize(new_dimensions.cols as usize, false);
+        for col in self.dimensions.cols..new_dimensions.cols {
+            self.tab_stops[col as usize] = col % 8 == 0;
+        }
         self.dimensions = new_dimensions;
+        self.scroll_region = None;
+        self.clamp_cursor();
+        self.damage_all();
+    }
 
-        // Clamp cursor to new dimensions
-        if new_dimensions.rows > 0 {
-            self.cursor.position.row = self.cursor.position.row.min(new_dimensions.rows - 1);
-        }
-        if new_dimensions.cols > 0 {
-            self.cursor.position.col = self.cursor.position.col.min(new_dimensions.cols - 1);
+    /// Rewrap primary screen rows together with the scrollback.
+    ///
+    /// Cursor positions are moved with the text under them. The screen ends
+    /// at the last row with content or the first cursor, whichever is lower,
+    /// and starts no further down than the first cursor. Later cursors that
+    /// end up in scrollback are pinned to the top row.
+    fn reflow_primary(
+        &mut self,
+        screen: VecDeque<Row>,
+        new_dimensions: Dimensions,
+        cursors: &[Position],
+    ) -> (VecDeque<Row>, Vec<Position>) {
+        let mut rows: Vec<StoredRow> = self
+            .scrollback
+            .drain(..)
+            .map(|row| StoredRow {
+                cells: row.unpack(),
+                wrapped: row.wrapped,
+            })
+            .collect();
+        let screen_start = rows.len();
+        rows.extend(screen.into_iter().map(|row| StoredRow {
+            cells: row.cells(&self.styles),
+            wrapped: row.wrapped,
+        }));
+
+        let cursors: Vec<(usize, usize)> = cursors
+            .iter()
+            .map(|pos| (screen_start + pos.row as usize, pos.col as usize))
+            .collect();
+        let (rows, cursors) = reflow_rows(rows, new_dimensions.cols as usize, &cursors);
+        let cursor = cursors.first().copied();
+
+        let content_end = rows
+            .iter()
+            .rposition(|row| row.cells.iter().any(|cell| *cell != Cell::default()))
+            .map_or(0, |idx| idx + 1);
+        let end = cursor.map_or(content_end, |(row, _)| content_end.max(row + 1));
+        let mut start = end.saturating_sub(new_dimensions.rows as usize);
+        if let Some((row, _)) = cursor {
+            start = start.min(row);
         }
+
+        let mut rows = rows.into_iter();
+        self.scrollback.extend(
+            rows.by_ref()
+                .take(start)
+                .map(|row| PackedRow::pack(&row.cells, row.wrapped)),
+        );
+        self.trim_scrollback();
+
+        let cols = new_dimensions.cols as usize;
+        let generation = self.generation;
+        let mut screen: VecDeque<Row> = rows
+            .take(new_dimensions.rows as usize)
+            .map(|mut row| {
+                row.cells.resize(cols, Cell::default());
+                Row::from_cells(&row.cells, &mut self.styles, row.wrapped, generation)
+            })
+            .collect();
+        screen.resize_with(new_dimensions.rows as usize, || {
+            Row::blank(new_dimensions.cols, self.generation)
+        });
+
+        let cursors = cursors
+            .into_iter()
+            .map(|(row, col)| Position::new(row.saturating_sub(start) as u16, col as u16))
+            .collect();
+        (screen, cursors)
     }
 
     /// Clear the entire grid.
     pub fn clear(&mut self) {
-        for cell in &mut self.cells {
-            *cell = Cell::default();
-        }
-        for wrapped in &mut self.line_wrapped {
-            *wrapped = false;
+        for row in &mut self.rows {
+            row.clear(self.generation);
         }
+        self.damage_all();
     }
 
     /// Clear a specific region.
     pub fn clear_region(&mut self, bounds: &Bounds) {
         for row in bounds.row..(bounds.row + bounds.height) {
             for col in bounds.col..(bounds.col + bounds.width) {
-                if let Some(cell) = self.cell_mut(row, col) {
-                    *cell = Cell::default();
-                }
+                self.set_cell(row, col, Cell::default());
             }
         }
     }
@@ -287,7 +594,7 @@ impl Grid {
     /// Check if a row is wrapped (continues from previous row).
     pub fn is_line_wrapped(&self, row: u16) -> bool {
         if row < self.dimensions.rows {
-            self.line_wrapped[row as usize]
+            self.rows[row as usize].wrapped
         } else {
             false
         }
@@ -295,10 +602,430 @@ impl Grid {
 
     /// Set line wrap flag for a row.
     pub fn set_line_wrapped(&mut self, row: u16, wrapped: bool) {
-        if row < self.dimensions.rows {
-            self.line_wrapped[row as usize] = wrapped;
+        if row < self.dimensions.rows && self.rows[row as usize].wrapped != wrapped {
+            self.rows[row as usize].wrapped = wrapped;
+            self.damage(row..row + 1);
+        }
+    }
+
+    /// Reset the grid to its power-on state (RIS), keeping the dimensions.
+    pub fn reset(&mut self) {
+        let scrollback_limit = self.scrollback_limit;
+        let generation = self.generation;
+        *self = Grid::new(self.dimensions);
+        self.scrollback_limit = scrollback_limit;
+        self.generation = generation;
+        self.damage_all();
+    }
+
+    /// Number of rows in scrollback.
+    pub fn scrollback_len(&self) -> usize {
+        self.scrollback.len()
+    }
+
+    /// Get a scrollback row, 0 being the oldest.
+    ///
+    /// Rows are stored packed and expanded on access. They keep the width
+    /// they had when they scrolled off, so they may be shorter or longer
+    /// than the current width.
+    pub fn scrollback_row(&self, index: usize) -> Option<Vec<Cell>> {
+        self.scrollback.get(index).map(PackedRow::unpack)
+    }
+
+    /// Set the maximum number of scrollback rows, dropping the oldest extra rows.
+    pub fn set_scrollback_limit(&mut self, lines: usize) {
+        self.scrollback_limit = lines;
+        self.trim_scrollback();
+    }
+
+    /// Discard all scrollback (ED 3).
+    pub fn clear_scrollback(&mut self) {
+        self.scrollback.clear();
+    }
+
+    /// Drop the oldest scrollback rows beyond the limit.
+    fn trim_scrollback(&mut self) {
+        let excess = self.scrollback.len().saturating_sub(self.scrollback_limit);
+        self.scrollback.drain(..excess);
+    }
+
+    /// Check if the alternate screen is active.
+    pub fn is_alternate_screen(&self) -> bool {
+        self.saved_primary.is_some()
+    }
+
+    /// Switch to a blank alternate screen, keeping the primary screen aside.
+    pub fn enter_alternate_screen(&mut self) {
+        if self.saved_primary.is_none() {
+            let blank = blank_rows(self.dimensions, self.generation);
+            self.saved_primary = Some(std::mem::replace(&mut self.rows, blank));
+            self.damage_all();
+        } else {
+            self.clear();
+        }
+    }
+
+    /// Switch back to the primary screen, discarding the alternate screen.
+    pub fn exit_alternate_screen(&mut self) {
+        if let Some(primary) = self.saved_primary.take() {
+            self.rows = primary;
+            self.damage_all();
+        }
+    }
+
+    /// Get the scroll region as (top, bottom), 0-indexed and inclusive.
+    ///
+    /// Defaults to the full screen when no region is set.
+    pub fn scroll_region(&self) -> (u16, u16) {
+        self.scroll_region
+            .unwrap_or((0, self.dimensions.rows.saturating_sub(1)))
+    }
+
+    /// Set the scroll region (DECSTBM), 0-indexed and inclusive.
+    ///
+    /// Invalid regions (top >= bottom or out of bounds) reset to the full screen.
+    pub fn set_scroll_region(&mut self, top: u16, bottom: u16) {
+        if top < bottom && bottom < self.dimensions.rows {
+            self.scroll_region = Some((top, bottom));
+        } else {
+            self.scroll_region = None;
+        }
+    }
+
+    /// Scroll rows `top..=bottom` up by `n`, blanking the rows at the bottom.
+    ///
+    /// Rows are moved, not copied; the rows scrolled out are blanked and
+    /// reused at the bottom. A full-screen scroll only rotates the ring. A
+    /// partial region (DECSTBM) rotates its row handles in place, so its cost
+    /// grows with the screen height but not with the width or with `n`.
+    fn scroll_rows_up(&mut self, top: u16, bottom: u16, n: u16) {
+        if top > bottom || bottom >= self.dimensions.rows {
+            return;
+        }
+        let n = n.min(bottom - top + 1) as usize;
+        let (top, bottom) = (top as usize, bottom as usize);
+
+        if top == 0 && bottom + 1 == self.rows.len() {
+            self.rows.rotate_left(n);
+        } else {
+            self.rows.make_contiguous()[top..=bottom].rotate_left(n);
+        }
+        for row in self.rows.range_mut(bottom + 1 - n..=bottom) {
+            row.clear(0);
+        }
+        // A scrolled-off row can't be continued by the new top row
+        self.rows[top].wrapped = false;
+        self.damage(top as u16..bottom as u16 + 1);
+    }
+
+    /// Scroll rows `top..=bottom` down by `n`, blanking the rows at the top.
+    ///
+    /// Costs the same as [`Grid::scroll_rows_up`].
+    fn scroll_rows_down(&mut self, top: u16, bottom: u16, n: u16) {
+        if top > bottom || bottom >= self.dimensions.rows {
+            return;
+        }
+        let n = n.min(bottom - top + 1) as usize;
+        let (top, bottom) = (top as usize, bottom as usize);
+
+        if top == 0 && bottom + 1 == self.rows.len() {
+            self.rows.rotate_right(n);
+        } else {
+            self.rows.make_contiguous()[top..=bottom].rotate_right(n);
+        }
+        for row in self.rows.range_mut(top..top + n) {
+            row.clear(0);
+        }
+        self.damage(top as u16..bottom as u16 + 1);
+    }
+
+    /// Scroll the scroll region up by `n` lines (SU, or LF at the bottom margin).
+    ///
+    /// On the primary screen, rows leaving a region that starts at the top go
+    /// to scrollback.
+    pub fn scroll_up(&mut self, n: u16) {
+        let (top, bottom) = self.scroll_region();
+        if top != 0 || self.saved_primary.is_some() || bottom >= self.dimensions.rows {
+            self.scroll_rows_up(top, bottom, n);
+            return;
+        }
+
+        let n = n.min(bottom + 1) as usize;
+        for row in self.rows.range(..n) {
+            self.scrollback
+                .push_back(PackedRow::pack(&row.cells(&self.styles), row.wrapped));
+        }
+        self.trim_scrollback();
+
+        // The new top row may still continue a row now in scrollback
+        let continued = n <= bottom as usize && self.rows[n].wrapped;
+        self.scroll_rows_up(top, bottom, n as u16);
+        self.rows[0].wrapped = continued;
+    }
+
+    /// Scroll the scroll region down by `n` lines (SD, or RI at the top margin).
+    pub fn scroll_down(&mut self, n: u16) {
+        let (top, bottom) = self.scroll_region();
+        self.scroll_rows_down(top, bottom, n);
+    }
+
+    /// Insert `n` blank lines at the cursor row (IL).
+    ///
+    /// Has no effect when the cursor is outside the scroll region.
+    pub fn insert_lines(&mut self, n: u16) {
+        let (top, bottom) = self.scroll_region();
+        let row = self.cursor.position.row;
+        if row >= top && row <= bottom {
+            self.scroll_rows_down(row, bottom, n);
+        }
+    }
+
+    /// Delete `n` lines at the cursor row (DL).
+    ///
+    /// Has no effect when the cursor is outside the scroll region.
+    pub fn delete_lines(&mut self, n: u16) {
+        let (top, bottom) = self.scroll_region();
+        let row = self.cursor.position.row;
+        if row >= top && row <= bottom {
+            self.scroll_rows_up(row, bottom, n);
+        }
+    }
+
+    /// Get the cursor row, recording damage to it, and the cursor column.
+    fn cursor_row(&mut self) -> (&mut Row, usize) {
+        let Position { row, col } = self.cursor.position;
+        self.damage(row..row + 1);
+        (&mut self.rows[row as usize], col as usize)
+    }
+
+    /// Insert `n` blank characters at the cursor, shifting the rest of the line right (ICH).
+    pub fn insert_chars(&mut self, n: u16) {
+        let (row, col) = self.cursor_row();
+        row.insert_blanks(col, n as usize);
+    }
+
+    /// Delete `n` characters at the cursor, shifting the rest of the line left (DCH).
+    pub fn delete_chars(&mut self, n: u16) {
+        let (row, col) = self.cursor_row();
+        row.delete(col, n as usize);
+    }
+
+    /// Erase `n` characters from the cursor without shifting (ECH).
+    pub fn erase_chars(&mut self, n: u16) {
+        let (row, col) = self.cursor_row();
+        row.fill(col, n as usize, ' ', DEFAULT_STYLE);
+    }
+
+    /// Fill the whole screen with a character (DECALN uses 'E').
+    pub fn fill(&mut self, character: char) {
+        for row in &mut self.rows {
+            row.fill(0, row.len(), character, DEFAULT_STYLE);
+            row.wrapped = false;
+        }
+        self.damage_all();
+    }
+
+    /// Set a tab stop at the given column (HTS).
+    pub fn set_tab_stop(&mut self, col: u16) {
+        if let Some(stop) = self.tab_stops.get_mut(col as usize) {
+            *stop = true;
+        }
+    }
+
+    /// Clear the tab stop at the given column (TBC 0).
+    pub fn clear_tab_stop(&mut self, col: u16) {
+        if let Some(stop) = self.tab_stops.get_mut(col as usize) {
+            *stop = false;
         }
     }
+
+    /// Clear all tab stops (TBC 3).
+    pub fn clear_all_tab_stops(&mut self) {
+        self.tab_stops.fill(false);
+    }
+
+    /// Column of the next tab stop after `col`, or the last column if there is none.
+    pub fn next_tab_stop(&self, col: u16) -> u16 {
+        let last = self.dimensions.cols.saturating_sub(1);
+        ((col + 1)..self.dimensions.cols)
+            .find(|&c| self.tab_stops[c as usize])
+            .unwrap_or(last)
+    }
+
+    /// Column of the previous tab stop before `col`, or column 0 if there is none.
+    pub fn prev_tab_stop(&self, col: u16) -> u16 {
+        (0..col.min(self.dimensions.cols))
+            .rev()
+            .find(|&c| self.tab_stops[c as usize])
+            .unwrap_or(0)
+    }
+}
+
+/// The subset of a cell's style reported in style runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RunStyle {
+    fg: Color,
+    bg: Color,
+    bold: bool,
+    reverse: bool,
+    underline: bool,
+    dim: bool,
+}
+
+impl RunStyle {
+    fn of(cell: &Cell) -> Self {
+        Self {
+            fg: cell.fg,
+            bg: cell.bg,
+            bold: cell.attrs.bold,
+            reverse: cell.attrs.reverse,
+            underline: cell.attrs.underline,
+            dim: cell.attrs.dim,
+        }
+    }
+
+    /// A blank whose foreground color cannot be seen.
+    fn is_invisible_blank(&self, cell: &Cell) -> bool {
+        cell.character.is_whitespace()
+            && self.bg == Color::Default
+            && !self.reverse
+            && !self.underline
+    }
+
+    fn to_run(self, start_col: u16, end_col: u16) -> StyleRun {
+        StyleRun {
+            start_col,
+            end_col,
+            fg: self.fg.label(),
+            bg: self.bg.label(),
+            bold: self.bold,
+            reverse: self.reverse,
+            underline: self.underline,
+            dim: self.dim,
+        }
+    }
+}
+
+/// Whether two cells have the same colors and attributes.
+fn same_style(a: &Cell, b: &Cell) -> bool {
+    a.fg == b.fg && a.bg == b.bg && a.attrs == b.attrs
+}
+
+/// Append an SGR sequence that sets `cell`'s style from a reset state.
+fn push_sgr(out: &mut String, cell: &Cell) {
+    let attrs = &cell.attrs;
+    let mut params: Vec<String> = vec!["0".to_string()];
+    for (on, code) in [
+        (attrs.bold, "1"),
+        (attrs.dim, "2"),
+        (attrs.italic, "3"),
+        (attrs.underline, "4"),
+        (attrs.blink, "5"),
+        (attrs.reverse, "7"),
+        (attrs.hidden, "8"),
+        (attrs.strikethrough, "9"),
+    ] {
+        if on {
+            params.push(code.to_string());
+        }
+    }
+    params.extend(color_sgr(cell.fg, 30));
+    params.extend(color_sgr(cell.bg, 40));
+
+    out.push_str("\x1b[");
+    out.push_str(&params.join(";"));
+    out.push('m');
+}
+
+/// SGR parameter for a color, where `base` is 30 for foreground and 40 for background.
+fn color_sgr(color: Color, base: u8) -> Option<String> {
+    match color {
+        Color::Default => None,
+        Color::Indexed(index) => Some(format!("{};5;{index}", base + 8)),
+        Color::Rgb { r, g, b } => Some(format!("{};2;{r};{g};{b}", base + 8)),
+        named => named.palette_index().map(|index| {
+            if index < 8 {
+                (base + index).to_string()
+            } else {
+                (base + 60 + index - 8).to_string()
+            }
+        }),
+    }
+}
+
+/// A screen of blank rows.
+fn blank_rows(dimensions: Dimensions, generation: u64) -> VecDeque<Row> {
+    (0..dimensions.rows)
+        .map(|_| Row::blank(dimensions.cols, generation))
+        .collect()
+}
+
+/// Keep the top-left part of the screen, padding with blanks to new dimensions.
+fn crop_rows(rows: &mut VecDeque<Row>, new: Dimensions) {
+    rows.resize_with(new.rows as usize, || Row::blank(new.cols, 0));
+    for row in rows.iter_mut() {
+        row.resize(new.cols as usize);
+    }
+}
+
+/// Rewrap rows to a new width.
+///
+/// Consecutive rows joined by soft wraps form a logical line, which is cut
+/// into rows of `cols` cells with trailing blanks dropped. Each cursor is a
+/// row index and column into `rows`; their new locations are returned, in
+/// the same order, alongside the rewrapped rows.
+fn reflow_rows(
+    rows: Vec<StoredRow>,
+    cols: usize,
+    cursors: &[(usize, usize)],
+) -> (Vec<StoredRow>, Vec<(usize, usize)>) {
+    let mut out = Vec::with_capacity(rows.len());
+    let mut new_cursors = cursors.to_vec();
+    let mut line: Vec<Cell> = Vec::new();
+    // Cursors on the current line, as (index into `cursors`, offset in line)
+    let mut line_cursors: Vec<(usize, usize)> = Vec::new();
+
+    let mut flush = |line: &mut Vec<Cell>, line_cursors: &mut Vec<(usize, usize)>| {
+        while line.last() == Some(&Cell::default()) {
+            line.pop();
+        }
+        let cursor_rows = line_cursors
+            .iter()
+            .map(|&(_, offset)| offset / cols + 1)
+            .max()
+            .unwrap_or(0);
+        let row_count = line.len().div_ceil(cols).max(cursor_rows).max(1);
+
+        for (idx, offset) in line_cursors.drain(..) {
+            new_cursors[idx] = (out.len() + offset / cols, offset % cols);
+        }
+        for idx in 0..row_count {
+            let start = (idx * cols).min(line.len());
+            let end = ((idx + 1) * cols).min(line.len());
+            out.push(StoredRow {
+                cells: line[start..end].to_vec(),
+                wrapped: idx > 0,
+            });
+        }
+        line.clear();
+    };
+
+    for (idx, row) in rows.into_iter().enumerate() {
+        if idx > 0 && !row.wrapped {
+            flush(&mut line, &mut line_cursors);
+        }
+        for (cursor, &(row, col)) in cursors.iter().enumerate() {
+            if row == idx {
+                line_cursors.push((cursor, line.len() + col));
+            }
+        }
+        line.extend(row.cells);
+    }
+    if !line.is_empty() || !line_cursors.is_empty() {
+        flush(&mut line, &mut line_cursors);
+    }
+
+    (out, new_cursors)
 }
 
 #[cfg(test)]
@@ -323,16 +1050,14 @@ mod tests {
         assert_eq!(cell.character, ' ');
 
         // Modify cell
-        if let Some(cell) = grid.cell_mut(5, 5) {
-            cell.character = 'X';
-        }
+        grid.set_cell(5, 5, Cell::new('X'));
 
         // Verify modification
         assert_eq!(grid.cell(5, 5).unwrap().character, 'X');
 
         // Out of bounds
         assert!(grid.cell(10, 10).is_none());
-        assert!(grid.cell_mut(10, 10).is_none());
+        grid.set_cell(10, 10, Cell::new('X'));
     }
 
     #[test]
@@ -341,9 +1066,7 @@ mod tests {
 
         // Modify a row
         for col in 0..10 {
-            if let Some(cell) = grid.cell_mut(2, col) {
-                cell.character = (b'0' + col as u8) as char;
-            }
+            grid.set_cell(2, col, Cell::new((b'0' + col as u8) as char));
         }
 
         // Get row
@@ -363,9 +1086,7 @@ mod tests {
         // Write "HELLO" at row 1
         let text = "HELLO";
         for (i, ch) in text.chars().enumerate() {
-            if let Some(cell) = grid.cell_mut(1, i as u16) {
-                cell.character = ch;
-            }
+            grid.set_cell(1, i as u16, Cell::new(ch));
         }
 
         // Extract the region
@@ -381,9 +1102,11 @@ mod tests {
         // Write pattern
         for row in 0..3 {
             for col in 0..5 {
-                if let Some(cell) = grid.cell_mut(row, col) {
-                    cell.character = if (row + col) % 2 == 0 { 'X' } else { 'O' };
-                }
+                grid.set_cell(
+                    row,
+                    col,
+                    Cell::new(if (row + col) % 2 == 0 { 'X' } else { 'O' }),
+                );
             }
         }
 
@@ -395,6 +1118,70 @@ mod tests {
         assert_eq!(lines[2], "XOXOX");
     }
 
+    #[test]
+    fn test_grid_to_ansi() {
+        let mut grid = Grid::new(Dimensions::new(3, 6));
+        grid.set_cell(0, 0, Cell::new('o'));
+        grid.set_cell(0, 1, Cell::new('k'));
+        let mut red = Cell::with_fg('E', Color::Red);
+        red.attrs = red.attrs.with_bold();
+        grid.set_cell(1, 0, red.clone());
+        red.character = 'R';
+        grid.set_cell(1, 1, red);
+        let mut highlight = Cell::new(' ');
+        highlight.bg = Color::Indexed(236);
+        grid.set_cell(1, 2, highlight);
+
+        assert_eq!(
+            grid.to_ansi(),
+            "ok\n\x1b[0;1;31mER\x1b[0;48;5;236m \x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_grid_to_ansi_joins_wrapped_rows() {
+        let mut grid = Grid::new(Dimensions::new(2, 3));
+        for (col, c) in "ab ".chars().enumerate() {
+            grid.set_cell(0, col as u16, Cell::new(c));
+        }
+        grid.set_cell(1, 0, Cell::with_fg('c', Color::Rgb { r: 1, g: 2, b: 3 }));
+        grid.set_line_wrapped(1, true);
+
+        assert_eq!(grid.to_ansi(), "ab \x1b[0;38;2;1;2;3mc\x1b[0m");
+    }
+
+    #[test]
+    fn test_grid_style_rows() {
+        let mut grid = Grid::new(Dimensions::new(3, 12));
+        // "error: bad" in red, with the space between words kept in the run
+        for (col, c) in "error: bad  ".chars().enumerate() {
+            grid.set_cell(0, col as u16, Cell::with_fg(c, Color::Red));
+        }
+        // A reverse-video selection followed by dimmed text
+        let mut selected = Cell::new('>');
+        selected.attrs = selected.attrs.with_reverse();
+        grid.set_cell(2, 0, selected);
+        let mut dim = Cell::with_fg('x', Color::Indexed(244));
+        dim.attrs.dim = true;
+        grid.set_cell(2, 2, dim);
+
+        let rows = grid.style_rows();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].row, 0);
+        assert_eq!(rows[0].runs.len(), 1);
+        assert_eq!(rows[0].runs[0].start_col, 0);
+        assert_eq!(rows[0].runs[0].end_col, 10);
+        assert_eq!(rows[0].runs[0].fg.as_deref(), Some("red"));
+
+        assert_eq!(rows[1].row, 2);
+        assert_eq!(rows[1].runs.len(), 2);
+        assert!(rows[1].runs[0].reverse);
+        assert_eq!((rows[1].runs[0].start_col, rows[1].runs[0].end_col), (0, 1));
+        assert!(rows[1].runs[1].dim);
+        assert_eq!(rows[1].runs[1].fg.as_deref(), Some("#808080"));
+        assert_eq!(rows[1].runs[1].start_col, 2);
+    }
+
     #[test]
     fn test_grid_resize_preserve() {
         let mut grid = Grid::new(Dimensions::new(5, 5));
@@ -402,9 +1189,7 @@ mod tests {
         // Fill with pattern
         for row in 0..5 {
             for col in 0..5 {
-                if let Some(cell) = grid.cell_mut(row, col) {
-                    cell.character = 'A';
-                }
+                grid.set_cell(row, col, Cell::new('A'));
             }
         }
 
@@ -426,9 +1211,7 @@ mod tests {
         let mut grid = Grid::new(Dimensions::new(10, 10));
 
         // Set marker cell
-        if let Some(cell) = grid.cell_mut(2, 2) {
-            cell.character = 'M';
-        }
+        grid.set_cell(2, 2, Cell::new('M'));
 
         // Resize to smaller
         grid.resize(Dimensions::new(5, 5));
@@ -480,9 +1263,7 @@ mod tests {
         // Fill grid
         for row in 0..5 {
             for col in 0..5 {
-                if let Some(cell) = grid.cell_mut(row, col) {
-                    cell.character = 'X';
-                }
+                grid.set_cell(row, col, Cell::new('X'));
             }
         }
 
@@ -504,9 +1285,7 @@ mod tests {
         // Fill grid
         for row in 0..5 {
             for col in 0..5 {
-                if let Some(cell) = grid.cell_mut(row, col) {
-                    cell.character = 'X';
-                }
+                grid.set_cell(row, col, Cell::new('X'));
             }
         }
 
@@ -522,4 +1301,262 @@ mod tests {
         assert_eq!(grid.cell(0, 0).unwrap().character, 'X');
         assert_eq!(grid.cell(4, 4).unwrap().character, 'X');
     }
+
+    fn grid_with_rows(rows: &[&str], cols: u16) -> Grid {
+        let mut grid = Grid::new(Dimensions::new(rows.len() as u16, cols));
+        for (r, text) in rows.iter().enumerate() {
+            for (c, ch) in text.chars().enumerate() {
+                grid.set_cell(r as u16, c as u16, Cell::new(ch));
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_grid_scroll_region_up_down() {
+        let mut grid = grid_with_rows(&["a", "b", "c", "d"], 3);
+        grid.set_scroll_region(1, 2);
+
+        grid.scroll_up(1);
+        assert_eq!(grid.to_plain_text(), "a\nc\n\nd");
+
+        grid.scroll_down(1);
+        assert_eq!(grid.to_plain_text(), "a\n\nc\nd");
+    }
+
+    #[test]
+    fn test_grid_insert_delete_lines() {
+        let mut grid = grid_with_rows(&["a", "b", "c"], 3);
+        grid.cursor_mut().position = Position::new(1, 0);
+
+        grid.insert_lines(1);
+        assert_eq!(grid.to_plain_text(), "a\n\nb");
+
+        grid.delete_lines(2);
+        assert_eq!(grid.to_plain_text(), "a\n\n");
+    }
+
+    #[test]
+    fn test_grid_insert_delete_erase_chars() {
+        let mut grid = grid_with_rows(&["abcde"], 5);
+        grid.cursor_mut().position = Position::new(0, 1);
+
+        grid.insert_chars(2);
+        assert_eq!(grid.to_plain_text(), "a  bc");
+
+        grid.delete_chars(2);
+        assert_eq!(grid.to_plain_text(), "abc");
+
+        grid.erase_chars(1);
+        assert_eq!(grid.to_plain_text(), "a c");
+    }
+
+    #[test]
+    fn test_grid_tab_stops() {
+        let mut grid = Grid::new(Dimensions::new(2, 30));
+        assert_eq!(grid.next_tab_stop(0), 8);
+        assert_eq!(grid.prev_tab_stop(8), 0);
+
+        grid.set_tab_stop(3);
+        assert_eq!(grid.next_tab_stop(0), 3);
+
+        grid.clear_all_tab_stops();
+        assert_eq!(grid.next_tab_stop(0), 29);
+    }
+
+    fn row_string(grid: &Grid, row: u16) -> String {
+        grid.row(row)
+            .unwrap()
+            .iter()
+            .map(|c| c.character)
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn test_grid_reflow_narrower() {
+        let mut grid = grid_with_rows(&["abcdef", "gh", ""], 6);
+        grid.set_line_wrapped(1, true);
+        grid.cursor_mut().position = Position::new(1, 2);
+
+        grid.resize(Dimensions::new(3, 4));
+        assert_eq!(row_string(&grid, 0), "abcd");
+        assert_eq!(row_string(&grid, 1), "efgh");
+        assert!(grid.is_line_wrapped(1));
+        // The cursor stays right after "gh"
+        assert_eq!(grid.cursor().position, Position::new(2, 0));
+        assert!(grid.is_line_wrapped(2));
+    }
+
+    #[test]
+    fn test_grid_reflow_wider() {
+        let mut grid = grid_with_rows(&["abcd", "ef", "xy"], 4);
+        grid.set_line_wrapped(1, true);
+        grid.cursor_mut().position = Position::new(2, 2);
+
+        grid.resize(Dimensions::new(3, 8));
+        assert_eq!(row_string(&grid, 0), "abcdef");
+        assert_eq!(row_string(&grid, 1), "xy");
+        assert_eq!(row_string(&grid, 2), "");
+        assert!(!grid.is_line_wrapped(1));
+        assert_eq!(grid.cursor().position, Position::new(1, 2));
+    }
+
+    #[test]
+    fn test_grid_reflow_through_scrollback() {
+        let mut grid = grid_with_rows(&["abcd", "efgh"], 4);
+        grid.set_line_wrapped(1, true);
+        grid.cursor_mut().position = Position::new(1, 3);
+
+        // Narrowing pushes the top of the line into scrollback
+        grid.resize(Dimensions::new(2, 2));
+        assert_eq!(grid.scrollback_len(), 2);
+        assert_eq!(grid.scrollback_row(0).unwrap()[0].character, 'a');
+        assert_eq!(row_string(&grid, 0), "ef");
+        assert_eq!(row_string(&grid, 1), "gh");
+        assert_eq!(grid.cursor().position, Position::new(1, 1));
+
+        // Widening pulls it back
+        grid.resize(Dimensions::new(2, 4));
+        assert_eq!(grid.scrollback_len(), 0);
+        assert_eq!(row_string(&grid, 0), "abcd");
+        assert_eq!(row_string(&grid, 1), "efgh");
+        assert_eq!(grid.cursor().position, Position::new(1, 3));
+    }
+
+    #[test]
+    fn test_grid_scroll_up_saves_scrollback() {
+        let mut grid = grid_with_rows(&["a", "b", "c"], 3);
+        grid.scroll_up(1);
+        assert_eq!(grid.scrollback_len(), 1);
+        assert_eq!(grid.scrollback_row(0).unwrap()[0].character, 'a');
+
+        // Rows leaving a region below the top are lost, as in xterm
+        grid.set_scroll_region(1, 2);
+        grid.scroll_up(1);
+        assert_eq!(grid.scrollback_len(), 1);
+
+        grid.set_scrollback_limit(0);
+        assert_eq!(grid.scrollback_len(), 0);
+    }
+
+    #[test]
+    fn test_grid_scrollback_keeps_styles() {
+        let mut grid = Grid::new(Dimensions::new(2, 4));
+        grid.set_cell(0, 1, Cell::with_fg('r', Color::Red));
+        grid.set_line_wrapped(1, true);
+        grid.scroll_up(2);
+
+        let row = grid.scrollback_row(0).unwrap();
+        assert_eq!(row.len(), 4);
+        assert_eq!(row[1], Cell::with_fg('r', Color::Red));
+        assert_eq!(row[2], Cell::default());
+        assert_eq!(grid.scrollback_len(), 2);
+        assert_eq!(grid.row(0).unwrap(), vec![Cell::default(); 4]);
+    }
+
+    #[test]
+    fn test_grid_keeps_styles_across_style_compaction() {
+        let mut grid = Grid::new(Dimensions::new(2, 4));
+        grid.set_cell(1, 0, Cell::with_fg('p', Color::Red));
+        grid.enter_alternate_screen();
+        grid.set_cell(1, 0, Cell::with_fg('a', Color::Blue));
+
+        let rgb = |i: u32| Color::Rgb {
+            r: i as u8,
+            g: (i >> 8) as u8,
+            b: 1,
+        };
+        for i in 0..5000 {
+            grid.set_cell(0, 0, Cell::with_fg('x', rgb(i)));
+        }
+        assert_eq!(grid.cell(0, 0), Some(Cell::with_fg('x', rgb(4999))));
+        assert_eq!(grid.cell(1, 0), Some(Cell::with_fg('a', Color::Blue)));
+
+        grid.exit_alternate_screen();
+        assert_eq!(grid.cell(1, 0), Some(Cell::with_fg('p', Color::Red)));
+    }
+
+    #[test]
+    fn test_grid_alternate_screen() {
+        let mut grid = grid_with_rows(&["abcd", "efgh"], 4);
+        grid.set_line_wrapped(1, true);
+
+        grid.enter_alternate_screen();
+        assert!(grid.is_alternate_screen());
+        assert_eq!(row_string(&grid, 0), "");
+        grid.set_cell(0, 0, Cell::new('X'));
+        grid.scroll_up(1);
+        assert_eq!(grid.scrollback_len(), 0);
+
+        // Only the primary screen is reflowed
+        grid.resize(Dimensions::new(2, 8));
+        grid.exit_alternate_screen();
+        assert!(!grid.is_alternate_screen());
+        assert_eq!(row_string(&grid, 0), "abcdefgh");
+    }
+
+    #[test]
+    fn test_grid_reflow_moves_saved_cursor() {
+        let mut grid = grid_with_rows(&["abcd", "efgh", "xy"], 4);
+        grid.set_line_wrapped(1, true);
+        grid.cursor_mut().position = Position::new(1, 1);
+        grid.save_cursor();
+        grid.cursor_mut().position = Position::new(2, 2);
+
+        grid.resize(Dimensions::new(3, 8));
+        assert_eq!(grid.cursor().position, Position::new(1, 2));
+        grid.restore_cursor();
+        // Still on the 'f'
+        assert_eq!(grid.cursor().position, Position::new(0, 5));
+
+        // Saved from the primary screen before switching (mode 1049)
+        grid.save_cursor();
+        grid.enter_alternate_screen();
+        grid.resize(Dimensions::new(3, 4));
+        grid.exit_alternate_screen();
+        grid.restore_cursor();
+        assert_eq!(row_string(&grid, 1), "efgh");
+        assert_eq!(grid.cursor().position, Position::new(1, 1));
+    }
+
+    #[test]
+    fn test_grid_damage_tracking() {
+        let mut grid = grid_with_rows(&["a", "b", "c"], 3);
+        let generation = grid.generation();
+        assert!(!grid.changed_since(generation));
+
+        grid.set_cell(1, 0, Cell::new('x'));
+        assert!(grid.changed_since(generation));
+        assert_eq!(grid.damaged_rows(generation), vec![1]);
+
+        let generation = grid.generation();
+        grid.set_scroll_region(1, 2);
+        grid.scroll_up(1);
+        assert_eq!(grid.damaged_rows(generation), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_grid_set_cell_ignores_identical_content() {
+        let mut grid = grid_with_rows(&["a"], 3);
+        let generation = grid.generation();
+        let cell = grid.cell(0, 0).unwrap().clone();
+
+        grid.set_cell(0, 0, cell);
+        grid.set_line_wrapped(0, false);
+        assert!(!grid.changed_since(generation));
+
+        grid.set_cell(0, 1, Cell::new('b'));
+        assert_eq!(grid.damaged_rows(generation), vec![0]);
+    }
+
+    #[test]
+    fn test_grid_reset_keeps_generation_increasing() {
+        let mut grid = grid_with_rows(&["a"], 3);
+        let generation = grid.generation();
+        grid.reset();
+        assert!(grid.changed_since(generation));
+        assert_eq!(grid.damaged_rows(generation), vec![0]);
+    }
 }
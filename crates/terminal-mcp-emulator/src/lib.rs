@@ -7,6 +7,7 @@
 //! - Terminal grid state management
 //! - PTY (pseudo-terminal) lifecycle management
 //! - Cell and color types for terminal rendering
+//! - PNG and SVG rendering of the screen
 //!
 //! ## Architecture
 //!
@@ -16,13 +17,21 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+pub mod charset;
 pub mod grid;
+pub mod limits;
 pub mod parser;
 pub mod pty;
 pub mod recording;
+pub mod render;
+mod row;
+pub mod sandbox;
 
 // Re-export commonly used types
+pub use charset::{Charset, CharsetState};
 pub use grid::{Cursor, CursorStyle, Grid};
+pub use limits::SessionCgroup;
 pub use parser::Parser;
 pub use pty::PtyHandle;
 pub use recording::{AsciinemaHeader, RecordEvent, SessionRecorder};
+pub use render::ScreenRenderer;
@@ -0,0 +1,348 @@
+//! Resource limits for PTY children.
+//!
+//! Rlimits are set by a small `sh` wrapper that calls `ulimit` (setrlimit)
+//! and then execs the command, so they apply from the first instruction and
+//! are inherited by everything it starts. Session-wide memory and process
+//! limits use a cgroup v2 created under a delegated parent; the wrapper moves
+//! itself into it before exec, so no child can escape it.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tracing::{debug, warn};
+
+use terminal_mcp_core::{Error, LimitKind, ResourceLimits, Result};
+
+/// Rewrite `command args` to run with the rlimits in `limits`, inside `cgroup`.
+///
+/// Returns the command unchanged when there is nothing to apply.
+pub fn wrap_command(
+    command: &str,
+    args: &[String],
+    limits: &ResourceLimits,
+    cgroup: Option<&SessionCgroup>,
+) -> (String, Vec<String>) {
+    let mut script = String::new();
+    if let Some(cgroup) = cgroup {
+        let procs = cgroup.path.join("cgroup.procs");
+        script.push_str(&format!(
+            "echo $$ > '{}' || exit 126\n",
+            procs.display().to_string().replace('\'', r"'\''")
+        ));
+    }
+    if let Some(seconds) = limits.cpu_seconds {
+        // SIGXCPU at the soft limit reports the cause; SIGKILL a second later if ignored
+        script.push_str(&format!(
+            "ulimit -S -t {seconds} && ulimit -H -t {} || exit 126\n",
+            seconds.saturating_add(1)
+        ));
+    }
+    if let Some(mb) = limits.address_space_mb {
+        script.push_str(&format!("ulimit -v {} || exit 126\n", mb * 1024));
+    }
+    if let Some(files) = limits.open_files {
+        script.push_str(&format!("ulimit -n {files} || exit 126\n"));
+    }
+    if let Some(processes) = limits.processes {
+        // bash and zsh use -u, dash uses -p
+        script.push_str(&format!(
+            "ulimit -u {processes} 2>/dev/null || ulimit -p {processes} || exit 126\n"
+        ));
+    }
+
+    if script.is_empty() {
+        return (command.to_string(), args.to_vec());
+    }
+    script.push_str("exec \"$@\"\n");
+
+    let mut wrapped = vec!["-c".to_string(), script, "limits".to_string()];
+    wrapped.push(command.to_string());
+    wrapped.extend(args.iter().cloned());
+    ("sh".to_string(), wrapped)
+}
+
+/// Distinguishes cgroups of sessions created by this process.
+static CGROUP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Controllers the session cgroups need from their parent.
+const CONTROLLERS: [&str; 2] = ["memory", "pids"];
+
+/// Leaf the server moves into when it lives in the delegated parent itself.
+const SERVER_LEAF: &str = "server";
+
+/// A cgroup v2 holding one session's processes.
+///
+/// Removed, along with anything still running in it, when dropped.
+#[derive(Debug)]
+pub struct SessionCgroup {
+    path: PathBuf,
+}
+
+impl SessionCgroup {
+    /// Create a cgroup under `parent` with the memory and process limits.
+    ///
+    /// `parent` must be a cgroup v2 directory the server may write to, such
+    /// as one delegated by systemd (`Delegate=yes`). If the server runs in
+    /// `parent` itself, it moves into a `server` child first: cgroup v2 only
+    /// hands controllers to the children of a cgroup without processes.
+    pub fn create(parent: &Path, limits: &ResourceLimits) -> Result<Self> {
+        let name = format!(
+            "session-{}-{}",
+            std::process::id(),
+            CGROUP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = parent.join(name);
+
+        enable_controllers(parent, std::process::id())?;
+
+        std::fs::create_dir(&path).map_err(|e| {
+            Error::PtyError(format!("Failed to create cgroup {}: {e}", path.display()))
+        })?;
+        let cgroup = Self { path };
+
+        if let Some(mb) = limits.memory_mb {
+            cgroup.write("memory.max", &(mb * 1024 * 1024).to_string())?;
+            // Without this the limit only pushes memory into swap
+            let _ = cgroup.write("memory.swap.max", "0");
+        }
+        if let Some(pids) = limits.max_pids {
+            cgroup.write("pids.max", &pids.to_string())?;
+        }
+
+        debug!("Created session cgroup {}", cgroup.path.display());
+        Ok(cgroup)
+    }
+
+    /// Directory of the cgroup.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The first limit the session ran into, if any.
+    pub fn limit_hit(&self) -> Option<LimitKind> {
+        if self.event_count("memory.events", "oom_kill") > 0 {
+            return Some(LimitKind::Memory);
+        }
+        if self.event_count("pids.events", "max") > 0 {
+            return Some(LimitKind::Processes);
+        }
+        None
+    }
+
+    fn write(&self, file: &str, value: &str) -> Result<()> {
+        std::fs::write(self.path.join(file), value).map_err(|e| {
+            Error::PtyError(format!(
+                "Failed to set {file} on {}: {e}",
+                self.path.display()
+            ))
+        })
+    }
+
+    /// Read a counter from a flat-keyed events file.
+    fn event_count(&self, file: &str, key: &str) -> u64 {
+        std::fs::read_to_string(self.path.join(file))
+            .ok()
+            .and_then(|events| {
+                events.lines().find_map(|line| {
+                    let (name, count) = line.split_once(' ')?;
+                    (name == key).then(|| count.trim().parse().ok()).flatten()
+                })
+            })
+            .unwrap_or(0)
+    }
+}
+
+/// Let the children of `parent` use the memory and pids controllers, moving
+/// `server_pid` out of `parent` if it is in the way.
+fn enable_controllers(parent: &Path, server_pid: u32) -> Result<()> {
+    let control = parent.join("cgroup.subtree_control");
+    let enabled = std::fs::read_to_string(&control).map_err(|e| {
+        Error::PtyError(format!(
+            "Failed to read {}: {e}; cgroup_parent must be a cgroup v2 directory",
+            control.display()
+        ))
+    })?;
+    if CONTROLLERS
+        .iter()
+        .all(|controller| enabled.split_whitespace().any(|c| c == *controller))
+    {
+        return Ok(());
+    }
+
+    let procs = std::fs::read_to_string(parent.join("cgroup.procs")).unwrap_or_default();
+    if procs
+        .lines()
+        .any(|pid| pid.trim() == server_pid.to_string())
+    {
+        let leaf = parent.join(SERVER_LEAF);
+        if let Err(e) = std::fs::create_dir(&leaf) {
+            if e.kind() != std::io::ErrorKind::AlreadyExists {
+                return Err(Error::PtyError(format!(
+                    "Failed to create cgroup {}: {e}",
+                    leaf.display()
+                )));
+            }
+        }
+        std::fs::write(leaf.join("cgroup.procs"), server_pid.to_string()).map_err(|e| {
+            Error::PtyError(format!(
+                "Failed to move the server into {}: {e}",
+                leaf.display()
+            ))
+        })?;
+        debug!("Moved the server into {}", leaf.display());
+    }
+
+    let add: Vec<String> = CONTROLLERS.iter().map(|c| format!("+{c}")).collect();
+    std::fs::write(&control, add.join(" ")).map_err(|e| {
+        Error::PtyError(format!(
+            "Failed to enable the memory and pids controllers in {}: {e}; \
+             the cgroup must be delegated and hold no other processes",
+            control.display()
+        ))
+    })
+}
+
+impl Drop for SessionCgroup {
+    fn drop(&mut self) {
+        // cgroup.kill needs Linux 5.14; older kernels leave stragglers to the PTY hangup
+        let _ = std::fs::write(self.path.join("cgroup.kill"), "1");
+        for _ in 0..20 {
+            if std::fs::remove_dir(&self.path).is_ok() {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        warn!("Failed to remove session cgroup {}", self.path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh cgroup under this process's own, with the memory and pids
+    /// controllers available; `None` where cgroup v2 isn't delegated to us.
+    #[cfg(target_os = "linux")]
+    fn test_cgroup_parent() -> Option<PathBuf> {
+        let mountinfo = std::fs::read_to_string("/proc/self/mountinfo").ok()?;
+        let mount = mountinfo
+            .lines()
+            .find(|line| {
+                line.split(" - ")
+                    .nth(1)
+                    .is_some_and(|fs| fs.starts_with("cgroup2 "))
+            })?
+            .split_whitespace()
+            .nth(4)?
+            .to_string();
+        let own = std::fs::read_to_string("/proc/self/cgroup").ok()?;
+        let own = own.lines().find_map(|line| line.strip_prefix("0::"))?;
+        let parent = Path::new(&mount)
+            .join(own.trim_start_matches('/'))
+            .join(format!("terminal-mcp-test-{}", std::process::id()));
+        std::fs::create_dir(&parent).ok()?;
+        let controllers =
+            std::fs::read_to_string(parent.join("cgroup.controllers")).unwrap_or_default();
+        if CONTROLLERS
+            .iter()
+            .all(|c| controllers.split_whitespace().any(|have| have == *c))
+        {
+            Some(parent)
+        } else {
+            let _ = std::fs::remove_dir(&parent);
+            None
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_cgroup_in_server_cgroup() {
+        let Some(parent) = test_cgroup_parent() else {
+            return;
+        };
+        // Stands in for a server living in its own delegated cgroup
+        let mut server = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        std::fs::write(parent.join("cgroup.procs"), server.id().to_string()).unwrap();
+
+        enable_controllers(&parent, server.id()).unwrap();
+        let leaf = parent.join(SERVER_LEAF);
+        let procs = std::fs::read_to_string(leaf.join("cgroup.procs")).unwrap();
+        assert_eq!(procs.trim(), server.id().to_string());
+
+        let limits = ResourceLimits {
+            memory_mb: Some(64),
+            max_pids: Some(16),
+            ..Default::default()
+        };
+        let cgroup = SessionCgroup::create(&parent, &limits).unwrap();
+        let memory_max = std::fs::read_to_string(cgroup.path().join("memory.max")).unwrap();
+        assert_eq!(memory_max.trim(), (64 * 1024 * 1024).to_string());
+        drop(cgroup);
+
+        server.kill().unwrap();
+        server.wait().unwrap();
+        std::fs::remove_dir(&leaf).unwrap();
+        std::fs::remove_dir(&parent).unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_cgroup_parent_must_be_cgroup() {
+        let dir = std::env::temp_dir().join(format!("not-a-cgroup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let limits = ResourceLimits {
+            memory_mb: Some(64),
+            ..Default::default()
+        };
+        assert!(SessionCgroup::create(&dir, &limits).is_err());
+        std::fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_wrap_command_without_limits() {
+        let (program, args) =
+            wrap_command("ls", &["-l".to_string()], &ResourceLimits::default(), None);
+        assert_eq!(program, "ls");
+        assert_eq!(args, ["-l"]);
+
+        // Output and cgroup limits don't need a wrapper
+        let limits = ResourceLimits {
+            output_bytes_per_sec: Some(1024),
+            ..Default::default()
+        };
+        assert_eq!(wrap_command("ls", &[], &limits, None).0, "ls");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_wrap_command_sets_rlimits() {
+        let limits = ResourceLimits {
+            cpu_seconds: Some(30),
+            address_space_mb: Some(4096),
+            open_files: Some(64),
+            processes: Some(500),
+            ..Default::default()
+        };
+        let (program, args) = wrap_command(
+            "sh",
+            &[
+                "-c".to_string(),
+                "ulimit -t; ulimit -v; ulimit -n".to_string(),
+            ],
+            &limits,
+            None,
+        );
+        assert_eq!(program, "sh");
+        assert_eq!(&args[2..4], ["limits", "sh"]);
+
+        let output = std::process::Command::new(program)
+            .args(&args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "30\n4194304\n64\n");
+    }
+}
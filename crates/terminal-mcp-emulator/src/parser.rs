@@ -2,21 +2,95 @@
 
 use vte::{Params, Perform};
 
-use terminal_mcp_core::{Cell, CellAttributes, Color, Position};
+use terminal_mcp_core::{
+    Cell, CellAttributes, Color, Dimensions, KeyboardMode, Notification, Position, TerminalEvent,
+    TerminalEvents,
+};
 
-use crate::grid::Grid;
+use crate::charset::{Charset, CharsetState};
+use crate::grid::{CursorStyle, Grid};
+
+/// Maximum depth of the kitty keyboard flags stack.
+const KITTY_STACK_LIMIT: usize = 16;
+
+/// Number of recent notifications kept for snapshots.
+const NOTIFICATION_HISTORY: usize = 32;
+
+/// Number of undrained events kept before the oldest are dropped.
+const PENDING_EVENT_LIMIT: usize = 256;
 
 /// ANSI parser wrapping VTE state machine.
-#[derive(Debug)]
 pub struct Parser {
     /// Terminal grid state
     grid: Grid,
+    /// VTE state machine, kept across calls so sequences split between reads survive
+    vte: vte::Parser,
+    /// Keyboard reporting mode negotiated by the application
+    keyboard: KeyboardMode,
+    /// Previously pushed kitty keyboard flags
+    kitty_stack: Vec<u8>,
+    /// Replies to terminal queries, to be written back to the application
+    responses: Vec<u8>,
+    /// Bracketed paste mode (DECSET 2004)
+    bracketed_paste: bool,
+    /// Origin mode (DECOM): cursor addressing relative to the scroll region
+    origin_mode: bool,
+    /// Origin mode saved by DECSC
+    saved_origin_mode: bool,
+    /// Auto-wrap mode (DECAWM)
+    autowrap: bool,
+    /// A character was printed in the last column; the next one wraps first
+    pending_wrap: bool,
+    /// Last printed character, repeated by REP
+    last_printed: Option<char>,
+    /// G0–G3 character set designations and shifts
+    charsets: CharsetState,
+    /// Character sets saved by DECSC
+    saved_charsets: CharsetState,
+    /// The VTE state machine is known to be in its ground state, so
+    /// printable ASCII can bypass it
+    ground: bool,
+    /// Bell and notification totals
+    events: TerminalEvents,
+    /// Events not yet taken by `take_events`
+    pending_events: Vec<TerminalEvent>,
+}
+
+impl std::fmt::Debug for Parser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Parser")
+            .field("grid", &self.grid)
+            .field("keyboard", &self.keyboard)
+            .field("kitty_stack", &self.kitty_stack)
+            .field("responses", &self.responses)
+            .field("bracketed_paste", &self.bracketed_paste)
+            .field("origin_mode", &self.origin_mode)
+            .field("autowrap", &self.autowrap)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Parser {
     /// Create a new parser with the given grid.
     pub fn new(grid: Grid) -> Self {
-        Self { grid }
+        Self {
+            grid,
+            vte: vte::Parser::new(),
+            keyboard: KeyboardMode::default(),
+            kitty_stack: Vec::new(),
+            responses: Vec::new(),
+            bracketed_paste: false,
+            origin_mode: false,
+            saved_origin_mode: false,
+            autowrap: true,
+            pending_wrap: false,
+            last_printed: None,
+            charsets: CharsetState::default(),
+            saved_charsets: CharsetState::default(),
+            ground: true,
+            events: TerminalEvents::default(),
+            pending_events: Vec::new(),
+        }
     }
 
     /// Get a reference to the grid.
@@ -29,6 +103,22 @@ impl Parser {
         &mut self.grid
     }
 
+    /// Resize the grid, reflowing the primary screen.
+    pub fn resize(&mut self, dimensions: Dimensions) {
+        self.grid.resize(dimensions);
+
+        // A pending wrap leaves the cursor on the last character of a full
+        // row. If reflow moved that character off the last column, the
+        // cursor belongs just after it instead.
+        if self.pending_wrap {
+            let cursor = &mut self.grid.cursor_mut().position;
+            if cursor.col + 1 < dimensions.cols {
+                cursor.col += 1;
+                self.pending_wrap = false;
+            }
+        }
+    }
+
     /// Consume the parser and return the grid.
     pub fn into_grid(self) -> Grid {
         self.grid
@@ -36,17 +126,198 @@ impl Parser {
 
     /// Process bytes through the VTE parser.
     ///
+    /// Runs of printable ASCII in the ground state are written a row at a
+    /// time without going through VTE.
+    ///
     /// Returns the number of bytes consumed.
     pub fn process(&mut self, bytes: &[u8]) -> usize {
-        let mut parser = vte::Parser::new();
-        for byte in bytes {
-            parser.advance(self, *byte);
+        let cursor_before = self.grid.cursor().clone();
+
+        let mut vte = std::mem::take(&mut self.vte);
+        let mut rest = bytes;
+        while let Some((&byte, tail)) = rest.split_first() {
+            if self.ground {
+                let run = rest.iter().take_while(|b| is_printable_ascii(**b)).count();
+                if run > 0 {
+                    self.print_ascii(&rest[..run]);
+                    rest = &rest[run..];
+                    continue;
+                }
+            }
+            // Only callbacks that end in the ground state set this again
+            self.ground = false;
+            vte.advance(self, byte);
+            rest = tail;
+        }
+        self.vte = vte;
+
+        // Cursor moves and visibility changes count as screen changes too
+        if *self.grid.cursor() != cursor_before {
+            self.grid.touch();
         }
         bytes.len()
     }
 
-    /// Move cursor forward by n columns (wrapping if needed).
+    /// Keyboard reporting mode currently requested by the application.
+    pub fn keyboard_mode(&self) -> KeyboardMode {
+        self.keyboard
+    }
+
+    /// Whether the application has enabled bracketed paste mode (DECSET 2004).
+    pub fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
+    /// Take pending replies to terminal queries (device attributes, keyboard
+    /// protocol queries, cursor position reports).
+    ///
+    /// The caller is responsible for writing them to the PTY.
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.responses)
+    }
+
+    /// Print a run of printable ASCII, filling whole rows at a time.
+    ///
+    /// Equivalent to calling `print` for each byte.
+    fn print_ascii(&mut self, text: &[u8]) {
+        let cols = self.grid.dimensions().cols;
+        if !self.autowrap || !self.charsets.is_ascii() || cols == 0 {
+            for &byte in text {
+                self.print(byte as char);
+            }
+            return;
+        }
+
+        let last_col = cols - 1;
+        let mut rest = text;
+        while !rest.is_empty() {
+            if self.pending_wrap && self.grid.cursor().position.col == last_col {
+                self.grid.cursor_mut().position.col = 0;
+                self.linefeed();
+                let row = self.grid.cursor().position.row;
+                self.grid.set_line_wrapped(row, true);
+            }
+            self.pending_wrap = false;
+
+            let Position { row, col } = self.grid.cursor().position;
+            let written = self.grid.write_ascii(row, col, rest);
+            rest = &rest[written..];
+
+            let end = col as usize + written;
+            if end < cols as usize {
+                self.grid.cursor_mut().position.col = end as u16;
+            } else {
+                self.grid.cursor_mut().position.col = last_col;
+                self.pending_wrap = true;
+            }
+        }
+        self.last_printed = text.last().map(|&byte| byte as char);
+    }
+
+    /// Bells and recent notifications received so far.
+    pub fn events(&self) -> &TerminalEvents {
+        &self.events
+    }
+
+    /// Take events raised since the last call, oldest first.
+    pub fn take_events(&mut self) -> Vec<TerminalEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+
+    /// Record an event in the totals and the pending queue.
+    fn raise(&mut self, event: TerminalEvent) {
+        match &event {
+            TerminalEvent::Bell { timestamp } => {
+                self.events.bell_count += 1;
+                self.events.last_bell = Some(timestamp.clone());
+            }
+            TerminalEvent::Notification(notification) => {
+                self.events.notification_count += 1;
+                if self.events.notifications.len() >= NOTIFICATION_HISTORY {
+                    self.events.notifications.remove(0);
+                }
+                self.events.notifications.push(notification.clone());
+            }
+        }
+        if self.pending_events.len() >= PENDING_EVENT_LIMIT {
+            self.pending_events.remove(0);
+        }
+        self.pending_events.push(event);
+    }
+
+    /// Handle desktop notification OSCs.
+    ///
+    /// OSC 9 (iTerm2, `ESC ] 9 ; body BEL`) and OSC 777 (rxvt/foot/ghostty,
+    /// `ESC ] 777 ; notify ; title ; body BEL`). ConEmu reuses OSC 9 with a
+    /// numeric subcommand such as `9 ; 4` for progress, which is ignored.
+    fn notification(&mut self, params: &[&[u8]]) {
+        let text = |parts: &[&[u8]]| String::from_utf8_lossy(&parts.join(&b';')).into_owned();
+
+        let (title, body) = match params {
+            [b"9", rest @ ..] if !rest.is_empty() => {
+                let subcommand = rest[0].iter().all(u8::is_ascii_digit) && !rest[0].is_empty();
+                if subcommand {
+                    return;
+                }
+                (None, text(rest))
+            }
+            [b"777", b"notify", title, body @ ..] => (Some(text(&[title])), text(body)),
+            _ => return,
+        };
+
+        self.raise(TerminalEvent::Notification(Notification {
+            title,
+            body,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }));
+    }
+
+    /// Queue a reply to the application.
+    fn respond(&mut self, reply: &str) {
+        self.responses.extend_from_slice(reply.as_bytes());
+    }
+
+    /// Handle kitty keyboard protocol sequences (`CSI > u`, `CSI < u`, `CSI = u`, `CSI ? u`).
+    fn kitty_keyboard(&mut self, params: &Params, marker: u8) {
+        let mut iter = params.iter();
+        let first = iter.next().map(|p| p[0]);
+
+        match marker {
+            // Push flags
+            b'>' => {
+                if self.kitty_stack.len() >= KITTY_STACK_LIMIT {
+                    self.kitty_stack.remove(0);
+                }
+                self.kitty_stack.push(self.keyboard.kitty_flags);
+                self.keyboard.kitty_flags = first.unwrap_or(0) as u8;
+            }
+            // Pop flags
+            b'<' => {
+                for _ in 0..first.unwrap_or(1).max(1) {
+                    self.keyboard.kitty_flags = self.kitty_stack.pop().unwrap_or(0);
+                }
+            }
+            // Set flags: mode 1 replaces, 2 sets bits, 3 clears bits
+            b'=' => {
+                let flags = first.unwrap_or(0) as u8;
+                match iter.next().map(|p| p[0]).unwrap_or(1) {
+                    2 => self.keyboard.kitty_flags |= flags,
+                    3 => self.keyboard.kitty_flags &= !flags,
+                    _ => self.keyboard.kitty_flags = flags,
+                }
+            }
+            // Query flags
+            b'?' => {
+                let reply = format!("\x1b[?{}u", self.keyboard.kitty_flags);
+                self.respond(&reply);
+            }
+            _ => {}
+        }
+    }
+
+    /// Move cursor forward by n columns.
     fn cursor_forward(&mut self, n: u16) {
+        self.pending_wrap = false;
         let dims = self.grid.dimensions();
         let cursor = self.grid.cursor_mut();
 
@@ -55,26 +326,130 @@ impl Parser {
 
     /// Move cursor backward by n columns.
     fn cursor_backward(&mut self, n: u16) {
+        self.pending_wrap = false;
         let cursor = self.grid.cursor_mut();
         cursor.position.col = cursor.position.col.saturating_sub(n);
     }
 
-    /// Move cursor down by n rows.
+    /// Move cursor down by n rows, stopping at the bottom margin.
     fn cursor_down(&mut self, n: u16) {
+        self.pending_wrap = false;
         let dims = self.grid.dimensions();
+        let (_, bottom) = self.grid.scroll_region();
         let cursor = self.grid.cursor_mut();
 
-        let new_row = (cursor.position.row + n).min(dims.rows.saturating_sub(1));
+        let limit = if cursor.position.row <= bottom {
+            bottom
+        } else {
+            dims.rows.saturating_sub(1)
+        };
+        let new_row = (cursor.position.row + n).min(limit);
         cursor.position.row = new_row;
 
         // Clear wrap flag - explicit newline means new logical line
         self.grid.set_line_wrapped(new_row, false);
     }
 
-    /// Move cursor up by n rows.
+    /// Move cursor up by n rows, stopping at the top margin.
     fn cursor_up(&mut self, n: u16) {
+        self.pending_wrap = false;
+        let (top, _) = self.grid.scroll_region();
         let cursor = self.grid.cursor_mut();
-        cursor.position.row = cursor.position.row.saturating_sub(n);
+
+        let limit = if cursor.position.row >= top { top } else { 0 };
+        cursor.position.row = cursor.position.row.saturating_sub(n).max(limit);
+    }
+
+    /// Move cursor down one line, scrolling the region at the bottom margin (LF, IND).
+    fn linefeed(&mut self) {
+        self.pending_wrap = false;
+        let dims = self.grid.dimensions();
+        let (_, bottom) = self.grid.scroll_region();
+        let row = self.grid.cursor().position.row;
+
+        if row == bottom {
+            self.grid.scroll_up(1);
+        } else if row + 1 < dims.rows {
+            self.grid.cursor_mut().position.row = row + 1;
+            // Explicit newline means new logical line
+            self.grid.set_line_wrapped(row + 1, false);
+        }
+    }
+
+    /// Move cursor up one line, scrolling the region at the top margin (RI).
+    fn reverse_index(&mut self) {
+        self.pending_wrap = false;
+        let (top, _) = self.grid.scroll_region();
+        let row = self.grid.cursor().position.row;
+
+        if row == top {
+            self.grid.scroll_down(1);
+        } else {
+            self.grid.cursor_mut().position.row = row.saturating_sub(1);
+        }
+    }
+
+    /// Move cursor to a 0-indexed position, relative to the scroll region in origin mode.
+    fn goto(&mut self, row: u16, col: u16) {
+        self.pending_wrap = false;
+        let dims = self.grid.dimensions();
+        let (min_row, max_row) = if self.origin_mode {
+            let (top, bottom) = self.grid.scroll_region();
+            (top, bottom)
+        } else {
+            (0, dims.rows.saturating_sub(1))
+        };
+
+        self.grid.cursor_mut().position = Position::new(
+            row.saturating_add(min_row).min(max_row),
+            col.min(dims.cols.saturating_sub(1)),
+        );
+    }
+
+    /// Move cursor to a 0-indexed row, keeping the column (origin mode aware).
+    fn goto_row(&mut self, row: u16) {
+        let col = self.grid.cursor().position.col;
+        self.goto(row, col);
+    }
+
+    /// Row of the cursor relative to the origin (scroll region top in origin mode).
+    fn relative_row(&self) -> u16 {
+        let row = self.grid.cursor().position.row;
+        if self.origin_mode {
+            row.saturating_sub(self.grid.scroll_region().0)
+        } else {
+            row
+        }
+    }
+
+    /// Save cursor, rendition, origin mode and character sets (DECSC, `CSI s`).
+    fn save_cursor(&mut self) {
+        self.grid.save_cursor();
+        self.saved_origin_mode = self.origin_mode;
+        self.saved_charsets = self.charsets;
+    }
+
+    /// Restore state saved by [`Parser::save_cursor`] (DECRC, `CSI u`).
+    fn restore_cursor(&mut self) {
+        self.pending_wrap = false;
+        self.grid.restore_cursor();
+        self.origin_mode = self.saved_origin_mode;
+        self.charsets = self.saved_charsets;
+    }
+
+    /// Full reset (RIS): clear the screen and return all modes to their defaults.
+    fn reset(&mut self) {
+        self.grid.reset();
+        self.keyboard = KeyboardMode::default();
+        self.kitty_stack.clear();
+        self.bracketed_paste = false;
+        self.origin_mode = false;
+        self.saved_origin_mode = false;
+        self.autowrap = true;
+        self.pending_wrap = false;
+        self.last_printed = None;
+        self.charsets = CharsetState::default();
+        self.saved_charsets = CharsetState::default();
     }
 
     /// Process SGR (Select Graphic Rendition) parameters.
@@ -296,42 +671,66 @@ impl Parser {
     }
 }
 
+/// Get the numeric parameter at `idx`, treating a missing or zero value as `default`.
+fn param(params: &Params, idx: usize, default: u16) -> u16 {
+    match params.iter().nth(idx).map(|p| p[0]) {
+        Some(0) | None => default,
+        Some(value) => value,
+    }
+}
+
+/// Bytes that VTE prints unchanged from the ground state.
+fn is_printable_ascii(byte: u8) -> bool {
+    (0x20..0x7f).contains(&byte)
+}
+
 impl Perform for Parser {
     /// Print a character to the terminal.
     fn print(&mut self, c: char) {
-        let cursor_pos = self.grid.cursor().position;
+        self.ground = true;
+        let c = self.charsets.translate(c);
         let dims = self.grid.dimensions();
+        let last_col = dims.cols.saturating_sub(1);
 
-        // Get current attributes and colors before borrowing cell mutably
-        let attrs = *self.grid.current_attrs();
-        let fg = self.grid.current_fg();
-        let bg = self.grid.current_bg();
-
-        // Get or create cell at cursor position
-        if let Some(cell) = self.grid.cell_mut(cursor_pos.row, cursor_pos.col) {
-            cell.character = c;
-            cell.attrs = attrs;
-            cell.fg = fg;
-            cell.bg = bg;
-        }
+        // Deferred wrap: the previous character filled the last column
+        if self.pending_wrap && self.autowrap && self.grid.cursor().position.col == last_col {
+            self.grid.cursor_mut().position.col = 0;
+            self.linefeed();
 
-        // Move cursor forward (with wrapping)
-        let cursor = self.grid.cursor_mut();
-        cursor.position.col += 1;
+            // Mark the new row as wrapped (continuation of current line)
+            let row = self.grid.cursor().position.row;
+            self.grid.set_line_wrapped(row, true);
+        }
+        self.pending_wrap = false;
 
-        if cursor.position.col >= dims.cols {
-            cursor.position.col = 0;
-            let next_row = (cursor.position.row + 1).min(dims.rows.saturating_sub(1));
-            cursor.position.row = next_row;
+        let cursor_pos = self.grid.cursor().position;
 
-            // Mark next row as wrapped (continuation of current line)
-            self.grid.set_line_wrapped(next_row, true);
+        let cell = Cell {
+            character: c,
+            fg: self.grid.current_fg(),
+            bg: self.grid.current_bg(),
+            attrs: *self.grid.current_attrs(),
+        };
+        // Redrawing identical content is not a change
+        self.grid.set_cell(cursor_pos.row, cursor_pos.col, cell);
+        self.last_printed = Some(c);
+
+        // Move cursor forward; at the last column wrap on the next character
+        if cursor_pos.col < last_col {
+            self.grid.cursor_mut().position.col += 1;
+        } else if self.autowrap {
+            self.pending_wrap = true;
         }
     }
 
     /// Execute a control character.
     fn execute(&mut self, byte: u8) {
         match byte {
+            // Bell (BEL)
+            0x07 => self.raise(TerminalEvent::Bell {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }),
+
             // Backspace (BS)
             0x08 => {
                 self.cursor_backward(1);
@@ -339,23 +738,28 @@ impl Perform for Parser {
 
             // Horizontal Tab (HT)
             0x09 => {
-                let dims = self.grid.dimensions();
-                let cursor = self.grid.cursor_mut();
-                // Move to next tab stop (every 8 columns)
-                let next_tab = ((cursor.position.col / 8) + 1) * 8;
-                cursor.position.col = next_tab.min(dims.cols.saturating_sub(1));
+                self.pending_wrap = false;
+                let col = self.grid.cursor().position.col;
+                self.grid.cursor_mut().position.col = self.grid.next_tab_stop(col);
             }
 
-            // Line Feed (LF)
-            0x0A => {
-                self.cursor_down(1);
+            // Line Feed (LF), Vertical Tab (VT), Form Feed (FF)
+            0x0A..=0x0C => {
+                self.linefeed();
             }
 
             // Carriage Return (CR)
             0x0D => {
+                self.pending_wrap = false;
                 self.grid.cursor_mut().position.col = 0;
             }
 
+            // Shift Out (SO): invoke G1
+            0x0E => self.charsets.lock_shift(1),
+
+            // Shift In (SI): invoke G0
+            0x0F => self.charsets.lock_shift(0),
+
             _ => {} // Ignore other control codes for now
         }
     }
@@ -376,42 +780,61 @@ impl Perform for Parser {
     }
 
     /// OSC (Operating System Command) dispatch.
-    fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {
-        // Not implemented yet - will handle title changes, etc.
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        self.notification(params);
     }
 
     /// CSI (Control Sequence Introducer) dispatch.
     fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], _ignore: bool, c: char) {
+        self.ground = true;
         // Check for private mode sequences (CSI ? Pn h/l)
         let is_private_mode = intermediates.contains(&b'?');
 
         match c {
             // Private mode set (DECSET) / reset (DECRST)
             'h' | 'l' if is_private_mode => {
-                let mode = params.iter().next().map(|p| p[0]).unwrap_or(0);
                 let enable = c == 'h';
 
-                match mode {
-                    // Alternate screen buffer (switch on 'h', restore on 'l')
-                    1049 | 47 | 1047 => {
-                        if enable {
-                            // Clear grid when switching to alternate buffer
-                            self.grid.clear();
+                for param in params.iter() {
+                    match param[0] {
+                        // Alternate screen, saving and restoring the cursor around it
+                        1049 if enable => {
+                            self.save_cursor();
+                            self.grid.enter_alternate_screen();
+                        }
+                        1049 => {
+                            self.grid.exit_alternate_screen();
+                            self.restore_cursor();
                         }
-                        // Note: We don't maintain a separate buffer, just clear on switch
-                    }
 
-                    // Cursor visibility (25)
-                    25 => {
-                        // Cursor visibility - no-op for now (we don't track this)
-                    }
+                        // Alternate screen
+                        47 | 1047 if enable => self.grid.enter_alternate_screen(),
+                        47 | 1047 => self.grid.exit_alternate_screen(),
 
-                    // Auto-wrap mode (7)
-                    7 => {
-                        // Auto-wrap - no-op for now
-                    }
+                        // Origin mode (DECOM) - also homes the cursor
+                        6 => {
+                            self.origin_mode = enable;
+                            self.goto(0, 0);
+                        }
+
+                        // Auto-wrap mode (DECAWM)
+                        7 => {
+                            self.autowrap = enable;
+                            self.pending_wrap = false;
+                        }
+
+                        // Text cursor enable (DECTCEM)
+                        25 => {
+                            self.grid.cursor_mut().visible = enable;
+                        }
 
-                    _ => {} // Ignore other private modes
+                        // Bracketed paste (2004)
+                        2004 => {
+                            self.bracketed_paste = enable;
+                        }
+
+                        _ => {} // Ignore other private modes
+                    }
                 }
             }
 
@@ -422,39 +845,136 @@ impl Perform for Parser {
 
             // Cursor Up (CUU)
             'A' => {
-                let n = params.iter().next().map(|p| p[0]).unwrap_or(1);
-                self.cursor_up(n);
+                self.cursor_up(param(params, 0, 1));
             }
 
             // Cursor Down (CUD)
             'B' => {
-                let n = params.iter().next().map(|p| p[0]).unwrap_or(1);
-                self.cursor_down(n);
+                self.cursor_down(param(params, 0, 1));
             }
 
             // Cursor Forward (CUF)
             'C' => {
-                let n = params.iter().next().map(|p| p[0]).unwrap_or(1);
-                self.cursor_forward(n);
+                self.cursor_forward(param(params, 0, 1));
             }
 
             // Cursor Backward (CUB)
             'D' => {
-                let n = params.iter().next().map(|p| p[0]).unwrap_or(1);
-                self.cursor_backward(n);
+                self.cursor_backward(param(params, 0, 1));
             }
 
-            // Cursor Position (CUP)
-            'H' => {
-                let mut iter = params.iter();
-                let row = iter.next().map(|p| p[0]).unwrap_or(1).saturating_sub(1);
-                let col = iter.next().map(|p| p[0]).unwrap_or(1).saturating_sub(1);
+            // Cursor Next Line (CNL)
+            'E' => {
+                self.cursor_down(param(params, 0, 1));
+                self.grid.cursor_mut().position.col = 0;
+            }
 
-                let dims = self.grid.dimensions();
-                self.grid.cursor_mut().position = Position::new(
-                    row.min(dims.rows.saturating_sub(1)),
-                    col.min(dims.cols.saturating_sub(1)),
-                );
+            // Cursor Previous Line (CPL)
+            'F' => {
+                self.cursor_up(param(params, 0, 1));
+                self.grid.cursor_mut().position.col = 0;
+            }
+
+            // Cursor Horizontal Absolute (CHA) / Horizontal Position Absolute (HPA)
+            'G' | '`' => {
+                let row = self.relative_row();
+                self.goto(row, param(params, 0, 1) - 1);
+            }
+
+            // Vertical Position Absolute (VPA)
+            'd' => {
+                self.goto_row(param(params, 0, 1) - 1);
+            }
+
+            // Cursor Position (CUP) / Horizontal and Vertical Position (HVP)
+            'H' | 'f' => {
+                self.goto(param(params, 0, 1) - 1, param(params, 1, 1) - 1);
+            }
+
+            // Cursor Horizontal Tabulation (CHT)
+            'I' => {
+                self.pending_wrap = false;
+                for _ in 0..param(params, 0, 1) {
+                    let col = self.grid.cursor().position.col;
+                    self.grid.cursor_mut().position.col = self.grid.next_tab_stop(col);
+                }
+            }
+
+            // Cursor Backward Tabulation (CBT)
+            'Z' => {
+                self.pending_wrap = false;
+                for _ in 0..param(params, 0, 1) {
+                    let col = self.grid.cursor().position.col;
+                    self.grid.cursor_mut().position.col = self.grid.prev_tab_stop(col);
+                }
+            }
+
+            // Tab Clear (TBC)
+            'g' => match params.iter().next().map(|p| p[0]).unwrap_or(0) {
+                0 => {
+                    let col = self.grid.cursor().position.col;
+                    self.grid.clear_tab_stop(col);
+                }
+                3 => self.grid.clear_all_tab_stops(),
+                _ => {}
+            },
+
+            // Insert Characters (ICH)
+            '@' if intermediates.is_empty() => {
+                self.pending_wrap = false;
+                self.grid.insert_chars(param(params, 0, 1));
+            }
+
+            // Delete Characters (DCH)
+            'P' => {
+                self.pending_wrap = false;
+                self.grid.delete_chars(param(params, 0, 1));
+            }
+
+            // Erase Characters (ECH)
+            'X' => {
+                self.pending_wrap = false;
+                self.grid.erase_chars(param(params, 0, 1));
+            }
+
+            // Repeat preceding character (REP)
+            'b' => {
+                if let Some(ch) = self.last_printed {
+                    for _ in 0..param(params, 0, 1) {
+                        self.print(ch);
+                    }
+                }
+            }
+
+            // Insert Lines (IL)
+            'L' => {
+                self.grid.insert_lines(param(params, 0, 1));
+                self.cursor_backward(u16::MAX);
+            }
+
+            // Delete Lines (DL)
+            'M' => {
+                self.grid.delete_lines(param(params, 0, 1));
+                self.cursor_backward(u16::MAX);
+            }
+
+            // Scroll Up (SU)
+            'S' if intermediates.is_empty() => {
+                self.grid.scroll_up(param(params, 0, 1));
+            }
+
+            // Scroll Down (SD)
+            'T' if intermediates.is_empty() => {
+                self.grid.scroll_down(param(params, 0, 1));
+            }
+
+            // Set Top and Bottom Margins (DECSTBM) - also homes the cursor
+            'r' if intermediates.is_empty() => {
+                let rows = self.grid.dimensions().rows;
+                let top = param(params, 0, 1) - 1;
+                let bottom = param(params, 1, rows).min(rows) - 1;
+                self.grid.set_scroll_region(top, bottom);
+                self.goto(0, 0);
             }
 
             // Erase in Display (ED)
@@ -468,17 +988,13 @@ impl Perform for Parser {
                     0 => {
                         // Clear rest of current row
                         for col in cursor_pos.col..dims.cols {
-                            if let Some(cell) = self.grid.cell_mut(cursor_pos.row, col) {
-                                *cell = Cell::default();
-                            }
+                            self.grid.set_cell(cursor_pos.row, col, Cell::default());
                         }
 
                         // Clear all rows below
                         for row in (cursor_pos.row + 1)..dims.rows {
                             for col in 0..dims.cols {
-                                if let Some(cell) = self.grid.cell_mut(row, col) {
-                                    *cell = Cell::default();
-                                }
+                                self.grid.set_cell(row, col, Cell::default());
                             }
                         }
                     }
@@ -488,25 +1004,26 @@ impl Perform for Parser {
                         // Clear all rows above
                         for row in 0..cursor_pos.row {
                             for col in 0..dims.cols {
-                                if let Some(cell) = self.grid.cell_mut(row, col) {
-                                    *cell = Cell::default();
-                                }
+                                self.grid.set_cell(row, col, Cell::default());
                             }
                         }
 
                         // Clear from start of current row to cursor
                         for col in 0..=cursor_pos.col {
-                            if let Some(cell) = self.grid.cell_mut(cursor_pos.row, col) {
-                                *cell = Cell::default();
-                            }
+                            self.grid.set_cell(cursor_pos.row, col, Cell::default());
                         }
                     }
 
                     // Clear entire screen
-                    2 | 3 => {
+                    2 => {
                         self.grid.clear();
                     }
 
+                    // Clear scrollback
+                    3 => {
+                        self.grid.clear_scrollback();
+                    }
+
                     _ => {}
                 }
             }
@@ -521,27 +1038,21 @@ impl Perform for Parser {
                     // Clear from cursor to end of line
                     0 => {
                         for col in cursor_pos.col..dims.cols {
-                            if let Some(cell) = self.grid.cell_mut(cursor_pos.row, col) {
-                                *cell = Cell::default();
-                            }
+                            self.grid.set_cell(cursor_pos.row, col, Cell::default());
                         }
                     }
 
                     // Clear from start of line to cursor
                     1 => {
                         for col in 0..=cursor_pos.col {
-                            if let Some(cell) = self.grid.cell_mut(cursor_pos.row, col) {
-                                *cell = Cell::default();
-                            }
+                            self.grid.set_cell(cursor_pos.row, col, Cell::default());
                         }
                     }
 
                     // Clear entire line
                     2 => {
                         for col in 0..dims.cols {
-                            if let Some(cell) = self.grid.cell_mut(cursor_pos.row, col) {
-                                *cell = Cell::default();
-                            }
+                            self.grid.set_cell(cursor_pos.row, col, Cell::default());
                         }
                     }
 
@@ -549,19 +1060,73 @@ impl Perform for Parser {
                 }
             }
 
+            // xterm modifyOtherKeys (CSI > 4 ; level m)
+            'm' if intermediates.first() == Some(&b'>') => {
+                let mut iter = params.iter();
+                if iter.next().map(|p| p[0]) == Some(4) {
+                    self.keyboard.modify_other_keys = iter.next().map(|p| p[0]).unwrap_or(0) as u8;
+                }
+            }
+
+            // Query modifyOtherKeys (CSI ? 4 m)
+            'm' if is_private_mode && params.iter().next().map(|p| p[0]) == Some(4) => {
+                let reply = format!("\x1b[>4;{}m", self.keyboard.modify_other_keys);
+                self.respond(&reply);
+            }
+
+            // Reset modifyOtherKeys (CSI > 4 n)
+            'n' if intermediates.first() == Some(&b'>')
+                && params.iter().next().map(|p| p[0]) == Some(4) =>
+            {
+                self.keyboard.modify_other_keys = 0;
+            }
+
+            // Device Status Report (DSR)
+            'n' if intermediates.is_empty() => match params.iter().next().map(|p| p[0]) {
+                Some(5) => self.respond("\x1b[0n"),
+                Some(6) => {
+                    let pos = self.grid.cursor().position;
+                    let reply = format!("\x1b[{};{}R", pos.row + 1, pos.col + 1);
+                    self.respond(&reply);
+                }
+                _ => {}
+            },
+
+            // Primary Device Attributes (DA1): VT220 with ANSI color
+            'c' if intermediates.is_empty()
+                && params.iter().next().map(|p| p[0]).unwrap_or(0) == 0 =>
+            {
+                self.respond("\x1b[?62;22c");
+            }
+
             // SGR (Select Graphic Rendition)
-            'm' => {
+            'm' if intermediates.is_empty() => {
                 self.process_sgr(params);
             }
 
             // Save Cursor Position (SCP)
-            's' => {
-                self.grid.save_cursor();
+            's' if intermediates.is_empty() => {
+                self.save_cursor();
+            }
+
+            // Set cursor style (DECSCUSR)
+            'q' if intermediates == [b' '] => {
+                let style = match params.iter().next().map(|p| p[0]).unwrap_or(0) {
+                    3 | 4 => CursorStyle::Underline,
+                    5 | 6 => CursorStyle::Bar,
+                    _ => CursorStyle::Block,
+                };
+                self.grid.cursor_mut().style = style;
+            }
+
+            // Kitty keyboard protocol
+            'u' if !intermediates.is_empty() => {
+                self.kitty_keyboard(params, intermediates[0]);
             }
 
             // Restore Cursor Position (RCP)
             'u' => {
-                self.grid.restore_cursor();
+                self.restore_cursor();
             }
 
             _ => {} // Ignore unknown CSI sequences
@@ -569,8 +1134,63 @@ impl Perform for Parser {
     }
 
     /// ESC (Escape) dispatch.
-    fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {
-        // Not implemented yet - will handle things like DECSC, DECRC
+    fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, byte: u8) {
+        self.ground = true;
+        match (intermediates, byte) {
+            // Save Cursor (DECSC)
+            ([], b'7') => self.save_cursor(),
+
+            // Restore Cursor (DECRC)
+            ([], b'8') => self.restore_cursor(),
+
+            // Index (IND)
+            ([], b'D') => self.linefeed(),
+
+            // Next Line (NEL)
+            ([], b'E') => {
+                self.linefeed();
+                self.grid.cursor_mut().position.col = 0;
+            }
+
+            // Horizontal Tab Set (HTS)
+            ([], b'H') => {
+                let col = self.grid.cursor().position.col;
+                self.grid.set_tab_stop(col);
+            }
+
+            // Reverse Index (RI)
+            ([], b'M') => self.reverse_index(),
+
+            // Full Reset (RIS)
+            ([], b'c') => self.reset(),
+
+            // Single Shift 2/3 (SS2/SS3)
+            ([], b'N') => self.charsets.single_shift(2),
+            ([], b'O') => self.charsets.single_shift(3),
+
+            // Locking Shift 2/3 (LS2/LS3)
+            ([], b'n') => self.charsets.lock_shift(2),
+            ([], b'o') => self.charsets.lock_shift(3),
+
+            // Designate G0–G3 character set (SCS)
+            ([slot @ (b'(' | b')' | b'*' | b'+')], designator) => {
+                if let Some(charset) = Charset::from_designator(designator) {
+                    let slot = (slot - b'(') as usize;
+                    self.charsets.designate(slot, charset);
+                }
+            }
+
+            // Screen Alignment Test (DECALN): fill with 'E', reset margins, home cursor
+            ([b'#'], b'8') => {
+                self.grid.fill('E');
+                let rows = self.grid.dimensions().rows;
+                self.grid.set_scroll_region(0, rows.saturating_sub(1));
+                self.origin_mode = false;
+                self.goto(0, 0);
+            }
+
+            _ => {} // Ignore other escape sequences
+        }
     }
 }
 
@@ -779,4 +1399,513 @@ mod tests {
         assert_eq!(parser.grid().cell(0, 5).unwrap().character, ' ');
         assert_eq!(parser.grid().cell(0, 9).unwrap().character, ' ');
     }
+
+    #[test]
+    fn test_parser_sequence_split_across_reads() {
+        let grid = Grid::new(Dimensions::new(24, 80));
+        let mut parser = Parser::new(grid);
+
+        parser.process(b"\x1b[3");
+        parser.process(b"1mX");
+
+        let cell = parser.grid().cell(0, 0).unwrap();
+        assert_eq!(cell.character, 'X');
+        assert_eq!(cell.fg, Color::Red);
+    }
+
+    #[test]
+    fn test_parser_kitty_keyboard_push_pop() {
+        let grid = Grid::new(Dimensions::new(24, 80));
+        let mut parser = Parser::new(grid);
+
+        parser.process(b"\x1b[>1u");
+        assert_eq!(parser.keyboard_mode().kitty_flags, 1);
+
+        parser.process(b"\x1b[>9u");
+        assert_eq!(parser.keyboard_mode().kitty_flags, 9);
+
+        parser.process(b"\x1b[=8;3u");
+        assert_eq!(parser.keyboard_mode().kitty_flags, 1);
+
+        parser.process(b"\x1b[<u");
+        assert_eq!(parser.keyboard_mode().kitty_flags, 1);
+
+        parser.process(b"\x1b[<u");
+        assert_eq!(parser.keyboard_mode().kitty_flags, 0);
+
+        // Restore cursor (plain CSI u) must still work
+        parser.grid_mut().cursor_mut().position = Position::new(2, 3);
+        parser.process(b"\x1b[s\x1b[H\x1b[u");
+        assert_eq!(parser.grid().cursor().position, Position::new(2, 3));
+    }
+
+    #[test]
+    fn test_parser_kitty_keyboard_query() {
+        let grid = Grid::new(Dimensions::new(24, 80));
+        let mut parser = Parser::new(grid);
+
+        parser.process(b"\x1b[>5u\x1b[?u\x1b[c");
+        assert_eq!(parser.take_responses(), b"\x1b[?5u\x1b[?62;22c");
+        assert!(parser.take_responses().is_empty());
+    }
+
+    #[test]
+    fn test_parser_modify_other_keys() {
+        let grid = Grid::new(Dimensions::new(24, 80));
+        let mut parser = Parser::new(grid);
+
+        parser.process(b"\x1b[>4;2mX");
+        assert_eq!(parser.keyboard_mode().modify_other_keys, 2);
+        // Must not be mistaken for SGR underline/bold
+        let cell = parser.grid().cell(0, 0).unwrap();
+        assert!(!cell.attrs.underline);
+
+        parser.process(b"\x1b[?4m");
+        assert_eq!(parser.take_responses(), b"\x1b[>4;2m");
+
+        parser.process(b"\x1b[>4m");
+        assert_eq!(parser.keyboard_mode().modify_other_keys, 0);
+    }
+
+    #[test]
+    fn test_parser_cursor_position_report() {
+        let grid = Grid::new(Dimensions::new(24, 80));
+        let mut parser = Parser::new(grid);
+
+        parser.process(b"\x1b[5;10H\x1b[6n");
+        assert_eq!(parser.take_responses(), b"\x1b[5;10R");
+    }
+
+    #[test]
+    fn test_parser_bracketed_paste_mode() {
+        let grid = Grid::new(Dimensions::new(24, 80));
+        let mut parser = Parser::new(grid);
+
+        assert!(!parser.bracketed_paste());
+        parser.process(b"\x1b[?2004h");
+        assert!(parser.bracketed_paste());
+        parser.process(b"\x1b[?2004l");
+        assert!(!parser.bracketed_paste());
+    }
+
+    /// Create a parser and feed it `bytes`.
+    fn parse(rows: u16, cols: u16, bytes: &[u8]) -> Parser {
+        let mut parser = Parser::new(Grid::new(Dimensions::new(rows, cols)));
+        parser.process(bytes);
+        parser
+    }
+
+    fn row_text(parser: &Parser, row: u16) -> String {
+        parser
+            .grid()
+            .row(row)
+            .unwrap()
+            .iter()
+            .map(|c| c.character)
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn test_parser_ich() {
+        let parser = parse(2, 10, b"abcdef\x1b[3G\x1b[2@");
+        assert_eq!(row_text(&parser, 0), "ab  cdef");
+    }
+
+    #[test]
+    fn test_parser_dch() {
+        let parser = parse(2, 10, b"abcdef\x1b[2G\x1b[2P");
+        assert_eq!(row_text(&parser, 0), "adef");
+    }
+
+    #[test]
+    fn test_parser_ech() {
+        let parser = parse(2, 10, b"abcdef\x1b[2G\x1b[3X");
+        assert_eq!(row_text(&parser, 0), "a   ef");
+        assert_eq!(parser.grid().cursor().position.col, 1);
+    }
+
+    #[test]
+    fn test_parser_rep() {
+        let parser = parse(2, 10, b"-x\x1b[3b");
+        assert_eq!(row_text(&parser, 0), "-xxxx");
+    }
+
+    #[test]
+    fn test_parser_cha_hpa() {
+        let parser = parse(2, 10, b"\x1b[2;3H\x1b[7G");
+        assert_eq!(parser.grid().cursor().position, Position::new(1, 6));
+
+        let parser = parse(2, 10, b"\x1b[2;3H\x1b[5`");
+        assert_eq!(parser.grid().cursor().position, Position::new(1, 4));
+    }
+
+    #[test]
+    fn test_parser_vpa() {
+        let parser = parse(5, 10, b"\x1b[1;4H\x1b[3d");
+        assert_eq!(parser.grid().cursor().position, Position::new(2, 3));
+    }
+
+    #[test]
+    fn test_parser_cnl_cpl() {
+        let parser = parse(5, 10, b"\x1b[2;5H\x1b[2E");
+        assert_eq!(parser.grid().cursor().position, Position::new(3, 0));
+
+        let parser = parse(5, 10, b"\x1b[4;5H\x1b[F");
+        assert_eq!(parser.grid().cursor().position, Position::new(2, 0));
+    }
+
+    #[test]
+    fn test_parser_hvp() {
+        let parser = parse(5, 10, b"\x1b[3;4f");
+        assert_eq!(parser.grid().cursor().position, Position::new(2, 3));
+    }
+
+    #[test]
+    fn test_parser_cht_cbt() {
+        let parser = parse(2, 40, b"\x1b[2I");
+        assert_eq!(parser.grid().cursor().position.col, 16);
+
+        let parser = parse(2, 40, b"\x1b[1;20H\x1b[Z");
+        assert_eq!(parser.grid().cursor().position.col, 16);
+    }
+
+    #[test]
+    fn test_parser_hts_tbc() {
+        // Set a stop at column 3, then tab from column 0
+        let mut parser = parse(2, 40, b"\x1b[4G\x1bH\r\t");
+        assert_eq!(parser.grid().cursor().position.col, 3);
+
+        // Clear it at the cursor
+        parser.process(b"\x1b[0g\r\t");
+        assert_eq!(parser.grid().cursor().position.col, 8);
+
+        // Clear all
+        parser.process(b"\x1b[3g\r\t");
+        assert_eq!(parser.grid().cursor().position.col, 39);
+    }
+
+    #[test]
+    fn test_parser_decsc_decrc() {
+        let parser = parse(5, 10, b"\x1b[2;3H\x1b[31m\x1b7\x1b[H\x1b[0m\x1b8X");
+        assert_eq!(parser.grid().cursor().position, Position::new(1, 3));
+        assert_eq!(parser.grid().cell(1, 2).unwrap().fg, Color::Red);
+    }
+
+    #[test]
+    fn test_parser_ris() {
+        let mut parser = parse(3, 10, b"hello\x1b[?2004h\x1b[>1u\x1b[31m");
+        parser.process(b"\x1bc");
+
+        assert_eq!(parser.grid().to_plain_text().trim(), "");
+        assert_eq!(parser.grid().cursor().position, Position::origin());
+        assert!(!parser.bracketed_paste());
+        assert_eq!(parser.keyboard_mode(), KeyboardMode::default());
+        assert_eq!(parser.grid().current_fg(), Color::Default);
+    }
+
+    #[test]
+    fn test_parser_decaln() {
+        let parser = parse(2, 3, b"\x1b[2;3H\x1b#8");
+        assert_eq!(row_text(&parser, 0), "EEE");
+        assert_eq!(row_text(&parser, 1), "EEE");
+        assert_eq!(parser.grid().cursor().position, Position::origin());
+    }
+
+    #[test]
+    fn test_parser_origin_mode() {
+        // Region rows 2..4 (1-indexed), origin mode on
+        let mut parser = parse(6, 10, b"\x1b[2;4r\x1b[?6h");
+        assert_eq!(parser.grid().cursor().position, Position::new(1, 0));
+
+        parser.process(b"\x1b[2;2H");
+        assert_eq!(parser.grid().cursor().position, Position::new(2, 1));
+
+        // Addressing is clamped to the region
+        parser.process(b"\x1b[9;1H");
+        assert_eq!(parser.grid().cursor().position, Position::new(3, 0));
+
+        parser.process(b"\x1b[?6l");
+        assert_eq!(parser.grid().cursor().position, Position::origin());
+    }
+
+    #[test]
+    fn test_parser_decawm_off() {
+        let parser = parse(2, 5, b"\x1b[?7labcdefg");
+        assert_eq!(row_text(&parser, 0), "abcdg");
+        assert_eq!(row_text(&parser, 1), "");
+    }
+
+    #[test]
+    fn test_parser_deferred_wrap() {
+        // Filling the last cell of the screen must not scroll
+        let mut parser = parse(2, 3, b"abcdef");
+        assert_eq!(row_text(&parser, 0), "abc");
+        assert_eq!(row_text(&parser, 1), "def");
+        assert!(parser.grid().is_line_wrapped(1));
+
+        // The next character wraps and scrolls
+        parser.process(b"g");
+        assert_eq!(row_text(&parser, 0), "def");
+        assert_eq!(row_text(&parser, 1), "g");
+    }
+
+    #[test]
+    fn test_parser_linefeed_scrolls_region() {
+        let parser = parse(4, 5, b"a\r\nb\r\nc\r\nd\x1b[2;3r\x1b[3;1H\n");
+        assert_eq!(row_text(&parser, 0), "a");
+        assert_eq!(row_text(&parser, 1), "c");
+        assert_eq!(row_text(&parser, 2), "");
+        assert_eq!(row_text(&parser, 3), "d");
+    }
+
+    #[test]
+    fn test_parser_reverse_index_and_il_dl() {
+        let mut parser = parse(3, 5, b"a\r\nb\r\nc\x1b[H\x1bM");
+        assert_eq!(row_text(&parser, 0), "");
+        assert_eq!(row_text(&parser, 1), "a");
+
+        parser.process(b"\x1b[2;1H\x1b[M");
+        assert_eq!(row_text(&parser, 1), "b");
+
+        parser.process(b"\x1b[L");
+        assert_eq!(row_text(&parser, 1), "");
+        assert_eq!(row_text(&parser, 2), "b");
+    }
+
+    #[test]
+    fn test_parser_dec_special_graphics_g0() {
+        let parser = parse(3, 10, b"\x1b(0lqqk\r\nx  x\r\nmqqj\x1b(Bq");
+        assert_eq!(row_text(&parser, 0), "┌──┐");
+        assert_eq!(row_text(&parser, 1), "│  │");
+        assert_eq!(row_text(&parser, 2), "└──┘q");
+    }
+
+    #[test]
+    fn test_parser_so_si_with_g1() {
+        let parser = parse(1, 10, b"\x1b)0a\x0eqq\x0fq");
+        assert_eq!(row_text(&parser, 0), "a──q");
+    }
+
+    #[test]
+    fn test_parser_single_shift() {
+        let parser = parse(1, 10, b"\x1b*0\x1bNqq");
+        assert_eq!(row_text(&parser, 0), "─q");
+    }
+
+    #[test]
+    fn test_parser_decsc_saves_charsets() {
+        let parser = parse(1, 10, b"\x1b(0\x1b7\x1b(B\x1b8q");
+        assert_eq!(row_text(&parser, 0), "─");
+    }
+
+    #[test]
+    fn test_parser_dectcem() {
+        let mut parser = parse(2, 10, b"\x1b[?25l");
+        assert!(!parser.grid().cursor_visible());
+        parser.process(b"\x1b[?25h");
+        assert!(parser.grid().cursor_visible());
+    }
+
+    #[test]
+    fn test_parser_decscusr() {
+        let mut parser = parse(2, 10, b"\x1b[6 q");
+        assert_eq!(parser.grid().cursor().style, CursorStyle::Bar);
+        parser.process(b"\x1b[4 q");
+        assert_eq!(parser.grid().cursor().style, CursorStyle::Underline);
+        parser.process(b"\x1b[ q");
+        assert_eq!(parser.grid().cursor().style, CursorStyle::Block);
+    }
+
+    #[test]
+    fn test_parser_decrc_keeps_cursor_visibility() {
+        let parser = parse(2, 10, b"\x1b7\x1b[?25l\x1b[5 q\x1b8");
+        assert!(!parser.grid().cursor_visible());
+        assert_eq!(parser.grid().cursor().style, CursorStyle::Bar);
+    }
+
+    #[test]
+    fn test_parser_alternate_screen_restores_primary() {
+        let mut parser = parse(3, 10, b"shell$ ");
+        parser.process(b"\x1b[?1049h\x1b[2;3Hvim");
+        assert!(parser.grid().is_alternate_screen());
+        assert_eq!(row_text(&parser, 0), "");
+
+        parser.process(b"\x1b[?1049l");
+        assert_eq!(row_text(&parser, 0), "shell$");
+        assert_eq!(parser.grid().cursor().position, Position::new(0, 7));
+    }
+
+    #[test]
+    fn test_parser_ed3_clears_scrollback_only() {
+        let mut parser = parse(2, 10, b"a\r\nb\r\nc");
+        assert_eq!(parser.grid().scrollback_len(), 1);
+        parser.process(b"\x1b[3J");
+        assert_eq!(parser.grid().scrollback_len(), 0);
+        assert_eq!(row_text(&parser, 1), "c");
+    }
+
+    #[test]
+    fn test_parser_resize_with_pending_wrap() {
+        let mut parser = parse(2, 4, b"abcd");
+        parser.resize(Dimensions::new(2, 8));
+        parser.process(b"e");
+        assert_eq!(row_text(&parser, 0), "abcde");
+
+        // Still on the last column: the wrap stays pending
+        let mut parser = parse(2, 4, b"abcd");
+        parser.resize(Dimensions::new(2, 2));
+        parser.process(b"e");
+        assert_eq!(row_text(&parser, 0), "cd");
+        assert_eq!(row_text(&parser, 1), "e");
+    }
+
+    #[test]
+    fn test_parser_resize_reflows_shell_line() {
+        let mut parser = parse(3, 10, b"$ echo 0123456789\r\nout\r\n$ ");
+        parser.resize(Dimensions::new(3, 20));
+        assert_eq!(row_text(&parser, 0), "$ echo 0123456789");
+        assert_eq!(row_text(&parser, 1), "out");
+        assert_eq!(row_text(&parser, 2), "$");
+        assert_eq!(parser.grid().cursor().position, Position::new(2, 2));
+    }
+
+    #[test]
+    fn test_parser_identical_redraw_is_not_damage() {
+        let mut parser = parse(2, 10, b"\x1b[Habc\x1b[K\x1b[H");
+        let generation = parser.grid().generation();
+
+        parser.process(b"\x1b[Habc\x1b[K\x1b[H");
+        assert!(!parser.grid().changed_since(generation));
+
+        // A cursor move alone is a change, but damages no rows
+        parser.process(b"\x1b[2;1H");
+        assert!(parser.grid().changed_since(generation));
+        assert!(parser.grid().damaged_rows(generation).is_empty());
+
+        parser.process(b"\x1b[Habd");
+        assert_eq!(parser.grid().damaged_rows(generation), vec![0]);
+    }
+
+    /// Feed bytes through VTE one at a time, bypassing the ASCII fast path.
+    fn parse_slow(rows: u16, cols: u16, bytes: &[u8]) -> Parser {
+        let mut parser = Parser::new(Grid::new(Dimensions::new(rows, cols)));
+        let mut vte = std::mem::take(&mut parser.vte);
+        for &byte in bytes {
+            vte.advance(&mut parser, byte);
+        }
+        parser.vte = vte;
+        parser
+    }
+
+    fn assert_same_screen(fast: &Parser, slow: &Parser) {
+        let (fast, slow) = (fast.grid(), slow.grid());
+        assert_eq!(fast.to_ansi(), slow.to_ansi());
+        assert_eq!(fast.cursor(), slow.cursor());
+        assert_eq!(fast.scrollback_len(), slow.scrollback_len());
+        for row in 0..fast.dimensions().rows {
+            assert_eq!(fast.row(row), slow.row(row));
+            assert_eq!(fast.is_line_wrapped(row), slow.is_line_wrapped(row));
+        }
+    }
+
+    #[test]
+    fn test_parser_ascii_fast_path_matches_vte() {
+        let inputs: [&[u8]; 6] = [
+            b"a line long enough to wrap several times on a narrow screen\r\nshort\r\n",
+            b"\x1b[31mred\x1b[0m plain \x1b[1;4mbold underline\x1b[m\r\n",
+            b"\x1b(0lqqk\x1b(B ascii \x0eqq\x0f \x1bNq done",
+            b"exactly10!next\x1b[?7lno autowrap past the edge\x1b[?7h",
+            "utf-8 caf\u{e9} \u{2500}\u{2500} mixed with ascii".as_bytes(),
+            b"\x1b]0;title\x07after osc \x1b]2;t\x1b\\after st \x1bP1$r\x1b\\x",
+        ];
+        for input in inputs {
+            let fast = parse(4, 10, input);
+            let slow = parse_slow(4, 10, input);
+            assert_same_screen(&fast, &slow);
+        }
+    }
+
+    #[test]
+    fn test_parser_ascii_fast_path_split_sequences() {
+        let input = b"ab\x1b[31mcd\x1b[0mef\x1b[2;3Hgh";
+        let slow = parse_slow(3, 10, input);
+
+        // Every split point, including inside escape sequences
+        for split in 0..input.len() {
+            let mut fast = Parser::new(Grid::new(Dimensions::new(3, 10)));
+            fast.process(&input[..split]);
+            fast.process(&input[split..]);
+            assert_same_screen(&fast, &slow);
+        }
+    }
+
+    #[test]
+    fn test_parser_ascii_fast_path_wraps() {
+        let parser = parse(3, 4, b"abcdefghij");
+        assert_eq!(row_text(&parser, 0), "abcd");
+        assert_eq!(row_text(&parser, 1), "efgh");
+        assert_eq!(row_text(&parser, 2), "ij");
+        assert!(parser.grid().is_line_wrapped(1));
+        assert!(parser.grid().is_line_wrapped(2));
+        assert_eq!(parser.grid().cursor().position, Position::new(2, 2));
+        assert_eq!(parser.last_printed, Some('j'));
+
+        // Filling the last row exactly leaves the wrap pending
+        let parser = parse(1, 4, b"abcd");
+        assert!(parser.pending_wrap);
+        assert_eq!(parser.grid().cursor().position, Position::new(0, 3));
+    }
+
+    #[test]
+    fn test_parser_bell() {
+        let mut parser = parse(3, 10, b"a\x07b\x07");
+        assert_eq!(row_text(&parser, 0), "ab");
+        assert_eq!(parser.events().bell_count, 2);
+        assert!(parser.events().last_bell.is_some());
+
+        let events = parser.take_events();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], TerminalEvent::Bell { .. }));
+        assert!(parser.take_events().is_empty());
+        assert_eq!(parser.events().bell_count, 2);
+
+        // BEL terminating an OSC is not a bell
+        let parser = parse(3, 10, b"\x1b]0;title\x07");
+        assert_eq!(parser.events().bell_count, 0);
+    }
+
+    #[test]
+    fn test_parser_notifications() {
+        let mut parser = parse(
+            3,
+            10,
+            b"\x1b]9;build done; 0 errors\x07\x1b]777;notify;CI;tests passed\x1b\\",
+        );
+        let events = parser.events();
+        assert_eq!(events.notification_count, 2);
+        assert_eq!(events.bell_count, 0);
+        assert_eq!(events.notifications[0].title, None);
+        assert_eq!(events.notifications[0].body, "build done; 0 errors");
+        assert_eq!(events.notifications[1].title.as_deref(), Some("CI"));
+        assert_eq!(events.notifications[1].body, "tests passed");
+        assert_eq!(parser.take_events().len(), 2);
+
+        // ConEmu progress and other OSC 777 commands are ignored
+        let parser = parse(3, 10, b"\x1b]9;4;1;50\x07\x1b]777;preexec\x07");
+        assert!(parser.events().is_empty());
+    }
+
+    #[test]
+    fn test_parser_notification_history_is_bounded() {
+        let mut parser = Parser::new(Grid::new(Dimensions::new(3, 10)));
+        for i in 0..NOTIFICATION_HISTORY + 5 {
+            parser.process(format!("\x1b]9;n{i}\x07").as_bytes());
+        }
+        let events = parser.events();
+        assert_eq!(events.notification_count, NOTIFICATION_HISTORY as u64 + 5);
+        assert_eq!(events.notifications.len(), NOTIFICATION_HISTORY);
+        assert_eq!(events.notifications[0].body, "n5");
+    }
 }
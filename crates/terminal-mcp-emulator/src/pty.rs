@@ -2,12 +2,15 @@
 
 use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 use tokio::task;
 use tracing::{debug, error, info, warn};
 
-use terminal_mcp_core::{Dimensions, Error, Result};
+use terminal_mcp_core::{Dimensions, Error, ResourceLimits, Result, SandboxSettings, TermiosFlags};
+
+use crate::limits::SessionCgroup;
 
 /// Handle to a spawned PTY process.
 pub struct PtyHandle {
@@ -25,6 +28,8 @@ pub struct PtyHandle {
     tmux_session: Option<String>,
     /// Last tmux capture content (for change detection)
     last_tmux_content: Arc<Mutex<Vec<u8>>>,
+    /// A read hit end of file
+    closed: Arc<AtomicBool>,
 }
 
 impl std::fmt::Debug for PtyHandle {
@@ -144,9 +149,42 @@ impl PtyHandle {
             reader: Arc::new(Mutex::new(Some(reader))),
             tmux_session: None,
             last_tmux_content: Arc::new(Mutex::new(Vec::new())),
+            closed: Arc::default(),
         })
     }
 
+    /// Spawn a command in a Linux namespace sandbox and/or with resource limits.
+    ///
+    /// With `sandbox`, the child gets its own user, mount, PID, IPC and network
+    /// namespaces and a root built from it, and `cwd` is a path inside the
+    /// sandbox; this requires util-linux `unshare` and unprivileged user
+    /// namespaces. The rlimits in `limits` apply to every process, and all of
+    /// them are placed in `cgroup` if given.
+    pub fn spawn_confined(
+        command: &str,
+        args: &[String],
+        dimensions: Dimensions,
+        cwd: Option<String>,
+        sandbox: Option<&SandboxSettings>,
+        limits: &ResourceLimits,
+        cgroup: Option<&SessionCgroup>,
+    ) -> Result<Self> {
+        let (mut program, mut wrapped) = (command.to_string(), args.to_vec());
+        let mut cwd = cwd;
+        if let Some(sandbox) = sandbox {
+            info!(
+                "Sandboxing command '{}': project_dir={:?}, network={}",
+                command, sandbox.project_dir, sandbox.network
+            );
+            (program, wrapped) =
+                crate::sandbox::wrap_command(&program, &wrapped, cwd.as_deref(), sandbox)?;
+            // The working directory is set inside the sandbox
+            cwd = None;
+        }
+        (program, wrapped) = crate::limits::wrap_command(&program, &wrapped, limits, cgroup);
+        Self::spawn(&program, &wrapped, dimensions, cwd)
+    }
+
     /// Spawn a PTY wrapper for controlling an existing tmux session.
     ///
     /// This creates a "virtual" PTY that uses tmux commands for I/O,
@@ -175,6 +213,7 @@ impl PtyHandle {
             reader: Arc::new(Mutex::new(None)),
             tmux_session: Some(session_name.to_string()),
             last_tmux_content: Arc::new(Mutex::new(Vec::new())),
+            closed: Arc::default(),
         })
     }
 
@@ -303,6 +342,8 @@ impl PtyHandle {
                 buffer.truncate(n);
                 if n > 0 {
                     debug!("Read {} bytes from PTY", n);
+                } else {
+                    self.closed.store(true, Ordering::Relaxed);
                 }
                 Ok(buffer)
             }
@@ -397,6 +438,53 @@ impl PtyHandle {
         Ok(data.len())
     }
 
+    /// Paste text into a tmux session via a tmux paste buffer.
+    ///
+    /// Uses `paste-buffer -p`, so tmux wraps the text in bracketed paste
+    /// sequences if the application inside has requested them. ESC is
+    /// dropped from the text, so it can't end the paste early or smuggle in
+    /// other control sequences.
+    pub fn tmux_paste(&self, text: &str) -> Result<usize> {
+        use std::process::Command;
+
+        let text = text.replace('\x1b', "");
+        let text = text.as_str();
+
+        let session = self
+            .tmux_session
+            .as_ref()
+            .ok_or_else(|| Error::PtyError("Not a tmux session".to_string()))?;
+        let buffer = format!("terminal-mcp-paste-{session}");
+
+        debug!(
+            "Pasting {} bytes into tmux session '{}'",
+            text.len(),
+            session
+        );
+
+        let status = Command::new("tmux")
+            .args(["set-buffer", "-b", &buffer, "--", text])
+            .status()
+            .map_err(|e| Error::PtyError(format!("Failed to set tmux buffer: {e}")))?;
+        if !status.success() {
+            return Err(Error::PtyError(format!(
+                "Tmux set-buffer failed with status: {status}"
+            )));
+        }
+
+        let status = Command::new("tmux")
+            .args(["paste-buffer", "-p", "-d", "-b", &buffer, "-t", session])
+            .status()
+            .map_err(|e| Error::PtyError(format!("Failed to paste tmux buffer: {e}")))?;
+        if !status.success() {
+            return Err(Error::PtyError(format!(
+                "Tmux paste-buffer failed with status: {status}"
+            )));
+        }
+
+        Ok(text.len())
+    }
+
     /// Write data to the PTY.
     ///
     /// # Arguments
@@ -500,6 +588,37 @@ impl PtyHandle {
         Ok(*dims)
     }
 
+    /// Read the line discipline flags the application set on the slave.
+    ///
+    /// `None` in tmux mode (the PTY belongs to the tmux client), on Windows,
+    /// or if the flags can't be read.
+    pub fn termios(&self) -> Option<TermiosFlags> {
+        #[cfg(unix)]
+        {
+            let master = self.master.lock().unwrap();
+            let fd = master.as_ref()?.as_raw_fd()?;
+
+            // SAFETY: tcgetattr fills the struct and we only read it on success
+            let termios = unsafe {
+                let mut termios = std::mem::MaybeUninit::<libc::termios>::zeroed();
+                if libc::tcgetattr(fd, termios.as_mut_ptr()) != 0 {
+                    return None;
+                }
+                termios.assume_init()
+            };
+            let lflag = termios.c_lflag;
+            Some(TermiosFlags {
+                echo: lflag & libc::ECHO != 0,
+                canonical: lflag & libc::ICANON != 0,
+                signals: lflag & libc::ISIG != 0,
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            None
+        }
+    }
+
     /// Check if this PTY is in tmux mode.
     pub fn is_tmux_mode(&self) -> bool {
         self.tmux_session.is_some()
@@ -515,6 +634,15 @@ impl PtyHandle {
         Ok(())
     }
 
+    /// Whether a read found the PTY at end of file.
+    ///
+    /// That happens once every process holding the terminal has exited and
+    /// all of its output has been read, which can be well after the child
+    /// itself exited.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
     /// Check if the child process is still running.
     pub fn is_alive(&self) -> bool {
         // Tmux mode: check if session exists
@@ -543,6 +671,23 @@ impl PtyHandle {
         child.try_wait().ok().flatten().is_none()
     }
 
+    /// Exit code of the child, once it has exited.
+    pub fn exit_code(&self) -> Option<u32> {
+        let mut child_lock = self.child.lock().ok()?;
+        let status = child_lock.as_mut()?.try_wait().ok()??;
+        Some(status.exit_code())
+    }
+
+    /// Signal that ended the child, as described by the OS, if it was killed.
+    pub fn exit_signal(&self) -> Option<String> {
+        let mut child_lock = self.child.lock().ok()?;
+        let status = child_lock.as_mut()?.try_wait().ok()??;
+        status
+            .to_string()
+            .strip_prefix("Terminated by ")
+            .map(str::to_string)
+    }
+
     /// Wait for the child process to exit.
     ///
     /// Returns the exit status if available.
@@ -649,6 +794,127 @@ mod tests {
         assert!(pty.is_alive());
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_pty_termios() {
+        let pty = PtyHandle::spawn(
+            "sh",
+            &["-c".to_string(), "stty -echo; sleep 2".to_string()],
+            Dimensions::new(24, 80),
+            None,
+        )
+        .unwrap();
+
+        // A fresh PTY starts in cooked mode with echo
+        let initial = pty.termios().unwrap();
+        assert!(initial.canonical);
+        assert!(initial.signals);
+
+        let mut echo_off = false;
+        for _ in 0..50 {
+            if !pty.termios().unwrap().echo {
+                echo_off = true;
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(echo_off);
+        assert!(pty.termios().unwrap().canonical);
+        pty.kill().unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_pty_spawn_sandboxed() {
+        if !crate::sandbox::is_available() {
+            return;
+        }
+
+        let project = std::env::temp_dir().join(format!("sandbox-test-{}", std::process::id()));
+        std::fs::create_dir_all(&project).unwrap();
+        let sandbox = SandboxSettings {
+            project_dir: Some(project.to_string_lossy().into_owned()),
+            ..SandboxSettings::default()
+        };
+        let script = "touch /usr/x 2>/dev/null || echo usr-ro; \
+                      touch out && echo project-rw; \
+                      echo dev-$(ls /dev | tr '\\n' ,); touch /dev/shm/x && echo shm-rw; \
+                      mount -o remount,bind,rw /usr 2>/dev/null || echo remount-denied; \
+                      umount /usr 2>/dev/null || echo umount-denied; \
+                      echo procs-$(ls -d /proc/[0-9]* | wc -l); ls /root 2>/dev/null || echo no-home";
+        let pty = PtyHandle::spawn_confined(
+            "sh",
+            &["-c".to_string(), script.to_string()],
+            Dimensions::new(24, 80),
+            None,
+            Some(&sandbox),
+            &ResourceLimits::default(),
+            None,
+        )
+        .unwrap();
+
+        let mut output = Vec::new();
+        for _ in 0..100 {
+            output.extend(pty.read().unwrap_or_default());
+            if String::from_utf8_lossy(&output).contains("no-home") {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let output = String::from_utf8_lossy(&output);
+        assert!(output.contains("usr-ro"), "{output}");
+        assert!(output.contains("project-rw"), "{output}");
+        assert!(
+            output.contains(
+                "dev-fd,full,null,ptmx,pts,random,shm,stderr,stdin,stdout,tty,urandom,zero,"
+            ),
+            "{output}"
+        );
+        assert!(output.contains("shm-rw"), "{output}");
+        // The command can't undo the read-only binds
+        assert!(output.contains("remount-denied"), "{output}");
+        assert!(output.contains("umount-denied"), "{output}");
+        // Only the sandbox's own processes are visible
+        let procs: usize = output
+            .split("procs-")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|count| count.parse().ok())
+            .unwrap();
+        assert!(procs < 10, "{output}");
+        assert!(project.join("out").exists());
+
+        std::fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_pty_cpu_limit() {
+        let limits = ResourceLimits {
+            cpu_seconds: Some(1),
+            ..Default::default()
+        };
+        let pty = PtyHandle::spawn_confined(
+            "sh",
+            &["-c".to_string(), "while :; do :; done".to_string()],
+            Dimensions::new(24, 80),
+            None,
+            None,
+            &limits,
+            None,
+        )
+        .unwrap();
+
+        for _ in 0..100 {
+            if !pty.is_alive() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let signal = pty.exit_signal().unwrap();
+        assert!(signal.to_lowercase().contains("cpu"), "{signal}");
+    }
+
     #[test]
     fn test_pty_dimensions() {
         let shell = if cfg!(windows) { "cmd.exe" } else { "/bin/sh" };
@@ -743,4 +1009,46 @@ mod tests {
         // Cleanup
         pty.kill().unwrap();
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_tmux_paste_drops_escapes() {
+        use std::process::Command;
+
+        let name = format!("terminal-mcp-test-{}", std::process::id());
+        // `cat -v` shows an ESC that got through as ^[
+        let created = Command::new("tmux")
+            .args([
+                "new-session",
+                "-d",
+                "-s",
+                &name,
+                "-x",
+                "80",
+                "-y",
+                "24",
+                "cat -v",
+            ])
+            .status();
+        if !created.is_ok_and(|status| status.success()) {
+            return;
+        }
+        let pty = PtyHandle::spawn_tmux(&name, Dimensions::new(24, 80)).unwrap();
+
+        pty.tmux_paste("a\x1b[201~b\x1b[31mc\r").unwrap();
+        let mut pane = String::new();
+        for _ in 0..50 {
+            std::thread::sleep(Duration::from_millis(20));
+            pane = String::from_utf8_lossy(&pty.read().unwrap()).into_owned();
+            if pane.contains("a[201~b[31mc") {
+                break;
+            }
+        }
+        let _ = Command::new("tmux")
+            .args(["kill-session", "-t", &name])
+            .status();
+
+        assert!(pane.contains("a[201~b[31mc"), "{pane:?}");
+        assert!(!pane.contains("^["), "{pane:?}");
+    }
 }
@@ -0,0 +1,605 @@
+//! Software rendering of the terminal screen to PNG, SVG and HTML.
+//!
+//! PNG output uses the public domain 8x8 bitmap font from `font8x8`, with
+//! glyph rows doubled to get the usual 1:2 terminal cell shape. SVG output
+//! uses text elements in a monospace font.
+
+use std::fmt::Write as _;
+
+use font8x8::{
+    UnicodeFonts, BASIC_FONTS, BLOCK_FONTS, BOX_FONTS, GREEK_FONTS, LATIN_FONTS, MISC_FONTS,
+};
+use terminal_mcp_core::{Cell, Color, CursorStyle, Error, Result};
+
+use crate::grid::Grid;
+
+/// RGB color triple.
+pub type Rgb = (u8, u8, u8);
+
+/// Cell width in PNG pixels before scaling.
+const CELL_WIDTH: usize = 8;
+
+/// Cell height in PNG pixels before scaling.
+const CELL_HEIGHT: usize = 16;
+
+/// Cell width in SVG units.
+const SVG_CELL_WIDTH: usize = 9;
+
+/// Cell height in SVG units.
+const SVG_CELL_HEIGHT: usize = 18;
+
+/// Largest accepted scale factor.
+const MAX_SCALE: u32 = 4;
+
+/// Renders a grid, with colors, attributes and cursor, to images.
+#[derive(Debug, Clone)]
+pub struct ScreenRenderer {
+    /// Color used for `Color::Default` foreground
+    pub default_fg: Rgb,
+    /// Color used for `Color::Default` background
+    pub default_bg: Rgb,
+    /// Integer scale factor for PNG output (1-4)
+    pub scale: u32,
+    /// Draw the cursor when the application shows it
+    pub show_cursor: bool,
+}
+
+impl Default for ScreenRenderer {
+    fn default() -> Self {
+        Self {
+            default_fg: (229, 229, 229),
+            default_bg: (0, 0, 0),
+            scale: 1,
+            show_cursor: true,
+        }
+    }
+}
+
+/// Resolved colors and attributes for drawing one cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CellStyle {
+    fg: Rgb,
+    bg: Rgb,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+    hidden: bool,
+}
+
+impl ScreenRenderer {
+    /// Create a renderer with a dark theme at scale 1.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the PNG scale factor, clamped to 1-4.
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale.clamp(1, MAX_SCALE);
+        self
+    }
+
+    /// Render the grid as a PNG image.
+    pub fn render_png(&self, grid: &Grid) -> Result<Vec<u8>> {
+        let (width, height, pixels) = self.rasterize(grid);
+        let scale = self.scale.clamp(1, MAX_SCALE) as usize;
+        let pixels = upscale(&pixels, width, height, scale);
+        let (width, height) = (width * scale, height * scale);
+
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&pixels))
+            .map_err(|e| Error::Other(format!("PNG encoding failed: {e}")))?;
+
+        Ok(png)
+    }
+
+    /// Render the grid as a self-contained SVG document.
+    pub fn render_svg(&self, grid: &Grid) -> String {
+        let dims = grid.dimensions();
+        let width = dims.cols as usize * SVG_CELL_WIDTH;
+        let height = dims.rows as usize * SVG_CELL_HEIGHT;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="monospace" font-size="15">"#
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect width="{width}" height="{height}" fill="{}"/>"#,
+            hex(self.default_bg)
+        );
+
+        for row in 0..dims.rows {
+            let styles = self.row_styles(grid, row);
+            let y = row as usize * SVG_CELL_HEIGHT;
+
+            // Backgrounds, merged into runs
+            for (start, len, style) in runs(&styles, |a, b| a.bg == b.bg) {
+                if style.bg != self.default_bg {
+                    let _ = writeln!(
+                        svg,
+                        r#"<rect x="{}" y="{y}" width="{}" height="{SVG_CELL_HEIGHT}" fill="{}"/>"#,
+                        start * SVG_CELL_WIDTH,
+                        len * SVG_CELL_WIDTH,
+                        hex(style.bg)
+                    );
+                }
+            }
+
+            // Text, one element per run of identical style
+            let same_text = |a: &CellStyle, b: &CellStyle| {
+                CellStyle { bg: a.fg, ..*a } == CellStyle { bg: b.fg, ..*b }
+            };
+            for (start, len, style) in runs(&styles, same_text) {
+                let text: String = styles[start..start + len]
+                    .iter()
+                    .map(|(style, c)| if style.hidden { ' ' } else { *c })
+                    .collect();
+                let trimmed = text.trim_start_matches(' ');
+                let start = start + (text.chars().count() - trimmed.chars().count());
+                let trimmed = trimmed.trim_end_matches(' ');
+                if trimmed.is_empty() {
+                    continue;
+                }
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" textLength="{}" lengthAdjust="spacingAndGlyphs" xml:space="preserve" fill="{}"{}>{}</text>"#,
+                    start * SVG_CELL_WIDTH,
+                    y + SVG_CELL_HEIGHT - 5,
+                    trimmed.chars().count() * SVG_CELL_WIDTH,
+                    hex(style.fg),
+                    svg_text_attributes(&style),
+                    escape_xml(trimmed)
+                );
+            }
+        }
+
+        if let Some((row, col, style)) = self.thin_cursor(grid) {
+            let x = col as usize * SVG_CELL_WIDTH;
+            let y = row as usize * SVG_CELL_HEIGHT;
+            let (w, h, y) = match style {
+                CursorStyle::Bar => (2, SVG_CELL_HEIGHT, y),
+                _ => (SVG_CELL_WIDTH, 2, y + SVG_CELL_HEIGHT - 2),
+            };
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="{}"/>"#,
+                hex(self.default_fg)
+            );
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Render the grid as a standalone HTML page with inline styles.
+    ///
+    /// Each row becomes one line of a `<pre>` block, with a `<span>` per run
+    /// of identically styled cells. Trailing unstyled blanks are dropped.
+    pub fn render_html(&self, grid: &Grid) -> String {
+        let dims = grid.dimensions();
+        let plain = self.cell_style(&Cell::default(), false);
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str("<title>Terminal Screen</title>\n</head>\n<body>\n");
+        let _ = write!(
+            html,
+            r#"<pre style="background:{};color:{};font-family:monospace;line-height:1.2;padding:8px;display:inline-block">"#,
+            hex(self.default_bg),
+            hex(self.default_fg)
+        );
+
+        for row in 0..dims.rows {
+            let mut styles = self.row_styles(grid, row);
+            while styles
+                .last()
+                .is_some_and(|(style, c)| *style == plain && *c == ' ')
+            {
+                styles.pop();
+            }
+
+            for (start, len, style) in runs(&styles, |a, b| a == b) {
+                let text: String = styles[start..start + len]
+                    .iter()
+                    .map(|(style, c)| if style.hidden { ' ' } else { *c })
+                    .collect();
+                let css = self.css(&style);
+                if css.is_empty() {
+                    html.push_str(&escape_xml(&text));
+                } else {
+                    let _ = write!(html, r#"<span style="{css}">{}</span>"#, escape_xml(&text));
+                }
+            }
+            html.push('\n');
+        }
+
+        html.push_str("</pre>\n</body>\n</html>\n");
+        html
+    }
+
+    /// Resolve colors and attributes for every cell of a row.
+    fn row_styles(&self, grid: &Grid, row: u16) -> Vec<(CellStyle, char)> {
+        (0..grid.dimensions().cols)
+            .map(|col| {
+                let cell = grid.cell(row, col).unwrap_or_default();
+                (
+                    self.cell_style(&cell, self.is_block_cursor(grid, row, col)),
+                    cell.character,
+                )
+            })
+            .collect()
+    }
+
+    /// Inline CSS for a styled run, omitting the page defaults.
+    fn css(&self, style: &CellStyle) -> String {
+        let mut css = Vec::new();
+        if style.fg != self.default_fg {
+            css.push(format!("color:{}", hex(style.fg)));
+        }
+        if style.bg != self.default_bg {
+            css.push(format!("background:{}", hex(style.bg)));
+        }
+        if style.bold {
+            css.push("font-weight:bold".to_string());
+        }
+        if style.italic {
+            css.push("font-style:italic".to_string());
+        }
+        let decorations = text_decorations(style);
+        if !decorations.is_empty() {
+            css.push(format!("text-decoration:{decorations}"));
+        }
+        css.join(";")
+    }
+
+    /// Draw the grid into an RGB buffer at scale 1.
+    fn rasterize(&self, grid: &Grid) -> (usize, usize, Vec<u8>) {
+        let dims = grid.dimensions();
+        let width = dims.cols as usize * CELL_WIDTH;
+        let height = dims.rows as usize * CELL_HEIGHT;
+        let mut canvas = Canvas {
+            width,
+            pixels: vec![0; width * height * 3],
+        };
+
+        for row in 0..dims.rows {
+            for col in 0..dims.cols {
+                let cell = grid.cell(row, col).unwrap_or_default();
+                let style = self.cell_style(&cell, self.is_block_cursor(grid, row, col));
+                let x = col as usize * CELL_WIDTH;
+                let y = row as usize * CELL_HEIGHT;
+
+                canvas.fill(x, y, CELL_WIDTH, CELL_HEIGHT, style.bg);
+                if !style.hidden && cell.character != ' ' {
+                    draw_glyph(&mut canvas, x, y, cell.character, &style);
+                }
+                if style.underline {
+                    canvas.fill(x, y + CELL_HEIGHT - 2, CELL_WIDTH, 1, style.fg);
+                }
+                if style.strikethrough {
+                    canvas.fill(x, y + CELL_HEIGHT / 2, CELL_WIDTH, 1, style.fg);
+                }
+            }
+        }
+
+        if let Some((row, col, style)) = self.thin_cursor(grid) {
+            let x = col as usize * CELL_WIDTH;
+            let y = row as usize * CELL_HEIGHT;
+            match style {
+                CursorStyle::Bar => canvas.fill(x, y, 2, CELL_HEIGHT, self.default_fg),
+                _ => canvas.fill(x, y + CELL_HEIGHT - 2, CELL_WIDTH, 2, self.default_fg),
+            }
+        }
+
+        (width, height, canvas.pixels)
+    }
+
+    /// Resolve a cell's colors, applying bold-as-bright, reverse, dim and hidden.
+    fn cell_style(&self, cell: &Cell, block_cursor: bool) -> CellStyle {
+        let attrs = cell.attrs;
+        let fg_color = match cell.fg.palette_index() {
+            // Like xterm, bold makes the eight base colors bright
+            Some(index) if attrs.bold && index < 8 => Color::Indexed(index + 8),
+            _ => cell.fg,
+        };
+        let mut fg = fg_color.to_rgb().unwrap_or(self.default_fg);
+        let mut bg = cell.bg.to_rgb().unwrap_or(self.default_bg);
+
+        if attrs.reverse != block_cursor {
+            std::mem::swap(&mut fg, &mut bg);
+        }
+        if attrs.dim {
+            fg = blend(fg, bg);
+        }
+
+        CellStyle {
+            fg,
+            bg,
+            bold: attrs.bold,
+            italic: attrs.italic,
+            underline: attrs.underline,
+            strikethrough: attrs.strikethrough,
+            hidden: attrs.hidden,
+        }
+    }
+
+    /// Whether a block cursor is drawn on this cell.
+    fn is_block_cursor(&self, grid: &Grid, row: u16, col: u16) -> bool {
+        let cursor = grid.cursor();
+        self.show_cursor
+            && cursor.visible
+            && cursor.style == CursorStyle::Block
+            && cursor.position.row == row
+            && cursor.position.col == col
+    }
+
+    /// Position and style of a visible underline or bar cursor.
+    fn thin_cursor(&self, grid: &Grid) -> Option<(u16, u16, CursorStyle)> {
+        let cursor = grid.cursor();
+        let visible = self.show_cursor && cursor.visible && cursor.style != CursorStyle::Block;
+        visible.then_some((cursor.position.row, cursor.position.col, cursor.style))
+    }
+}
+
+/// RGB pixel buffer.
+struct Canvas {
+    width: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    /// Set one pixel, ignoring coordinates outside the canvas.
+    fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        if x >= self.width {
+            return;
+        }
+        let idx = (y * self.width + x) * 3;
+        if let Some(pixel) = self.pixels.get_mut(idx..idx + 3) {
+            pixel.copy_from_slice(&[color.0, color.1, color.2]);
+        }
+    }
+
+    /// Fill a rectangle.
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, color: Rgb) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.set(px, py, color);
+            }
+        }
+    }
+}
+
+/// Look up the 8x8 bitmap for a character.
+fn glyph(c: char) -> Option<[u8; 8]> {
+    BASIC_FONTS
+        .get(c)
+        .or_else(|| BOX_FONTS.get(c))
+        .or_else(|| BLOCK_FONTS.get(c))
+        .or_else(|| LATIN_FONTS.get(c))
+        .or_else(|| GREEK_FONTS.get(c))
+        .or_else(|| MISC_FONTS.get(c))
+}
+
+/// Draw a character into the cell at (x, y).
+///
+/// Characters missing from the font are drawn as an outlined box.
+fn draw_glyph(canvas: &mut Canvas, x: usize, y: usize, c: char, style: &CellStyle) {
+    let Some(bitmap) = glyph(c) else {
+        canvas.fill(x + 1, y + 3, CELL_WIDTH - 2, 1, style.fg);
+        canvas.fill(x + 1, y + CELL_HEIGHT - 4, CELL_WIDTH - 2, 1, style.fg);
+        canvas.fill(x + 1, y + 3, 1, CELL_HEIGHT - 6, style.fg);
+        canvas.fill(x + CELL_WIDTH - 2, y + 3, 1, CELL_HEIGHT - 6, style.fg);
+        return;
+    };
+
+    for (gy, bits) in bitmap.iter().enumerate() {
+        // Slant italics by shifting the top half one pixel right
+        let shift = usize::from(style.italic && gy < 4);
+        for gx in 0..8 {
+            if bits & (1 << gx) == 0 {
+                continue;
+            }
+            let px = x + gx + shift;
+            // Bold smears each pixel one to the right, staying inside the cell
+            let end = if style.bold { px + 2 } else { px + 1 };
+            for px in px..end.min(x + CELL_WIDTH) {
+                canvas.set(px, y + gy * 2, style.fg);
+                canvas.set(px, y + gy * 2 + 1, style.fg);
+            }
+        }
+    }
+}
+
+/// Nearest-neighbour upscale of an RGB buffer.
+fn upscale(pixels: &[u8], width: usize, height: usize, scale: usize) -> Vec<u8> {
+    if scale == 1 {
+        return pixels.to_vec();
+    }
+    let mut out = Vec::with_capacity(pixels.len() * scale * scale);
+    for row in pixels.chunks(width * 3).take(height) {
+        let mut scaled_row = Vec::with_capacity(row.len() * scale);
+        for pixel in row.chunks(3) {
+            for _ in 0..scale {
+                scaled_row.extend_from_slice(pixel);
+            }
+        }
+        for _ in 0..scale {
+            out.extend_from_slice(&scaled_row);
+        }
+    }
+    out
+}
+
+/// Split a row into runs of cells that compare equal under `same`.
+///
+/// Returns (start column, length, style) for each run.
+fn runs(
+    cells: &[(CellStyle, char)],
+    same: impl Fn(&CellStyle, &CellStyle) -> bool,
+) -> Vec<(usize, usize, CellStyle)> {
+    let mut result: Vec<(usize, usize, CellStyle)> = Vec::new();
+    for (idx, (style, _)) in cells.iter().enumerate() {
+        match result.last_mut() {
+            Some((_, len, run_style)) if same(run_style, style) => *len += 1,
+            _ => result.push((idx, 1, *style)),
+        }
+    }
+    result
+}
+
+/// Font and decoration attributes for an SVG text element.
+fn svg_text_attributes(style: &CellStyle) -> String {
+    let mut attrs = String::new();
+    if style.bold {
+        attrs.push_str(r#" font-weight="bold""#);
+    }
+    if style.italic {
+        attrs.push_str(r#" font-style="italic""#);
+    }
+    let decorations = text_decorations(style);
+    if !decorations.is_empty() {
+        let _ = write!(attrs, r#" text-decoration="{decorations}""#);
+    }
+    attrs
+}
+
+/// Space-separated `text-decoration` values for underline and strikethrough.
+fn text_decorations(style: &CellStyle) -> String {
+    [
+        (style.underline, "underline"),
+        (style.strikethrough, "line-through"),
+    ]
+    .iter()
+    .filter(|(on, _)| *on)
+    .map(|(_, name)| *name)
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Mix two colors half and half.
+fn blend(a: Rgb, b: Rgb) -> Rgb {
+    let mix = |x: u8, y: u8| ((x as u16 + y as u16) / 2) as u8;
+    (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+/// Format a color as `#rrggbb`.
+fn hex(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2)
+}
+
+/// Escape text for use in XML content.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c if c.is_control() => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+    use terminal_mcp_core::Dimensions;
+
+    fn screen(rows: u16, cols: u16, bytes: &[u8]) -> Grid {
+        let mut parser = Parser::new(Grid::new(Dimensions::new(rows, cols)));
+        parser.process(bytes);
+        parser.into_grid()
+    }
+
+    /// Read back the pixel at (x, y) of a scale-1 raster.
+    fn pixel(renderer: &ScreenRenderer, grid: &Grid, x: usize, y: usize) -> Rgb {
+        let (width, _, pixels) = renderer.rasterize(grid);
+        let idx = (y * width + x) * 3;
+        (pixels[idx], pixels[idx + 1], pixels[idx + 2])
+    }
+
+    #[test]
+    fn test_render_png_header_and_size() {
+        let grid = screen(2, 3, b"hi");
+        let png = ScreenRenderer::new()
+            .with_scale(2)
+            .render_png(&grid)
+            .unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        // IHDR width and height
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 48);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 64);
+    }
+
+    #[test]
+    fn test_rasterize_colors() {
+        let renderer = ScreenRenderer {
+            show_cursor: false,
+            ..ScreenRenderer::new()
+        };
+        // Red background in the first cell, default in the second
+        let grid = screen(1, 2, b"\x1b[41m \x1b[0m ");
+        assert_eq!(pixel(&renderer, &grid, 0, 0), (205, 0, 0));
+        assert_eq!(pixel(&renderer, &grid, 8, 0), renderer.default_bg);
+
+        // A full block is drawn entirely in the foreground color
+        let grid = screen(1, 1, "\x1b[32m█".as_bytes());
+        assert_eq!(pixel(&renderer, &grid, 4, 8), (0, 205, 0));
+    }
+
+    #[test]
+    fn test_rasterize_block_cursor_inverts_cell() {
+        let renderer = ScreenRenderer::new();
+        let grid = screen(1, 2, b"");
+        assert_eq!(pixel(&renderer, &grid, 0, 0), renderer.default_fg);
+        assert_eq!(pixel(&renderer, &grid, 8, 0), renderer.default_bg);
+
+        let grid = screen(1, 2, b"\x1b[?25l");
+        assert_eq!(pixel(&renderer, &grid, 0, 0), renderer.default_bg);
+    }
+
+    #[test]
+    fn test_render_html() {
+        let renderer = ScreenRenderer {
+            show_cursor: false,
+            ..ScreenRenderer::new()
+        };
+        let grid = screen(2, 12, b"a<b \x1b[4;44mlink\x1b[0m   \r\n\x1b[7mx");
+        let html = renderer.render_html(&grid);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(r#"<pre style="background:#000000;color:#e5e5e5;"#));
+        assert!(html.contains(
+            r#"a&lt;b <span style="background:#0000ee;text-decoration:underline">link</span>"#
+        ));
+        // Reverse video swaps the default colors
+        assert!(html.contains(r#"<span style="color:#000000;background:#e5e5e5">x</span>"#));
+        // Trailing blanks are trimmed
+        assert!(!html.contains("</span>   "));
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let grid = screen(2, 10, b"\x1b[1;31m<err>\x1b[0m ok\x1b[6 q");
+        let svg = ScreenRenderer::new().render_svg(&grid);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r##"fill="#ff0000" font-weight="bold">&lt;err&gt;</text>"##));
+        assert!(svg.contains(">ok</text>"));
+        // Bar cursor after "ok"
+        assert!(svg.contains(r#"<rect x="72" y="0" width="2""#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
@@ -0,0 +1,350 @@
+//! Row storage for the grid and its scrollback.
+//!
+//! Screen rows own their characters, so scrolling moves rows rather than
+//! cells. Styles are interned in a [`StyleTable`] shared by the screen, and
+//! each cell keeps only a small index into it. Rows leaving the screen are
+//! packed into a compact form: the text plus run-length encoded styles, with
+//! trailing blanks dropped.
+
+use std::collections::HashMap;
+
+use terminal_mcp_core::{Cell, CellAttributes, Color};
+
+/// Index of a style in a [`StyleTable`].
+pub(crate) type StyleId = u32;
+
+/// The default style, present in every table.
+pub(crate) const DEFAULT_STYLE: StyleId = 0;
+
+/// Table size below which the table is never compacted.
+const MIN_COMPACT_AT: usize = 1024;
+
+/// Colors and attributes shared by cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Style {
+    fg: Color,
+    bg: Color,
+    attrs: CellAttributes,
+}
+
+impl Style {
+    pub(crate) fn new(fg: Color, bg: Color, attrs: CellAttributes) -> Self {
+        Self { fg, bg, attrs }
+    }
+
+    pub(crate) fn of(cell: &Cell) -> Self {
+        Self::new(cell.fg, cell.bg, cell.attrs)
+    }
+
+    pub(crate) fn cell(self, character: char) -> Cell {
+        Cell {
+            character,
+            fg: self.fg,
+            bg: self.bg,
+            attrs: self.attrs,
+        }
+    }
+}
+
+/// Styles used on screen, each stored once.
+///
+/// Styles are never removed one by one. Once the table has grown to twice
+/// the number of styles in use, the grid calls [`StyleTable::compact`] to
+/// drop the ones no row refers to any more.
+#[derive(Debug)]
+pub(crate) struct StyleTable {
+    styles: Vec<Style>,
+    ids: HashMap<Style, StyleId>,
+    /// Most recently interned style, usually the current rendition
+    last: (Style, StyleId),
+    /// Size at which the table is due for compaction
+    compact_at: usize,
+}
+
+impl Default for StyleTable {
+    fn default() -> Self {
+        let default = Style::of(&Cell::default());
+        Self {
+            styles: vec![default],
+            ids: HashMap::from([(default, DEFAULT_STYLE)]),
+            last: (default, DEFAULT_STYLE),
+            compact_at: MIN_COMPACT_AT,
+        }
+    }
+}
+
+impl StyleTable {
+    /// Look up a style by index.
+    pub(crate) fn get(&self, id: StyleId) -> Style {
+        self.styles[id as usize]
+    }
+
+    /// Index of a style already in the table.
+    pub(crate) fn find(&mut self, style: Style) -> Option<StyleId> {
+        if self.last.0 == style {
+            return Some(self.last.1);
+        }
+        let id = *self.ids.get(&style)?;
+        self.last = (style, id);
+        Some(id)
+    }
+
+    /// Whether adding another style should first compact the table.
+    pub(crate) fn is_due(&self) -> bool {
+        self.styles.len() >= self.compact_at
+    }
+
+    /// Index of a style, adding it if needed.
+    pub(crate) fn intern(&mut self, style: Style) -> StyleId {
+        if let Some(id) = self.find(style) {
+            return id;
+        }
+        let id = self.styles.len() as StyleId;
+        self.styles.push(style);
+        self.ids.insert(style, id);
+        self.last = (style, id);
+        id
+    }
+
+    /// Keep only the styles used by `rows`, renumbering them in place.
+    pub(crate) fn compact<'a>(&mut self, rows: impl Iterator<Item = &'a mut Row>) {
+        let mut table = Self::default();
+        let mut remap: HashMap<StyleId, StyleId> = HashMap::from([(DEFAULT_STYLE, DEFAULT_STYLE)]);
+        for row in rows {
+            for id in &mut row.styles {
+                *id = *remap
+                    .entry(*id)
+                    .or_insert_with(|| table.intern(self.styles[*id as usize]));
+            }
+        }
+        table.compact_at = MIN_COMPACT_AT.max(table.styles.len() * 2);
+        *self = table;
+    }
+}
+
+/// A row on the visible screen.
+#[derive(Debug, Clone)]
+pub(crate) struct Row {
+    chars: Vec<char>,
+    /// Style of each cell, indexing the grid's [`StyleTable`]
+    styles: Vec<StyleId>,
+    /// Continues the previous row (soft wrap)
+    pub(crate) wrapped: bool,
+    /// Generation at which the row last changed
+    pub(crate) generation: u64,
+}
+
+impl Row {
+    /// A blank row of `cols` cells.
+    pub(crate) fn blank(cols: u16, generation: u64) -> Self {
+        Self {
+            chars: vec![' '; cols as usize],
+            styles: vec![DEFAULT_STYLE; cols as usize],
+            wrapped: false,
+            generation,
+        }
+    }
+
+    /// A row holding `cells`, interning their styles.
+    pub(crate) fn from_cells(
+        cells: &[Cell],
+        table: &mut StyleTable,
+        wrapped: bool,
+        generation: u64,
+    ) -> Self {
+        Self {
+            chars: cells.iter().map(|cell| cell.character).collect(),
+            styles: cells
+                .iter()
+                .map(|cell| table.intern(Style::of(cell)))
+                .collect(),
+            wrapped,
+            generation,
+        }
+    }
+
+    /// Number of cells in the row.
+    pub(crate) fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    /// The cell at `col`.
+    pub(crate) fn cell(&self, col: usize, table: &StyleTable) -> Option<Cell> {
+        let character = *self.chars.get(col)?;
+        Some(table.get(self.styles[col]).cell(character))
+    }
+
+    /// Expand into cells.
+    pub(crate) fn cells(&self, table: &StyleTable) -> Vec<Cell> {
+        self.chars
+            .iter()
+            .zip(&self.styles)
+            .map(|(&character, &id)| table.get(id).cell(character))
+            .collect()
+    }
+
+    /// Set the cell at `col`. Returns whether it changed.
+    pub(crate) fn set(&mut self, col: usize, character: char, style: StyleId) -> bool {
+        let changed = self.chars[col] != character || self.styles[col] != style;
+        self.chars[col] = character;
+        self.styles[col] = style;
+        changed
+    }
+
+    /// Fill cells from `col` on with `character`, up to `n` cells.
+    pub(crate) fn fill(&mut self, col: usize, n: usize, character: char, style: StyleId) {
+        let end = (col + n).min(self.len());
+        self.chars[col..end].fill(character);
+        self.styles[col..end].fill(style);
+    }
+
+    /// Shift cells from `col` on right by `n`, blanking the gap (ICH).
+    pub(crate) fn insert_blanks(&mut self, col: usize, n: usize) {
+        let n = n.min(self.len() - col);
+        self.chars[col..].rotate_right(n);
+        self.styles[col..].rotate_right(n);
+        self.fill(col, n, ' ', DEFAULT_STYLE);
+    }
+
+    /// Remove `n` cells at `col`, shifting the rest left and blanking the end (DCH).
+    pub(crate) fn delete(&mut self, col: usize, n: usize) {
+        let len = self.len();
+        let n = n.min(len - col);
+        self.chars[col..].rotate_left(n);
+        self.styles[col..].rotate_left(n);
+        self.fill(len - n, n, ' ', DEFAULT_STYLE);
+    }
+
+    /// Truncate or pad with blanks to `cols` cells.
+    pub(crate) fn resize(&mut self, cols: usize) {
+        self.chars.resize(cols, ' ');
+        self.styles.resize(cols, DEFAULT_STYLE);
+    }
+
+    /// Blank the row in place, keeping its allocation.
+    pub(crate) fn clear(&mut self, generation: u64) {
+        self.chars.fill(' ');
+        self.styles.fill(DEFAULT_STYLE);
+        self.wrapped = false;
+        self.generation = generation;
+    }
+}
+
+/// A row stored in scrollback.
+///
+/// Holds one character per cell and a style per run of identically styled
+/// cells. A plain text row costs about one byte per character instead of a
+/// full `Cell`.
+#[derive(Debug, Clone)]
+pub(crate) struct PackedRow {
+    text: Box<str>,
+    /// (start column, style) for each run; cells before the first run are default
+    runs: Box<[(u16, Style)]>,
+    /// Width of the row when it was packed
+    width: u16,
+    /// Continues the previous row (soft wrap)
+    pub(crate) wrapped: bool,
+}
+
+impl PackedRow {
+    /// Pack a row of cells.
+    pub(crate) fn pack(cells: &[Cell], wrapped: bool) -> Self {
+        let len = cells
+            .iter()
+            .rposition(|cell| *cell != Cell::default())
+            .map_or(0, |idx| idx + 1);
+
+        let default = Style::of(&Cell::default());
+        let mut runs: Vec<(u16, Style)> = Vec::new();
+        for (col, cell) in cells[..len].iter().enumerate() {
+            let style = Style::of(cell);
+            let current = runs.last().map_or(default, |&(_, style)| style);
+            if style != current {
+                runs.push((col as u16, style));
+            }
+        }
+
+        Self {
+            text: cells[..len].iter().map(|cell| cell.character).collect(),
+            runs: runs.into_boxed_slice(),
+            width: cells.len() as u16,
+            wrapped,
+        }
+    }
+
+    /// Expand back into cells, padded to the packed width.
+    pub(crate) fn unpack(&self) -> Vec<Cell> {
+        let mut cells = Vec::with_capacity(self.width as usize);
+        let mut style = Style::of(&Cell::default());
+        let mut runs = self.runs.iter().peekable();
+
+        for (col, character) in self.text.chars().enumerate() {
+            while let Some(&&(start, next)) = runs.peek() {
+                if start as usize > col {
+                    break;
+                }
+                style = next;
+                runs.next();
+            }
+            cells.push(style.cell(character));
+        }
+        cells.resize(self.width as usize, Cell::default());
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_style_table_compacts_unused_styles() {
+        let mut table = StyleTable::default();
+        let mut row = Row::blank(4, 0);
+        let red = table.intern(Style::of(&Cell::with_fg('r', Color::Red)));
+        for index in 0..10 {
+            let style = Style::of(&Cell::with_fg('x', Color::Indexed(index)));
+            row.set(1, 'x', table.intern(style));
+        }
+        row.set(2, 'r', red);
+        assert_eq!(table.intern(Style::of(&Cell::default())), DEFAULT_STYLE);
+        assert_eq!(table.styles.len(), 12);
+
+        table.compact(std::iter::once(&mut row));
+        assert_eq!(table.styles.len(), 3);
+        assert_eq!(row.cell(0, &table), Some(Cell::default()));
+        assert_eq!(
+            row.cell(1, &table),
+            Some(Cell::with_fg('x', Color::Indexed(9)))
+        );
+        assert_eq!(row.cell(2, &table), Some(Cell::with_fg('r', Color::Red)));
+        assert_eq!(table.compact_at, MIN_COMPACT_AT);
+    }
+
+    #[test]
+    fn test_packed_row_round_trip() {
+        let mut cells = vec![Cell::default(); 10];
+        cells[0] = Cell::new('a');
+        cells[1] = Cell::with_fg('b', Color::Red);
+        cells[2] = Cell::with_fg('c', Color::Red);
+        cells[3].bg = Color::Rgb { r: 1, g: 2, b: 3 };
+        cells[4] = Cell::new('é');
+        cells[5].attrs = cells[5].attrs.with_bold();
+
+        let packed = PackedRow::pack(&cells, true);
+        assert!(packed.wrapped);
+        assert_eq!(packed.runs.len(), 4);
+        assert_eq!(packed.unpack(), cells);
+    }
+
+    #[test]
+    fn test_packed_row_drops_trailing_blanks() {
+        let mut cells = vec![Cell::default(); 80];
+        cells[0] = Cell::new('x');
+
+        let packed = PackedRow::pack(&cells, false);
+        assert_eq!(&*packed.text, "x");
+        assert!(packed.runs.is_empty());
+        assert_eq!(packed.unpack().len(), 80);
+    }
+}
@@ -0,0 +1,244 @@
+//! Linux namespace sandbox for PTY children.
+//!
+//! portable-pty offers no hook between fork and exec, so the child is started
+//! through util-linux `unshare` in new user, mount, PID, IPC and (optionally)
+//! network namespaces. A generated `sh` script then builds a fresh root on a
+//! tmpfs, bind-mounts the configured paths into it and pivots into it before
+//! running the command. Everything is unprivileged: the caller is mapped to
+//! root inside the user namespace only, and the command itself runs in a
+//! nested one as the caller's uid, without the capabilities to undo the mounts.
+
+use terminal_mcp_core::{Error, Result, SandboxSettings};
+
+/// Program used to create the namespaces.
+const UNSHARE: &str = "unshare";
+
+/// Device nodes bind-mounted into the sandbox's `/dev`.
+const DEVICES: &[&str] = &["null", "zero", "full", "random", "urandom", "tty"];
+
+/// `ro PATH` bind-mounts a host path read-only under `$root`.
+///
+/// `remount,ro` only applies to a single mount, so every submount is
+/// remounted too, keeping its flags: `nosuid` and friends are locked in a
+/// user namespace. `/proc/self/mountinfo` escapes blanks and backslashes.
+const RO_FUNCTION: &str = "ro() {\n\
+     \x20 [ -e \"$1\" ] || [ -L \"$1\" ] || return 0\n\
+     \x20 mkdir -p \"$root$(dirname \"$1\")\"\n\
+     \x20 if [ -L \"$1\" ]; then cp -P \"$1\" \"$root$1\"; return 0; fi\n\
+     \x20 if [ -d \"$1\" ]; then mkdir -p \"$root$1\"; else touch \"$root$1\"; fi\n\
+     \x20 mount --rbind \"$1\" \"$root$1\"\n\
+     \x20 target=\"$root$1\" awk '{\n\
+     \x20   m = $5; gsub(/\\\\040/, \" \", m); gsub(/\\\\011/, \"\\t\", m); gsub(/\\\\134/, \"\\\\\\\\\", m)\n\
+     \x20   p = ENVIRON[\"target\"]\n\
+     \x20   if (m == p || index(m, p \"/\") == 1) print $6 \" \" m\n\
+     \x20 }' /proc/self/mountinfo | while read -r flags mnt; do\n\
+     \x20   mount -o \"remount,bind,$flags,ro\" \"$mnt\"\n\
+     \x20 done\n\
+     }\n";
+
+/// Rewrite `command args` to run inside the sandbox.
+///
+/// Returns the program and arguments to spawn instead. `cwd` is the working
+/// directory inside the sandbox and defaults to the project directory.
+pub fn wrap_command(
+    command: &str,
+    args: &[String],
+    cwd: Option<&str>,
+    settings: &SandboxSettings,
+) -> Result<(String, Vec<String>)> {
+    if !cfg!(target_os = "linux") {
+        return Err(Error::PtyError(
+            "The namespace sandbox is only available on Linux".to_string(),
+        ));
+    }
+    settings.validate()?;
+
+    let mut wrapped: Vec<String> = [
+        "--user",
+        "--map-root-user",
+        "--mount",
+        "--pid",
+        "--fork",
+        "--kill-child",
+        "--ipc",
+    ]
+    .iter()
+    .map(|flag| flag.to_string())
+    .collect();
+    if !settings.network {
+        wrapped.push("--net".to_string());
+    }
+    wrapped.extend(["--".to_string(), "sh".to_string(), "-c".to_string()]);
+    wrapped.push(setup_script(cwd, settings));
+    // $0 for the script, then the command as "$@"
+    wrapped.push("sandbox".to_string());
+    wrapped.push(command.to_string());
+    wrapped.extend(args.iter().cloned());
+
+    Ok((UNSHARE.to_string(), wrapped))
+}
+
+/// Shell script that builds the sandbox root and runs `"$@"` in it.
+fn setup_script(cwd: Option<&str>, settings: &SandboxSettings) -> String {
+    let mut script = String::from(
+        "set -e\n\
+         root=/mnt\n\
+         [ -d \"$root\" ] || root=$(mktemp -d)\n\
+         mount -t tmpfs -o mode=755 sandbox \"$root\"\n",
+    );
+    script.push_str(RO_FUNCTION);
+
+    for path in &settings.read_only_paths {
+        script.push_str(&format!("ro {}\n", quote(path)));
+    }
+
+    // A minimal /dev: no host /dev/shm, /dev/mqueue or disks
+    script.push_str(&format!(
+        "mkdir -p \"$root/dev/pts\" \"$root/dev/shm\" \"$root/proc\" \"$root/tmp\" \"$root/.old\"\n\
+         for dev in {}; do\n\
+         \x20 touch \"$root/dev/$dev\"\n\
+         \x20 mount --bind \"/dev/$dev\" \"$root/dev/$dev\"\n\
+         done\n\
+         mount -t devpts -o newinstance,ptmxmode=0666,mode=620 devpts \"$root/dev/pts\"\n\
+         ln -s pts/ptmx \"$root/dev/ptmx\"\n\
+         mount -t tmpfs -o mode=1777 shm \"$root/dev/shm\"\n\
+         ln -s /proc/self/fd \"$root/dev/fd\"\n\
+         ln -s /proc/self/fd/0 \"$root/dev/stdin\"\n\
+         ln -s /proc/self/fd/1 \"$root/dev/stdout\"\n\
+         ln -s /proc/self/fd/2 \"$root/dev/stderr\"\n\
+         mount -t proc proc \"$root/proc\"\n\
+         mount -t tmpfs -o mode=1777 tmp \"$root/tmp\"\n",
+        DEVICES.join(" ")
+    ));
+    // After /tmp so a project under /tmp isn't hidden by it
+    if let Some(project) = &settings.project_dir {
+        let project = quote(project);
+        script.push_str(&format!(
+            "mkdir -p \"$root\"{project}\nmount --rbind {project} \"$root\"{project}\n"
+        ));
+    }
+    if !settings.network {
+        script.push_str("ip link set lo up 2>/dev/null || true\n");
+    }
+    script.push_str(
+        "cd \"$root\"\n\
+         pivot_root . .old\n\
+         umount -l /.old\n\
+         rmdir /.old\n",
+    );
+
+    let home = settings.project_dir.as_deref().unwrap_or("/tmp");
+    let workdir = cwd.or(settings.project_dir.as_deref()).unwrap_or("/");
+    script.push_str(&format!(
+        "export HOME={}\ncd {} 2>/dev/null || cd /\n",
+        quote(home),
+        quote(workdir)
+    ));
+
+    // Stay as PID 1 to reap orphans; the command still gets Ctrl+C. It runs
+    // as the caller's own uid in a nested user namespace, which has no
+    // capabilities over the mounts above, so it can't remount them writable
+    script.push_str(
+        "uid=$(awk '{ print $2 }' /proc/self/uid_map)\n\
+         gid=$(awk '{ print $2 }' /proc/self/gid_map)\n\
+         set +e\n\
+         unshare --user --map-user=\"$uid\" --map-group=\"$gid\" -- \"$@\"\n",
+    );
+    script
+}
+
+/// Whether unprivileged user namespaces can be created here; some
+/// distributions and CI runners disable them.
+pub fn is_available() -> bool {
+    cfg!(target_os = "linux")
+        && std::process::Command::new(UNSHARE)
+            .args(["--user", "--map-root-user", "--mount", "true"])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+}
+
+/// Quote a string for `sh`.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("/home/me"), "'/home/me'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_ro_covers_submounts() {
+        if !is_available() {
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("sandbox ro-{}", std::process::id()));
+        let dir = dir.to_string_lossy().into_owned();
+        let script = format!(
+            "set -e\n{RO_FUNCTION}\
+             mkdir -p \"$1/src/sub\" \"$1/root\"\n\
+             mount -t tmpfs sub \"$1/src/sub\"\n\
+             root=\"$1/root\"\n\
+             mount -t tmpfs root \"$root\"\n\
+             ro \"$1/src\"\n\
+             touch \"$root$1/src/x\" 2>/dev/null || echo top-ro\n\
+             touch \"$root$1/src/sub/x\" 2>/dev/null || echo sub-ro\n"
+        );
+        let output = std::process::Command::new(UNSHARE)
+            .args([
+                "--user",
+                "--map-root-user",
+                "--mount",
+                "sh",
+                "-c",
+                &script,
+                "sh",
+            ])
+            .arg(&dir)
+            .output()
+            .unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(stdout, "top-ro\nsub-ro\n", "{output:?}");
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_wrap_command() {
+        let settings = SandboxSettings {
+            read_only_paths: vec!["/usr".to_string()],
+            project_dir: Some("/work/my project".to_string()),
+            network: false,
+        };
+        let (program, args) = wrap_command("ls", &["-la".to_string()], None, &settings).unwrap();
+        assert_eq!(program, "unshare");
+        assert!(args.contains(&"--net".to_string()));
+        assert_eq!(&args[args.len() - 3..], ["sandbox", "ls", "-la"]);
+
+        let script = &args[args.len() - 4];
+        assert!(script.contains("ro '/usr'\n"));
+        assert!(script.contains("mount --rbind '/work/my project' \"$root\"'/work/my project'"));
+        assert!(script.contains("cd '/work/my project' 2>/dev/null"));
+
+        let settings = SandboxSettings {
+            network: true,
+            ..settings
+        };
+        let (_, args) = wrap_command("ls", &[], Some("/usr"), &settings).unwrap();
+        assert!(!args.contains(&"--net".to_string()));
+        assert!(args[args.len() - 3].contains("cd '/usr' 2>/dev/null"));
+
+        let relative = SandboxSettings {
+            project_dir: Some("work".to_string()),
+            ..SandboxSettings::default()
+        };
+        assert!(wrap_command("ls", &[], None, &relative).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! Screen change notifications.
+
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use tokio::sync::broadcast;
+
+use terminal_mcp_core::SessionId;
+use terminal_mcp_emulator::Grid;
+
+use crate::session::Session;
+
+/// Number of change events buffered per session before slow receivers lag.
+pub(crate) const CHANGE_CHANNEL_CAPACITY: usize = 64;
+
+/// A change to a session's screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenChange {
+    /// Session whose screen changed
+    pub session_id: SessionId,
+    /// Grid generation after the change
+    pub generation: u64,
+    /// Generation of the previous event
+    pub previous_generation: u64,
+    /// Rows whose content changed; empty if only the cursor moved
+    pub rows: Vec<u16>,
+    /// When the screen last changed
+    pub at: Instant,
+}
+
+impl Session {
+    /// Subscribe to screen change events.
+    ///
+    /// Events are published as output is processed, so they only arrive while
+    /// something drives the session (snapshots, waits, reads).
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<ScreenChange> {
+        self.changes.subscribe()
+    }
+
+    /// Current screen generation, a cheap "has the screen changed?" check.
+    pub fn screen_generation(&self) -> u64 {
+        self.parser().lock().unwrap().grid().generation()
+    }
+
+    /// Publish an event if the grid changed since the last one.
+    pub(crate) fn publish_changes(&self, grid: &Grid) {
+        let previous = self.published_generation.load(Ordering::Relaxed);
+        if !grid.changed_since(previous) {
+            return;
+        }
+        self.published_generation
+            .store(grid.generation(), Ordering::Relaxed);
+
+        let change = ScreenChange {
+            session_id: *self.id(),
+            generation: grid.generation(),
+            previous_generation: previous,
+            rows: grid.damaged_rows(previous),
+            at: grid.last_changed(),
+        };
+        // Nobody listening is fine
+        let _ = self.changes.send(change);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use terminal_mcp_core::Dimensions;
+
+    #[test]
+    fn test_screen_change_events() {
+        let session = Session::create(
+            if cfg!(windows) { "cmd.exe" } else { "cat" }.to_string(),
+            vec![],
+            Dimensions::new(24, 80),
+        )
+        .unwrap();
+        let mut changes = session.subscribe_changes();
+        let generation = session.screen_generation();
+
+        let parser_arc = session.parser();
+        let mut parser = parser_arc.lock().unwrap();
+        parser.process(b"\x1b[3;1Hhello");
+        session.publish_changes(parser.grid());
+        // Nothing new: no second event
+        session.publish_changes(parser.grid());
+        drop(parser);
+
+        let change = changes.try_recv().unwrap();
+        assert_eq!(change.session_id, *session.id());
+        assert_eq!(change.previous_generation, generation);
+        assert_eq!(change.rows, vec![2]);
+        assert!(session.screen_generation() > generation);
+        assert!(changes.try_recv().is_err());
+
+        session.terminate().unwrap();
+    }
+}
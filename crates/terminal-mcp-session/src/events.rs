@@ -0,0 +1,74 @@
+//! Bell and notification events.
+
+use tokio::sync::broadcast;
+
+use terminal_mcp_core::{TerminalEvent, TerminalEvents};
+use terminal_mcp_emulator::Parser;
+
+use crate::session::Session;
+
+/// Number of terminal events buffered per session before slow receivers lag.
+pub(crate) const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+impl Session {
+    /// Subscribe to bells and desktop notifications.
+    ///
+    /// Like screen changes, events are published while output is processed.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<TerminalEvent> {
+        self.events.subscribe()
+    }
+
+    /// Bells and recent notifications received so far.
+    pub fn terminal_events(&self) -> TerminalEvents {
+        self.parser().lock().unwrap().events().clone()
+    }
+
+    /// Publish events raised by the parser since the last call.
+    pub(crate) fn publish_events(&self, parser: &mut Parser) {
+        for event in parser.take_events() {
+            // Nobody listening is fine
+            let _ = self.events.send(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use terminal_mcp_core::Dimensions;
+
+    use super::*;
+
+    #[test]
+    fn test_terminal_events_published() {
+        let session = Session::create(
+            if cfg!(windows) { "cmd.exe" } else { "cat" }.to_string(),
+            vec![],
+            Dimensions::new(24, 80),
+        )
+        .unwrap();
+        let mut events = session.subscribe_events();
+
+        let parser_arc = session.parser();
+        let mut parser = parser_arc.lock().unwrap();
+        parser.process(b"\x07\x1b]9;done\x07");
+        session.publish_events(&mut parser);
+        session.publish_events(&mut parser);
+        drop(parser);
+
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            TerminalEvent::Bell { .. }
+        ));
+        match events.try_recv().unwrap() {
+            TerminalEvent::Notification(notification) => assert_eq!(notification.body, "done"),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert!(events.try_recv().is_err());
+
+        let totals = session.terminal_events();
+        assert_eq!(totals.bell_count, 1);
+        assert_eq!(totals.notification_count, 1);
+
+        session.terminate().unwrap();
+    }
+}
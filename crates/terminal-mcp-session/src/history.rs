@@ -0,0 +1,363 @@
+//! Screen history.
+//!
+//! Snapshots only show the screen at the moment they are taken, so an error
+//! toast or progress line drawn and erased in between is never seen. The
+//! history keeps the recent distinct screens as plain text frames, recorded
+//! whenever processed output changes the visible text.
+//!
+//! Spinners, progress bars and fast scrolling redraw the same rows over and
+//! over. Once a frame only redraws the rows its predecessor changed, further
+//! redraws of those rows less than [`FOLD_INTERVAL`] apart update that frame
+//! instead of adding new ones, so they can't evict the rest of the history.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use terminal_mcp_core::Position;
+use terminal_mcp_emulator::Grid;
+
+use crate::session::Session;
+
+/// Number of frames kept per session.
+pub const HISTORY_CAPACITY: usize = 100;
+
+/// Redraws of the same rows closer together than this share a frame.
+pub const FOLD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The screen as it was at one point in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenFrame {
+    /// Grid generation when the frame was taken
+    pub generation: u64,
+    /// When the frame was taken (RFC 3339)
+    pub timestamp: String,
+    /// Screen text, one line per row
+    pub text: String,
+    /// Cursor position
+    pub cursor: Position,
+}
+
+/// Bounded history of screen frames, oldest first.
+#[derive(Debug, Clone)]
+pub struct ScreenHistory {
+    frames: VecDeque<ScreenFrame>,
+    capacity: usize,
+    /// Grid generation last compared against the newest frame
+    checked_generation: Option<u64>,
+    /// Frames recorded over the session's life, including evicted ones
+    recorded: u64,
+    /// Rows the newest frame changed, as line indexes
+    changed_rows: Vec<usize>,
+    /// Whether the newest frame only redrew the rows its predecessor changed
+    redraw: bool,
+    /// When the newest frame was recorded or last updated
+    updated_at: Option<Instant>,
+}
+
+impl Default for ScreenHistory {
+    fn default() -> Self {
+        Self::new(HISTORY_CAPACITY)
+    }
+}
+
+impl ScreenHistory {
+    /// Create an empty history holding at most `capacity` frames.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity.min(HISTORY_CAPACITY)),
+            capacity: capacity.max(1),
+            checked_generation: None,
+            recorded: 0,
+            changed_rows: Vec::new(),
+            redraw: false,
+            updated_at: None,
+        }
+    }
+
+    /// Record the grid if its text differs from the last frame.
+    ///
+    /// Returns whether a frame was added or the newest frame updated.
+    pub fn record(&mut self, grid: &Grid) -> bool {
+        if let Some(checked) = self.checked_generation {
+            if !grid.changed_since(checked) {
+                return false;
+            }
+        }
+        let generation = grid.generation();
+        self.checked_generation = Some(generation);
+
+        // Only attributes or the cursor changed
+        let text = grid.to_plain_text();
+        if self.frames.back().is_some_and(|last| last.text == text) {
+            return false;
+        }
+
+        let changed_rows = self
+            .frames
+            .back()
+            .map(|last| changed_rows(&last.text, &text))
+            .unwrap_or_default();
+        let redraw = !changed_rows.is_empty()
+            && changed_rows
+                .iter()
+                .all(|row| self.changed_rows.contains(row));
+        let frame = ScreenFrame {
+            generation,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            text,
+            cursor: grid.cursor().position,
+        };
+        let fold = redraw
+            && self.redraw
+            && self
+                .updated_at
+                .is_some_and(|updated| updated.elapsed() < FOLD_INTERVAL);
+        self.updated_at = Some(Instant::now());
+        if fold {
+            if let Some(last) = self.frames.back_mut() {
+                *last = frame;
+                return true;
+            }
+        }
+
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+        self.changed_rows = changed_rows;
+        self.redraw = redraw;
+        self.recorded += 1;
+        true
+    }
+
+    /// All frames, oldest first.
+    pub fn frames(&self) -> impl Iterator<Item = &ScreenFrame> {
+        self.frames.iter()
+    }
+
+    /// Frames taken after `generation`, oldest first.
+    pub fn since(&self, generation: u64) -> impl Iterator<Item = &ScreenFrame> {
+        self.frames
+            .iter()
+            .filter(move |f| f.generation > generation)
+    }
+
+    /// The frame with the given generation.
+    pub fn get(&self, generation: u64) -> Option<&ScreenFrame> {
+        self.frames.iter().find(|f| f.generation == generation)
+    }
+
+    /// Number of frames held.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether no frame has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Frames recorded over the session's life, including evicted ones.
+    pub fn recorded(&self) -> u64 {
+        self.recorded
+    }
+
+    /// Line diff between two frames, or `None` if either is gone.
+    pub fn diff(&self, from: u64, to: u64) -> Option<String> {
+        Some(diff_lines(&self.get(from)?.text, &self.get(to)?.text))
+    }
+}
+
+impl Session {
+    /// Screen history of this session.
+    pub fn history(&self) -> std::sync::Arc<std::sync::Mutex<ScreenHistory>> {
+        self.history.clone()
+    }
+
+    /// Add a history frame if the screen text changed.
+    pub(crate) fn record_frame(&self, grid: &Grid) {
+        self.history.lock().unwrap().record(grid);
+    }
+}
+
+/// Indexes of the lines that differ between two texts.
+fn changed_rows(old: &str, new: &str) -> Vec<usize> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    (0..old.len().max(new.len()))
+        .filter(|&row| old.get(row) != new.get(row))
+        .collect()
+}
+
+/// Unified diff of two texts without context lines.
+///
+/// Hunks are headed `@@ -start,count +start,count @@` with 1-based line
+/// numbers. Frame lines are screen rows, except that soft-wrapped rows are
+/// joined into one line.
+pub fn diff_lines(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence lengths of the suffixes
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        // Collect one hunk of removals and additions
+        let (old_start, new_start) = (i, j);
+        while i < old.len() || j < new.len() {
+            if i < old.len() && j < new.len() && old[i] == new[j] {
+                break;
+            }
+            if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            i - old_start,
+            new_start + 1,
+            j - new_start
+        ));
+        for line in &old[old_start..i] {
+            out.push_str(&format!("-{line}\n"));
+        }
+        for line in &new[new_start..j] {
+            out.push_str(&format!("+{line}\n"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use terminal_mcp_core::Dimensions;
+    use terminal_mcp_emulator::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_history_records_distinct_screens() {
+        let mut parser = Parser::new(Grid::new(Dimensions::new(3, 20)));
+        let mut history = ScreenHistory::new(10);
+
+        parser.process(b"building...");
+        assert!(history.record(parser.grid()));
+        // Nothing changed
+        assert!(!history.record(parser.grid()));
+        // Same text, new color: no new frame
+        parser.process(b"\r\x1b[31mbuilding...\x1b[0m");
+        assert!(!history.record(parser.grid()));
+        assert_eq!(history.len(), 1);
+
+        parser.process(b"\r\nerror: disk full");
+        assert!(history.record(parser.grid()));
+        parser.process(b"\x1b[2K\r");
+        assert!(history.record(parser.grid()));
+
+        // The toast is gone from the screen but not from the history
+        assert!(!parser.grid().to_plain_text().contains("disk full"));
+        let texts: Vec<_> = history.frames().map(|f| f.text.clone()).collect();
+        assert!(texts[1].contains("error: disk full"));
+        assert_eq!(history.len(), 3);
+
+        let first = history.frames().next().unwrap().generation;
+        assert_eq!(history.since(first).count(), 2);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut parser = Parser::new(Grid::new(Dimensions::new(3, 20)));
+        let mut history = ScreenHistory::new(3);
+        // A different row each time, so nothing is folded
+        for i in 0..5 {
+            parser.process(format!("\x1b[{};1H{i}", i % 3 + 1).as_bytes());
+            history.record(parser.grid());
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.recorded(), 5);
+        let oldest = &history.frames().next().unwrap().text;
+        assert!(oldest.contains('2') && !oldest.contains('3'), "{oldest:?}");
+    }
+
+    #[test]
+    fn test_history_folds_redraws() {
+        let mut parser = Parser::new(Grid::new(Dimensions::new(3, 20)));
+        let mut history = ScreenHistory::new(10);
+        parser.process(b"$ make\r\n");
+        history.record(parser.grid());
+
+        // A spinner redrawing one row takes two frames, not one per tick
+        for tick in "|/-\\".chars().cycle().take(40) {
+            parser.process(format!("\rbuilding {tick}").as_bytes());
+            history.record(parser.grid());
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.recorded(), 3);
+        let newest = history.frames().last().unwrap().text.clone();
+        assert!(newest.contains("building \\"), "{newest:?}");
+
+        // Output on another row starts a new frame
+        parser.process(b"\r\ndone");
+        assert!(history.record(parser.grid()));
+        assert_eq!(history.len(), 4);
+
+        // A screen that stayed up is not overwritten by the next redraw
+        parser.process(b"\rdone.");
+        history.record(parser.grid());
+        parser.process(b"\rdone..");
+        history.record(parser.grid());
+        std::thread::sleep(FOLD_INTERVAL);
+        parser.process(b"\rdone...");
+        history.record(parser.grid());
+        let texts: Vec<_> = history.frames().map(|f| f.text.clone()).collect();
+        assert_eq!(texts.len(), 6);
+        assert!(texts[4].contains("done..") && !texts[4].contains("done..."));
+        assert!(texts[5].contains("done..."));
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(diff_lines("a\nb\nc", "a\nb\nc"), "");
+        assert_eq!(
+            diff_lines("a\nb\nc\nd", "a\nB\nc\nd\ne"),
+            "@@ -2,1 +2,1 @@\n-b\n+B\n@@ -5,0 +5,1 @@\n+e\n"
+        );
+        assert_eq!(diff_lines("a\nb", "b"), "@@ -1,1 +1,0 @@\n-a\n");
+    }
+
+    #[test]
+    fn test_history_diff() {
+        let mut parser = Parser::new(Grid::new(Dimensions::new(2, 10)));
+        let mut history = ScreenHistory::default();
+        parser.process(b"one");
+        history.record(parser.grid());
+        let from = parser.grid().generation();
+        parser.process(b"\r\ntwo");
+        history.record(parser.grid());
+        let to = parser.grid().generation();
+
+        assert_eq!(history.diff(from, to).unwrap(), "@@ -2,0 +2,1 @@\n+two\n");
+        assert!(history.diff(from, to + 1).is_none());
+    }
+}
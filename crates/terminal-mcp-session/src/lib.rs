@@ -16,19 +16,27 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+pub mod change;
+pub mod events;
+pub mod history;
+pub mod limits;
 pub mod manager;
 pub mod navigation;
 pub mod output;
+pub mod paste;
 pub mod session;
 pub mod snapshot;
 pub mod visual;
 pub mod wait;
 
 // Re-export commonly used types
+pub use change::ScreenChange;
+pub use history::{ScreenFrame, ScreenHistory};
 pub use manager::{SessionInfo, SessionManager, SessionManagerConfig};
 pub use navigation::NavigationCalculator;
 pub use output::{OutputBuffer, OutputRead};
+pub use paste::PasteMethod;
 pub use session::{Session, SessionStatus};
-pub use snapshot::SnapshotConfig;
+pub use snapshot::{InputPrompt, SnapshotConfig};
 pub use visual::{SessionMode, VisualTerminal, VisualTerminalHandle};
 pub use wait::{WaitCondition, WaitResult};
@@ -0,0 +1,355 @@
+//! Resource limit tracking.
+//!
+//! CPU time, address space, open files and process count are enforced by
+//! the kernel on the child (see `terminal_mcp_emulator::limits`). The output
+//! rate is enforced here: once a session has produced its allowance for the
+//! current second, its PTY is left unread, so the child blocks on write
+//! instead of flooding the server. The session reports
+//! [`LimitKind::OutputRate`] while it is throttled and goes back to running
+//! once a full window stays under the limit.
+
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+use terminal_mcp_core::{LimitKind, ResourceLimits};
+
+use crate::session::{Session, SessionStatus};
+
+/// Signal number of SIGXCPU, sent at the soft CPU time limit.
+#[cfg(any(target_arch = "mips", target_arch = "mips64"))]
+const SIGXCPU: u32 = 30;
+#[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+const SIGXCPU: u32 = 24;
+
+/// Window over which `output_bytes_per_sec` is counted.
+const OUTPUT_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Output bytes read in the current window.
+#[derive(Debug)]
+pub(crate) struct OutputRate {
+    limit: Option<u64>,
+    window_start: Instant,
+    bytes: u64,
+    /// Whether the last full window reached the limit
+    last_window_full: bool,
+}
+
+impl OutputRate {
+    pub(crate) fn new(limit: Option<u64>) -> Self {
+        Self {
+            limit,
+            window_start: Instant::now(),
+            bytes: 0,
+            last_window_full: false,
+        }
+    }
+
+    /// Whether more output may be read now.
+    pub(crate) fn allows(&mut self) -> bool {
+        self.roll();
+        !self.is_full()
+    }
+
+    /// Whether the current or the last full window reached the limit.
+    pub(crate) fn exceeded(&mut self) -> bool {
+        self.roll();
+        self.is_full() || self.last_window_full
+    }
+
+    fn is_full(&self) -> bool {
+        self.limit.is_some_and(|limit| self.bytes >= limit)
+    }
+
+    fn roll(&mut self) {
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= OUTPUT_RATE_WINDOW {
+            // Nothing was read for a whole window in between
+            self.last_window_full = elapsed < 2 * OUTPUT_RATE_WINDOW && self.is_full();
+            self.window_start = Instant::now();
+            self.bytes = 0;
+        }
+    }
+
+    /// Count bytes read in the current window.
+    pub(crate) fn add(&mut self, bytes: usize) {
+        self.bytes = self.bytes.saturating_add(bytes as u64);
+    }
+}
+
+impl Session {
+    /// Resource limits the session was created with.
+    pub fn limits(&self) -> &ResourceLimits {
+        &self.limits
+    }
+
+    /// Check whether the session ran into one of its limits.
+    ///
+    /// Looks at the session cgroup's event counters and at how the command
+    /// ended (SIGXCPU, or exit code 128 + SIGXCPU when a shell such as the
+    /// sandbox's reports it), and switches the status to
+    /// [`SessionStatus::LimitExceeded`] on the first hit. A throttled output
+    /// rate doesn't hide a later kill.
+    pub fn check_limits(&self) -> Option<LimitKind> {
+        match self.status() {
+            SessionStatus::Running | SessionStatus::LimitExceeded(LimitKind::OutputRate) => {}
+            SessionStatus::LimitExceeded(kind) => return Some(kind),
+            _ => return None,
+        }
+
+        let hit = self
+            .cgroup
+            .as_ref()
+            .and_then(|cgroup| cgroup.limit_hit())
+            .or_else(|| {
+                self.limits.cpu_seconds?;
+                let pty = self.pty();
+                let pty = pty.lock().unwrap();
+                // "CPU time limit exceeded"
+                let signalled = pty
+                    .exit_signal()
+                    .is_some_and(|signal| signal.to_lowercase().contains("cpu"));
+                let reported = pty.exit_code() == Some(128 + SIGXCPU);
+                (signalled || reported).then_some(LimitKind::CpuTime)
+            });
+        match hit {
+            Some(kind) => {
+                self.set_status(SessionStatus::LimitExceeded(kind));
+                Some(kind)
+            }
+            None => match self.status() {
+                SessionStatus::LimitExceeded(kind) => Some(kind),
+                _ => None,
+            },
+        }
+    }
+
+    /// Flag the session while its output is throttled and clear the flag
+    /// once a full window stays under the limit.
+    pub(crate) fn track_output_rate(&self, exceeded: bool) {
+        let mut status = self.status.lock().unwrap();
+        let new_status = match (*status, exceeded) {
+            (SessionStatus::Running, true) => {
+                warn!("Session {} exceeded its output rate", self.id());
+                SessionStatus::LimitExceeded(LimitKind::OutputRate)
+            }
+            (SessionStatus::LimitExceeded(LimitKind::OutputRate), false) => SessionStatus::Running,
+            _ => return,
+        };
+        info!(
+            "Session status changed: id={}, {:?} → {:?}",
+            self.id(),
+            *status,
+            new_status
+        );
+        *status = new_status;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_rate() {
+        let mut unlimited = OutputRate::new(None);
+        unlimited.add(usize::MAX);
+        assert!(unlimited.allows());
+
+        let mut rate = OutputRate::new(Some(100));
+        assert!(rate.allows());
+        rate.add(60);
+        assert!(rate.allows());
+        rate.add(60);
+        assert!(!rate.allows());
+
+        assert!(rate.exceeded());
+
+        // A new window starts over, but the last one was still over the limit
+        rate.window_start -= OUTPUT_RATE_WINDOW;
+        assert!(rate.allows());
+        assert!(rate.exceeded());
+        rate.add(10);
+
+        // A full window under the limit clears it
+        rate.window_start -= OUTPUT_RATE_WINDOW;
+        assert!(!rate.exceeded());
+
+        // So does a gap longer than a window
+        rate.add(100);
+        rate.window_start -= 3 * OUTPUT_RATE_WINDOW;
+        assert!(!rate.exceeded());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_output_rate_limit_throttles_session() {
+        use terminal_mcp_core::Dimensions;
+
+        let limits = ResourceLimits {
+            output_bytes_per_sec: Some(1000),
+            ..Default::default()
+        };
+        let session = Session::create_confined(
+            "yes".to_string(),
+            vec![],
+            Dimensions::new(24, 80),
+            None,
+            None,
+            &limits,
+        )
+        .unwrap();
+
+        let mut total = 0;
+        for _ in 0..50 {
+            total += session.process_output().unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        // One read can overshoot the allowance by at most a buffer
+        assert!(total > 0 && total < 1000 + 4096, "read {total} bytes");
+        assert_eq!(
+            session.check_limits(),
+            Some(LimitKind::OutputRate),
+            "{:?}",
+            session.status()
+        );
+        assert!(session.is_alive());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_reader_drains_throttled_output_after_exit() {
+        use std::sync::Arc;
+        use terminal_mcp_core::Dimensions;
+
+        let limits = ResourceLimits {
+            output_bytes_per_sec: Some(1000),
+            ..Default::default()
+        };
+        let session = Arc::new(
+            Session::create_confined(
+                "sh".to_string(),
+                vec![
+                    "-c".to_string(),
+                    "head -c 3000 /dev/zero | tr '\\0' x; echo; echo END".to_string(),
+                ],
+                Dimensions::new(24, 80),
+                None,
+                None,
+                &limits,
+            )
+            .unwrap(),
+        );
+        let reader = session.spawn_reader().unwrap();
+
+        // The command is done long before the throttled reader
+        reader.join().unwrap();
+        assert!(!session.is_alive());
+        let output = session.output_buffer().lock().unwrap().read_text(false);
+        assert_eq!(output.matches('x').count(), 3000);
+        assert!(output.contains("END"), "{output:?}");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_kill_reported_after_output_throttling() {
+        use terminal_mcp_core::Dimensions;
+
+        let limits = ResourceLimits {
+            cpu_seconds: Some(1),
+            output_bytes_per_sec: Some(1000),
+            ..Default::default()
+        };
+        let session = Session::create_confined(
+            "sh".to_string(),
+            vec![
+                "-c".to_string(),
+                "head -c 2000 /dev/zero | tr '\\0' x; while :; do :; done".to_string(),
+            ],
+            Dimensions::new(24, 80),
+            None,
+            None,
+            &limits,
+        )
+        .unwrap();
+
+        let mut throttled = false;
+        for _ in 0..200 {
+            session.process_output().unwrap();
+            throttled |= session.check_limits() == Some(LimitKind::OutputRate);
+            if !session.is_alive() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(throttled);
+        assert_eq!(session.check_limits(), Some(LimitKind::CpuTime));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_cpu_limit_in_sandbox() {
+        use std::sync::Arc;
+        use terminal_mcp_core::{Dimensions, SandboxSettings};
+
+        if !terminal_mcp_emulator::sandbox::is_available() {
+            return;
+        }
+        let limits = ResourceLimits {
+            cpu_seconds: Some(1),
+            ..Default::default()
+        };
+        let session = Arc::new(
+            Session::create_confined(
+                "sh".to_string(),
+                vec!["-c".to_string(), "while :; do :; done".to_string()],
+                Dimensions::new(24, 80),
+                None,
+                Some(&SandboxSettings::default()),
+                &limits,
+            )
+            .unwrap(),
+        );
+        let reader = session.spawn_reader().unwrap();
+
+        // The reader notices the kill without anyone asking
+        reader.join().unwrap();
+        assert_eq!(
+            session.status(),
+            SessionStatus::LimitExceeded(LimitKind::CpuTime)
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_cpu_limit_sets_status() {
+        use terminal_mcp_core::Dimensions;
+
+        let limits = ResourceLimits {
+            cpu_seconds: Some(1),
+            ..Default::default()
+        };
+        let session = Session::create_confined(
+            "sh".to_string(),
+            vec!["-c".to_string(), "while :; do :; done".to_string()],
+            Dimensions::new(24, 80),
+            None,
+            None,
+            &limits,
+        )
+        .unwrap();
+        assert_eq!(session.check_limits(), None);
+
+        for _ in 0..100 {
+            if !session.is_alive() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(session.check_limits(), Some(LimitKind::CpuTime));
+        assert_eq!(
+            session.status(),
+            SessionStatus::LimitExceeded(LimitKind::CpuTime)
+        );
+    }
+}
@@ -94,11 +94,14 @@ impl SessionManager {
         let sessions = self.sessions.read().unwrap();
         sessions
             .values()
-            .map(|session| SessionInfo {
-                session_id: *session.id(),
-                command: session.command().to_string(),
-                status: session.status(),
-                created_at: session.created_at(),
+            .map(|session| {
+                session.check_limits();
+                SessionInfo {
+                    session_id: *session.id(),
+                    command: session.command().to_string(),
+                    status: session.status(),
+                    created_at: session.created_at(),
+                }
             })
             .collect()
     }
@@ -32,13 +32,15 @@ impl NavigationCalculator {
     /// # Example
     /// ```
     /// # use terminal_mcp_session::NavigationCalculator;
-    /// # use terminal_mcp_core::{TerminalStateTree, Element, MenuItem, Bounds, Dimensions, Position};
+    /// # use terminal_mcp_core::{TerminalStateTree, Element, MenuItem, Bounds, CursorStyle, Dimensions, Position};
     /// let calc = NavigationCalculator::new();
     /// // Create a simple TST with a menu
     /// let tst = TerminalStateTree {
     ///     session_id: "test".to_string(),
     ///     dimensions: Dimensions::new(24, 80),
     ///     cursor: Position::new(0, 0),
+    ///     cursor_visible: true,
+    ///     cursor_style: CursorStyle::Block,
     ///     timestamp: "2025-11-30T00:00:00Z".to_string(),
     ///     elements: vec![
     ///         Element::Menu {
@@ -53,6 +55,11 @@ impl NavigationCalculator {
     ///     ],
     ///     raw_text: "".to_string(),
     ///     ansi_buffer: None,
+    ///     html: None,
+    ///     styles: None,
+    ///     events: Default::default(),
+    ///     termios: None,
+    ///     input_mode: None,
     /// };
     /// let keys = calc.calculate(&tst, "item_1").unwrap();
     /// // Should navigate down once and press Enter
@@ -162,13 +169,15 @@ impl NavigationCalculator {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use terminal_mcp_core::{Bounds, Dimensions, Position};
+    use terminal_mcp_core::{Bounds, CursorStyle, Dimensions, Position};
 
     fn create_test_snapshot() -> TerminalStateTree {
         TerminalStateTree {
             session_id: "test_session".to_string(),
             dimensions: Dimensions::new(24, 80),
             cursor: Position::new(0, 0),
+            cursor_visible: true,
+            cursor_style: CursorStyle::Block,
             timestamp: "2025-11-30T00:00:00Z".to_string(),
             elements: vec![Element::Menu {
                 ref_id: "menu_0".to_string(),
@@ -199,6 +208,11 @@ mod tests {
             }],
             raw_text: "".to_string(),
             ansi_buffer: None,
+            html: None,
+            styles: None,
+            events: Default::default(),
+            termios: None,
+            input_mode: None,
         }
     }
 
@@ -271,6 +285,8 @@ mod tests {
             session_id: "test".to_string(),
             dimensions: Dimensions::new(24, 80),
             cursor: Position::new(0, 0),
+            cursor_visible: true,
+            cursor_style: CursorStyle::Block,
             timestamp: "2025-11-30T00:00:00Z".to_string(),
             elements: vec![Element::Button {
                 ref_id: "button_0".to_string(),
@@ -279,6 +295,11 @@ mod tests {
             }],
             raw_text: "".to_string(),
             ansi_buffer: None,
+            html: None,
+            styles: None,
+            events: Default::default(),
+            termios: None,
+            input_mode: None,
         };
 
         let keys = calc.calculate(&snapshot, "button_0").unwrap();
@@ -295,6 +316,8 @@ mod tests {
             session_id: "test".to_string(),
             dimensions: Dimensions::new(24, 80),
             cursor: Position::new(0, 0),
+            cursor_visible: true,
+            cursor_style: CursorStyle::Block,
             timestamp: "2025-11-30T00:00:00Z".to_string(),
             elements: vec![Element::Checkbox {
                 ref_id: "checkbox_0".to_string(),
@@ -304,6 +327,11 @@ mod tests {
             }],
             raw_text: "".to_string(),
             ansi_buffer: None,
+            html: None,
+            styles: None,
+            events: Default::default(),
+            termios: None,
+            input_mode: None,
         };
 
         let keys = calc.calculate(&snapshot, "checkbox_0").unwrap();
@@ -320,6 +348,8 @@ mod tests {
             session_id: "test".to_string(),
             dimensions: Dimensions::new(24, 80),
             cursor: Position::new(0, 0),
+            cursor_visible: true,
+            cursor_style: CursorStyle::Block,
             timestamp: "2025-11-30T00:00:00Z".to_string(),
             elements: vec![Element::ProgressBar {
                 ref_id: "progress_0".to_string(),
@@ -328,6 +358,11 @@ mod tests {
             }],
             raw_text: "".to_string(),
             ansi_buffer: None,
+            html: None,
+            styles: None,
+            events: Default::default(),
+            termios: None,
+            input_mode: None,
         };
 
         let result = calc.calculate(&snapshot, "progress_0");
@@ -0,0 +1,174 @@
+//! Pasting text into the terminal.
+
+use std::time::Duration;
+
+use tracing::debug;
+
+use terminal_mcp_core::Result;
+
+use crate::session::Session;
+
+/// Start of a bracketed paste.
+const PASTE_START: &str = "\x1b[200~";
+
+/// End of a bracketed paste.
+const PASTE_END: &str = "\x1b[201~";
+
+/// Maximum bytes written at once when falling back to typing.
+const PASTE_CHUNK_BYTES: usize = 256;
+
+/// Delay between chunks when falling back to typing.
+const PASTE_CHUNK_DELAY: Duration = Duration::from_millis(10);
+
+/// How pasted text was delivered to the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasteMethod {
+    /// Wrapped in `ESC[200~ … ESC[201~`
+    Bracketed,
+    /// Typed in chunks because the application did not enable bracketed paste
+    Typed,
+    /// Handed to tmux, which brackets the text if the application requested it
+    Tmux,
+}
+
+impl PasteMethod {
+    /// Name used in tool responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PasteMethod::Bracketed => "bracketed",
+            PasteMethod::Typed => "typed",
+            PasteMethod::Tmux => "tmux",
+        }
+    }
+}
+
+impl Session {
+    /// Paste text into the terminal.
+    ///
+    /// Line endings are sent as CR, like a real terminal does on paste. If the
+    /// application has enabled bracketed paste mode (DECSET 2004) the text is
+    /// wrapped in paste markers so shells and editors insert it verbatim
+    /// instead of executing or auto-indenting it line by line. Otherwise the
+    /// text is typed in small chunks.
+    pub fn paste(&self, text: &str) -> Result<PasteMethod> {
+        // Pick up mode changes the application may have just sent
+        self.process_output()?;
+
+        let text = normalize_newlines(text);
+
+        let pty_arc = self.pty();
+        let pty = pty_arc.lock().unwrap();
+        if pty.is_tmux_mode() {
+            pty.tmux_paste(&paste_body(&text))?;
+            return Ok(PasteMethod::Tmux);
+        }
+        drop(pty);
+
+        let bracketed = self.parser().lock().unwrap().bracketed_paste();
+        debug!(
+            "Pasting into session: id={}, {} bytes, bracketed={}",
+            self.id(),
+            text.len(),
+            bracketed
+        );
+
+        if bracketed {
+            let data = format!("{PASTE_START}{}{PASTE_END}", paste_body(&text));
+            self.write(data.as_bytes())?;
+            return Ok(PasteMethod::Bracketed);
+        }
+
+        let chunks = split_chunks(&text, PASTE_CHUNK_BYTES);
+        for (i, chunk) in chunks.iter().enumerate() {
+            self.write(chunk.as_bytes())?;
+            if i + 1 < chunks.len() {
+                std::thread::sleep(PASTE_CHUNK_DELAY);
+            }
+        }
+        Ok(PasteMethod::Typed)
+    }
+}
+
+/// Convert LF and CRLF line endings to CR.
+fn normalize_newlines(text: &str) -> String {
+    text.replace("\r\n", "\r").replace('\n', "\r")
+}
+
+/// Text to put between the paste markers.
+///
+/// Every ESC is dropped, so no end marker can appear in the body, not even
+/// one that removing an inner marker would reassemble.
+fn paste_body(text: &str) -> String {
+    text.replace('\x1b', "")
+}
+
+/// Split text into chunks of at most `max_bytes`, on character boundaries.
+fn split_chunks(text: &str, max_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut end = 0;
+
+    for (idx, ch) in text.char_indices() {
+        let next = idx + ch.len_utf8();
+        if next - start > max_bytes && end > start {
+            chunks.push(&text[start..end]);
+            start = end;
+        }
+        end = next;
+    }
+    if end > start {
+        chunks.push(&text[start..end]);
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use terminal_mcp_core::Dimensions;
+
+    #[test]
+    fn test_normalize_newlines() {
+        assert_eq!(normalize_newlines("a\nb\r\nc"), "a\rb\rc");
+    }
+
+    #[test]
+    fn test_paste_body_has_no_end_marker() {
+        assert_eq!(paste_body("ls\rpwd"), "ls\rpwd");
+        assert_eq!(paste_body("a\x1b[201~b"), "a[201~b");
+
+        // Removing the inner marker once would leave an outer one behind
+        let nested = paste_body("\x1b[20\x1b[201~1~rm -rf ~\r");
+        assert!(!nested.contains(PASTE_END));
+        assert!(!nested.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_split_chunks() {
+        assert_eq!(split_chunks("abcdef", 4), vec!["abcd", "ef"]);
+        assert_eq!(split_chunks("", 4), Vec::<&str>::new());
+        // Never splits a multi-byte character
+        assert_eq!(split_chunks("aéé", 3), vec!["aé", "é"]);
+    }
+
+    #[test]
+    fn test_session_paste_method() {
+        let session = Session::create(
+            if cfg!(windows) { "cmd.exe" } else { "cat" }.to_string(),
+            vec![],
+            Dimensions::new(24, 80),
+        )
+        .unwrap();
+
+        assert_eq!(session.paste("echo hi\n").unwrap(), PasteMethod::Typed);
+
+        session.parser().lock().unwrap().process(b"\x1b[?2004h");
+        assert_eq!(
+            session.paste("line 1\nline 2\n").unwrap(),
+            PasteMethod::Bracketed
+        );
+
+        session.terminate().unwrap();
+    }
+}
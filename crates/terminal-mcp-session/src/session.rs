@@ -1,19 +1,31 @@
 //! Terminal session management.
 
+use std::sync::atomic::AtomicU64;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 
+use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
 
-use terminal_mcp_core::{Dimensions, Key, Result, SessionId};
+use terminal_mcp_core::{
+    Dimensions, Key, LimitKind, PendingLine, Redactor, ResourceLimits, Result, SandboxSettings,
+    SessionId, TerminalEvent,
+};
 use terminal_mcp_detector::DetectionPipeline;
-use terminal_mcp_emulator::{Grid, Parser, PtyHandle, SessionRecorder};
+use terminal_mcp_emulator::{Grid, Parser, PtyHandle, SessionCgroup, SessionRecorder};
 
+use crate::change::{ScreenChange, CHANGE_CHANNEL_CAPACITY};
+use crate::events::EVENT_CHANNEL_CAPACITY;
+use crate::history::ScreenHistory;
+use crate::limits::OutputRate;
 use crate::navigation::NavigationCalculator;
 use crate::output::OutputBuffer;
 use crate::snapshot::SnapshotConfig;
 use crate::visual::{SessionMode, VisualTerminalHandle};
 
+/// How long the background reader waits when the PTY has no output.
+const READ_INTERVAL: Duration = Duration::from_millis(10);
+
 /// Status of a terminal session.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SessionStatus {
@@ -23,6 +35,19 @@ pub enum SessionStatus {
     Exited,
     /// Session was terminated
     Terminated,
+    /// Session ran into one of its resource limits
+    LimitExceeded(LimitKind),
+}
+
+impl std::fmt::Display for SessionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionStatus::Running => write!(f, "running"),
+            SessionStatus::Exited => write!(f, "exited"),
+            SessionStatus::Terminated => write!(f, "terminated"),
+            SessionStatus::LimitExceeded(kind) => write!(f, "limit_exceeded: {kind}"),
+        }
+    }
 }
 
 /// A terminal session.
@@ -49,17 +74,47 @@ pub struct Session {
     /// Command arguments
     args: Vec<String>,
 
+    /// Working directory the command was started in
+    cwd: Option<String>,
+
     /// Session creation time
     created_at: SystemTime,
 
     /// Current session status
-    status: Arc<Mutex<SessionStatus>>,
+    pub(crate) status: Arc<Mutex<SessionStatus>>,
 
     /// Session mode (headless or visual)
     mode: SessionMode,
 
     /// Visual terminal handle (only for visual mode)
     visual_handle: Option<VisualTerminalHandle>,
+
+    /// Screen change events
+    pub(crate) changes: broadcast::Sender<ScreenChange>,
+
+    /// Grid generation of the last published change event
+    pub(crate) published_generation: Arc<AtomicU64>,
+
+    /// Bell and notification events
+    pub(crate) events: broadcast::Sender<TerminalEvent>,
+
+    /// Recent distinct screens
+    pub(crate) history: Arc<Mutex<ScreenHistory>>,
+
+    /// Resource limits applied to the command
+    pub(crate) limits: ResourceLimits,
+
+    /// Cgroup holding the command, when memory or pid limits are set
+    pub(crate) cgroup: Option<Arc<SessionCgroup>>,
+
+    /// Output read in the current rate window
+    pub(crate) output_rate: Arc<Mutex<OutputRate>>,
+
+    /// Secret redaction for raw output and recordings
+    pub(crate) redactor: Option<Redactor>,
+
+    /// Shell command line typed but not yet submitted
+    pending_line: Arc<Mutex<PendingLine>>,
 }
 
 impl Session {
@@ -94,10 +149,72 @@ impl Session {
         } else {
             debug!("Creating headless PTY session");
             // Headless mode: regular PTY
-            let pty = PtyHandle::spawn(&command, &args, dimensions, cwd)?;
+            let pty = PtyHandle::spawn(&command, &args, dimensions, cwd.clone())?;
             (None, pty)
         };
 
+        let mut session = Self::from_pty(command, args, dimensions, mode, visual_handle, pty);
+        session.cwd = cwd;
+        Ok(session)
+    }
+
+    /// Create a headless session with an optional namespace sandbox and
+    /// resource limits.
+    ///
+    /// With a sandbox, `cwd` is a path inside it. Memory and pid limits need
+    /// `limits.cgroup_parent`.
+    pub fn create_confined(
+        command: String,
+        args: Vec<String>,
+        dimensions: Dimensions,
+        cwd: Option<String>,
+        sandbox: Option<&SandboxSettings>,
+        limits: &ResourceLimits,
+    ) -> Result<Self> {
+        info!(
+            "Creating confined session: command='{}', dimensions={}x{}, cwd={:?}, sandboxed={}, limits={:?}",
+            command,
+            dimensions.rows,
+            dimensions.cols,
+            cwd,
+            sandbox.is_some(),
+            limits
+        );
+        limits.validate()?;
+        let cgroup = match (&limits.cgroup_parent, limits.needs_cgroup()) {
+            (Some(parent), true) => {
+                Some(SessionCgroup::create(std::path::Path::new(parent), limits)?)
+            }
+            _ => None,
+        };
+        let pty = PtyHandle::spawn_confined(
+            &command,
+            &args,
+            dimensions,
+            cwd.clone(),
+            sandbox,
+            limits,
+            cgroup.as_ref(),
+        )?;
+
+        let mut session =
+            Self::from_pty(command, args, dimensions, SessionMode::Headless, None, pty);
+        session.cwd = cwd;
+        session.limits = limits.clone();
+        session.cgroup = cgroup.map(Arc::new);
+        session.output_rate = Arc::new(Mutex::new(OutputRate::new(limits.output_bytes_per_sec)));
+        Ok(session)
+    }
+
+    /// Wrap a spawned PTY in a new session.
+    fn from_pty(
+        command: String,
+        args: Vec<String>,
+        dimensions: Dimensions,
+        mode: SessionMode,
+        visual_handle: Option<VisualTerminalHandle>,
+        pty: PtyHandle,
+    ) -> Self {
         // Create grid and parser
         let grid = Grid::new(dimensions);
         let parser = Parser::new(grid);
@@ -108,7 +225,7 @@ impl Session {
             session_id, mode
         );
 
-        Ok(Self {
+        Self {
             id: session_id,
             pty: Arc::new(Mutex::new(pty)),
             parser: Arc::new(Mutex::new(parser)),
@@ -116,11 +233,30 @@ impl Session {
             recorder: Arc::new(Mutex::new(None)),
             command,
             args,
+            cwd: None,
             created_at: SystemTime::now(),
             status: Arc::new(Mutex::new(SessionStatus::Running)),
             mode,
             visual_handle,
-        })
+            changes: broadcast::channel(CHANGE_CHANNEL_CAPACITY).0,
+            published_generation: Arc::new(AtomicU64::new(0)),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            history: Arc::new(Mutex::new(ScreenHistory::default())),
+            limits: ResourceLimits::default(),
+            cgroup: None,
+            output_rate: Arc::new(Mutex::new(OutputRate::new(None))),
+            redactor: None,
+            pending_line: Arc::new(Mutex::new(PendingLine::default())),
+        }
+    }
+
+    /// Redact secrets from raw output reads and recordings.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.output_buf = Arc::new(Mutex::new(
+            OutputBuffer::new().with_redactor(redactor.clone()),
+        ));
+        self.redactor = Some(redactor);
+        self
     }
 
     /// Get the session ID.
@@ -133,6 +269,11 @@ impl Session {
         Arc::clone(&self.pty)
     }
 
+    /// Get the command line typed into the session but not yet submitted.
+    pub fn pending_line(&self) -> Arc<Mutex<PendingLine>> {
+        Arc::clone(&self.pending_line)
+    }
+
     /// Get the parser (which contains the grid).
     pub fn parser(&self) -> Arc<Mutex<Parser>> {
         Arc::clone(&self.parser)
@@ -148,6 +289,11 @@ impl Session {
         &self.args
     }
 
+    /// Get the working directory the command was started in, if one was given.
+    pub fn cwd(&self) -> Option<&str> {
+        self.cwd.as_deref()
+    }
+
     /// Get the session creation time.
     pub fn created_at(&self) -> SystemTime {
         self.created_at
@@ -189,9 +335,20 @@ impl Session {
     /// Reads available output from the PTY and feeds it through the VTE parser
     /// to update the grid state. If recording is active, records the output.
     pub fn process_output(&self) -> Result<usize> {
+        let (allowed, exceeded) = {
+            let mut rate = self.output_rate.lock().unwrap();
+            (rate.allows(), rate.exceeded())
+        };
+        self.track_output_rate(exceeded);
+        // Over the output rate: leave the rest in the PTY until the next window
+        if !allowed {
+            return Ok(0);
+        }
+
         let pty = self.pty.lock().unwrap();
         let bytes = pty.read()?;
         let count = bytes.len();
+        self.output_rate.lock().unwrap().add(count);
 
         if count > 0 {
             debug!("Processing PTY output: id={}, {} bytes", self.id, count);
@@ -211,11 +368,73 @@ impl Session {
             // Process through parser
             let mut parser = self.parser.lock().unwrap();
             parser.process(&bytes);
+            let responses = parser.take_responses();
+            self.publish_changes(parser.grid());
+            self.record_frame(parser.grid());
+            self.publish_events(&mut parser);
+            drop(parser);
+
+            // Answer terminal queries (DA, CPR, keyboard protocol); tmux answers its own
+            if !responses.is_empty() && !pty.is_tmux_mode() {
+                debug!(
+                    "Answering terminal queries: id={}, {} bytes",
+                    self.id,
+                    responses.len()
+                );
+                pty.write(&responses)?;
+            }
         }
 
         Ok(count)
     }
 
+    /// Drain the PTY on a background thread.
+    ///
+    /// Screen history, bells and notifications then see output as it arrives,
+    /// not only when the next tool call reads it. The thread stops once the
+    /// session is dropped or the PTY is closed, which happens after every
+    /// process holding it has exited and its output is drained, checking then
+    /// whether a resource limit killed the command.
+    /// Tmux sessions are skipped: every tmux read captures the whole pane.
+    pub fn spawn_reader(self: &Arc<Self>) -> Option<std::thread::JoinHandle<()>> {
+        if self.pty.lock().unwrap().is_tmux_mode() {
+            return None;
+        }
+        let session = Arc::downgrade(self);
+        let name = format!("reader-{}", self.id);
+        let spawned = std::thread::Builder::new().name(name).spawn(move || {
+            loop {
+                let Some(session) = session.upgrade() else {
+                    return;
+                };
+                match session.process_output() {
+                    Ok(0) if session.pty.lock().unwrap().is_closed() => break,
+                    // Nothing to read yet, or over the output rate
+                    Ok(0) => {
+                        drop(session);
+                        std::thread::sleep(READ_INTERVAL);
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        debug!("Background reader stopped: id={}, {e}", session.id);
+                        break;
+                    }
+                }
+            }
+            // Report a limit that killed the command right away
+            if let Some(session) = session.upgrade() {
+                session.check_limits();
+            }
+        });
+        match spawned {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                warn!("Failed to start background reader: id={}, {e}", self.id);
+                None
+            }
+        }
+    }
+
     /// Write bytes to the PTY.
     ///
     /// If recording is active, records the input.
@@ -233,6 +452,16 @@ impl Session {
         pty.write(data)
     }
 
+    /// Write a secret to the PTY.
+    ///
+    /// Unlike [`Session::write`], nothing is recorded or logged.
+    pub fn write_secret(&self, secret: &[u8]) -> Result<()> {
+        debug!("Writing secret to PTY: id={}", self.id);
+        let pty = self.pty.lock().unwrap();
+        pty.write(secret)?;
+        Ok(())
+    }
+
     /// Resize the terminal.
     pub fn resize(&self, new_dimensions: Dimensions) -> Result<()> {
         info!(
@@ -243,7 +472,9 @@ impl Session {
         pty.resize(new_dimensions)?;
 
         let mut parser = self.parser.lock().unwrap();
-        parser.grid_mut().resize(new_dimensions);
+        parser.resize(new_dimensions);
+        self.publish_changes(parser.grid());
+        self.record_frame(parser.grid());
 
         Ok(())
     }
@@ -267,7 +498,7 @@ impl Session {
     /// ```
     pub fn press_key(&self, key: &str) -> Result<()> {
         let key = Key::parse(key)?;
-        let escape_sequence = key.to_escape_sequence();
+        let escape_sequence = self.key_sequence(&key);
         self.write(&escape_sequence)?;
 
         // In visual mode, add a small delay to allow the TUI application
@@ -281,6 +512,13 @@ impl Session {
         Ok(())
     }
 
+    /// Encode a key for the keyboard mode the application has negotiated
+    /// (legacy, modifyOtherKeys or the kitty keyboard protocol).
+    pub fn key_sequence(&self, key: &Key) -> Vec<u8> {
+        let mode = self.parser.lock().unwrap().keyboard_mode();
+        key.encode(mode)
+    }
+
     /// Type text into the terminal.
     ///
     /// Sends the text string to the PTY, optionally with a delay between each character.
@@ -364,7 +602,7 @@ impl Session {
         let mut key_names = Vec::new();
         for (i, key) in keys.iter().enumerate() {
             // Convert Key to escape sequence and send
-            let escape_seq = key.to_escape_sequence();
+            let escape_seq = self.key_sequence(key);
             self.write(&escape_seq)?;
 
             // Store key name for response
@@ -638,7 +876,11 @@ impl Session {
             parser.grid().dimensions()
         };
 
-        *recorder = Some(SessionRecorder::new(dimensions));
+        let mut new_recorder = SessionRecorder::new(dimensions);
+        if let Some(redactor) = &self.redactor {
+            new_recorder = new_recorder.with_redactor(redactor.clone());
+        }
+        *recorder = Some(new_recorder);
         Ok(())
     }
 
@@ -656,6 +898,15 @@ impl Session {
         self.recorder.lock().unwrap().is_some()
     }
 
+    /// The recording so far in asciicast v2 format, if one is active.
+    pub fn recording_text(&self) -> Result<Option<String>> {
+        let recorder = self.recorder.lock().unwrap();
+        recorder
+            .as_ref()
+            .map(SessionRecorder::to_string)
+            .transpose()
+    }
+
     /// Save the current recording to a file.
     ///
     /// # Errors
@@ -704,6 +955,34 @@ mod tests {
         assert_ne!(session1.id(), session2.id());
     }
 
+    #[test]
+    fn test_reader_records_screens_between_reads() {
+        // The toast is drawn and erased with no call in between
+        let session = Arc::new(
+            Session::create(
+                "sh".to_string(),
+                vec![
+                    "-c".to_string(),
+                    "printf 'saving...'; sleep 0.3; printf '\\r\\033[2Kdone'; sleep 5".to_string(),
+                ],
+                Dimensions::new(5, 40),
+            )
+            .unwrap(),
+        );
+        assert!(session.spawn_reader().is_some());
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        let screen = || session.parser().lock().unwrap().grid().to_plain_text();
+        while !screen().contains("done") {
+            assert!(std::time::Instant::now() < deadline, "no output");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(!screen().contains("saving"));
+        let history = session.history();
+        let history = history.lock().unwrap();
+        assert!(history.frames().any(|f| f.text.contains("saving...")));
+    }
+
     #[test]
     fn test_session_process_output() {
         let session = Session::create(
@@ -839,6 +1118,23 @@ mod tests {
         assert!(session.press_key("Delete").is_ok());
     }
 
+    #[test]
+    fn test_session_key_sequence_follows_keyboard_mode() {
+        let session = Session::create(
+            if cfg!(windows) { "cmd.exe" } else { "sh" }.to_string(),
+            vec![],
+            Dimensions::new(24, 80),
+        )
+        .unwrap();
+
+        let escape = Key::Escape;
+        assert_eq!(session.key_sequence(&escape), vec![0x1B]);
+
+        // Application enables kitty disambiguation
+        session.parser().lock().unwrap().process(b"\x1b[>1u");
+        assert_eq!(session.key_sequence(&escape), b"\x1b[27u");
+    }
+
     #[test]
     fn test_session_click() {
         use crate::SnapshotConfig;
@@ -933,8 +1229,14 @@ mod tests {
         // Process output
         session.process_output().unwrap();
 
+        // The recording can be read while it continues
+        let so_far = session.recording_text().unwrap().unwrap();
+        assert!(so_far.contains("echo hello"));
+        assert!(session.is_recording());
+
         // Stop recording
         let recorder = session.stop_recording().unwrap();
+        assert!(session.recording_text().unwrap().is_none());
 
         // Should have at least one event (the input)
         assert!(recorder.event_count() > 0);
@@ -1,9 +1,11 @@
 //! Terminal snapshot functionality.
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
-use terminal_mcp_core::{Result, TerminalStateTree};
+use terminal_mcp_core::{Bounds, Error, InputMode, Result, TerminalStateTree, TermiosFlags};
 use terminal_mcp_detector::{DetectionPipeline, TSTAssembler};
+use terminal_mcp_emulator::ScreenRenderer;
 
 use crate::session::Session;
 
@@ -18,6 +20,15 @@ pub struct SnapshotConfig {
 
     /// Maximum number of bytes to process per iteration
     pub max_bytes_per_iteration: usize,
+
+    /// Fill `ansi_buffer` with the screen re-encoded as ANSI
+    pub include_ansi: bool,
+
+    /// Fill `html` with the screen rendered as HTML
+    pub include_html: bool,
+
+    /// Fill `styles` with per-row color and attribute runs
+    pub include_styles: bool,
 }
 
 impl Default for SnapshotConfig {
@@ -26,11 +37,80 @@ impl Default for SnapshotConfig {
             idle_timeout: Duration::from_secs(5),
             idle_threshold: Duration::from_millis(100),
             max_bytes_per_iteration: 4096,
+            include_ansi: false,
+            include_html: false,
+            include_styles: false,
         }
     }
 }
 
+/// The text before the cursor and how input typed at it is handled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputPrompt {
+    /// Text on the cursor row before the cursor
+    pub text: String,
+    /// Input mode, as reported in snapshots
+    pub mode: Option<InputMode>,
+    /// PTY line discipline flags, when they can be read
+    pub termios: Option<TermiosFlags>,
+}
+
+impl InputPrompt {
+    /// Whether the prompt is waiting for a password or passphrase.
+    pub fn is_secret(&self) -> bool {
+        self.mode == Some(InputMode::Password)
+    }
+
+    /// Whether the PTY is known to echo typed input back to the screen.
+    pub fn echoes(&self) -> bool {
+        self.termios.is_some_and(|flags| flags.echo)
+    }
+
+    /// Whether the PTY is known to take a hidden line of input.
+    ///
+    /// Echo has to be off in canonical mode, the way password prompts read
+    /// input. Raw-mode applications draw typed keys themselves, so echo being
+    /// off says nothing about what ends up on screen.
+    pub fn hides_input(&self) -> bool {
+        self.termios
+            .is_some_and(|flags| !flags.echo && flags.canonical)
+    }
+}
+
 impl Session {
+    /// Read pending output and describe the prompt at the cursor.
+    ///
+    /// Uses the same rules as the snapshot's `input_mode`: disabled echo in
+    /// canonical mode, or prompt text such as `Password:` or
+    /// `Enter passphrase for key:`.
+    pub fn input_prompt(&self) -> Result<InputPrompt> {
+        self.process_output()?;
+        let termios = self.pty().lock().unwrap().termios();
+
+        let parser_arc = self.parser();
+        let parser = parser_arc.lock().unwrap();
+        let grid = parser.grid();
+        let cursor = grid.cursor().position;
+        let text = grid.extract_text(&Bounds::new(cursor.row, 0, cursor.col, 1));
+        let mode = input_mode(termios, grid.is_alternate_screen(), &text);
+        Ok(InputPrompt {
+            text,
+            mode,
+            termios,
+        })
+    }
+
+    /// The last non-blank line on screen, as of the output read so far.
+    pub fn last_screen_line(&self) -> Option<String> {
+        let parser_arc = self.parser();
+        let parser = parser_arc.lock().unwrap();
+        let text = parser.grid().to_plain_text();
+        text.lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .map(|line| line.trim().to_string())
+    }
+
     /// Capture a snapshot of the terminal state.
     ///
     /// This waits for the terminal to become idle (no output for idle_threshold),
@@ -41,14 +121,37 @@ impl Session {
         pipeline: &DetectionPipeline,
         config: &SnapshotConfig,
     ) -> Result<TerminalStateTree> {
+        self.snapshot_cancellable(pipeline, config, &AtomicBool::new(false))
+    }
+
+    /// Capture a snapshot like [`Session::snapshot`], giving up with
+    /// [`Error::Cancelled`] once `cancelled` is set while waiting for idle.
+    pub fn snapshot_cancellable(
+        &self,
+        pipeline: &DetectionPipeline,
+        config: &SnapshotConfig,
+        cancelled: &AtomicBool,
+    ) -> Result<TerminalStateTree> {
+        self.snapshot_with_generation(pipeline, config, cancelled)
+            .map(|(tst, _)| tst)
+    }
+
+    /// Capture a snapshot along with the grid generation it was built from.
+    pub(crate) fn snapshot_with_generation(
+        &self,
+        pipeline: &DetectionPipeline,
+        config: &SnapshotConfig,
+        cancelled: &AtomicBool,
+    ) -> Result<(TerminalStateTree, u64)> {
         // Wait for idle
-        self.wait_for_idle(config)?;
+        self.wait_for_idle(config, cancelled)?;
 
         // For tmux mode (visual mode), clear the grid and take one final snapshot
         // This ensures we get a clean state without accumulated content
         let pty_arc = self.pty();
         let pty = pty_arc.lock().unwrap();
         let is_tmux = pty.is_tmux_mode();
+        let termios = pty.termios();
         drop(pty);
 
         if is_tmux {
@@ -72,51 +175,78 @@ impl Session {
         let parser_arc = self.parser();
         let parser = parser_arc.lock().unwrap();
         let grid = parser.grid();
+        let generation = grid.generation();
         let cursor = grid.cursor().position;
+        let cursor_visible = grid.cursor().visible;
+        let cursor_style = grid.cursor().style;
         let dimensions = grid.dimensions();
         let raw_text = grid.to_plain_text();
+        let prompt = grid.extract_text(&Bounds::new(cursor.row, 0, cursor.col, 1));
+        let input_mode = input_mode(termios, grid.is_alternate_screen(), &prompt);
 
         // Run detection pipeline
         let detected = pipeline.detect(grid, cursor);
 
         // Build TST
         let assembler = TSTAssembler::new();
-        let tst = assembler.assemble(
+        let mut tst = assembler.assemble(
             detected,
             self.id().to_string(),
             dimensions,
             cursor,
             raw_text,
         );
+        tst.cursor_visible = cursor_visible;
+        tst.cursor_style = cursor_style;
+        tst.events = parser.events().clone();
+        tst.termios = termios;
+        tst.input_mode = input_mode;
+        if config.include_ansi {
+            tst.ansi_buffer = Some(grid.to_ansi());
+        }
+        if config.include_html {
+            tst.html = Some(ScreenRenderer::new().render_html(grid));
+        }
+        if config.include_styles {
+            tst.styles = Some(grid.style_rows());
+        }
 
-        Ok(tst)
+        Ok((tst, generation))
     }
 
     /// Wait for terminal to become idle.
     ///
-    /// Continuously processes PTY output until no new output is received
-    /// for the configured idle_threshold duration, or until idle_timeout is reached.
-    fn wait_for_idle(&self, config: &SnapshotConfig) -> Result<()> {
+    /// Continuously processes PTY output until the screen has not changed
+    /// for the configured idle_threshold duration, or until idle_timeout is
+    /// reached. Output that leaves the screen as it was (identical redraws,
+    /// query traffic) does not count as activity. `cancelled` is checked on
+    /// every iteration.
+    fn wait_for_idle(&self, config: &SnapshotConfig, cancelled: &AtomicBool) -> Result<()> {
         let start = Instant::now();
-        let mut last_output = Instant::now();
+        let mut last_change = Instant::now();
+        let mut generation = self.screen_generation();
 
         loop {
+            if cancelled.load(Ordering::Relaxed) {
+                return Err(Error::Cancelled);
+            }
+
             // Check timeout
             if start.elapsed() > config.idle_timeout {
                 break;
             }
 
             // Process available output
-            let bytes_read = self.process_output()?;
+            self.process_output()?;
 
-            if bytes_read > 0 {
+            let current = self.screen_generation();
+            if current != generation {
                 // Reset idle timer
-                last_output = Instant::now();
-            } else {
+                generation = current;
+                last_change = Instant::now();
+            } else if last_change.elapsed() >= config.idle_threshold {
                 // Check if idle long enough
-                if last_output.elapsed() >= config.idle_threshold {
-                    break;
-                }
+                break;
             }
 
             // Small sleep to avoid busy-waiting
@@ -127,6 +257,40 @@ impl Session {
     }
 }
 
+/// Words that mark a prompt for a secret.
+const SECRET_PROMPT_WORDS: &[&str] = &["password", "passphrase", "passcode", "pin"];
+
+/// Whether the text before the cursor asks for a secret.
+fn is_secret_prompt(prompt: &str) -> bool {
+    let prompt = prompt.trim_end().to_lowercase();
+    prompt.ends_with([':', '?', '>'])
+        && prompt
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| SECRET_PROMPT_WORDS.contains(&word))
+}
+
+/// Tell how typed input will be handled.
+///
+/// Line editors such as readline put the PTY in non-canonical mode with echo
+/// off while they read a line, so those flags alone don't mean a TUI. Unlike
+/// full-screen apps, line editors keep signals on and stay on the primary
+/// screen.
+fn input_mode(
+    termios: Option<TermiosFlags>,
+    alternate_screen: bool,
+    prompt: &str,
+) -> Option<InputMode> {
+    let secret = is_secret_prompt(prompt);
+    match termios {
+        Some(flags) if flags.canonical && flags.echo => Some(InputMode::Line),
+        Some(flags) if flags.canonical => Some(InputMode::Password),
+        Some(_) if secret => Some(InputMode::Password),
+        Some(flags) if alternate_screen || !flags.signals => Some(InputMode::Raw),
+        Some(_) => Some(InputMode::Line),
+        None => secret.then_some(InputMode::Password),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,6 +328,181 @@ mod tests {
         assert_eq!(tst.dimensions, Dimensions::new(24, 80));
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_snapshot_exports() {
+        let session = Session::create(
+            "printf".to_string(),
+            vec!["\\033[31mred\\033[0m".to_string()],
+            Dimensions::new(5, 20),
+        )
+        .unwrap();
+
+        let pipeline = DetectionPipeline::new();
+        std::thread::sleep(Duration::from_millis(200));
+
+        let tst = session
+            .snapshot(&pipeline, &SnapshotConfig::default())
+            .unwrap();
+        assert!(tst.ansi_buffer.is_none());
+        assert!(tst.html.is_none());
+        assert!(tst.styles.is_none());
+
+        let config = SnapshotConfig {
+            include_ansi: true,
+            include_html: true,
+            include_styles: true,
+            ..Default::default()
+        };
+        let tst = session.snapshot(&pipeline, &config).unwrap();
+        assert!(tst.ansi_buffer.unwrap().starts_with("\x1b[0;31mred\x1b[0m"));
+        assert!(tst.html.unwrap().contains("color:#cd0000\">red</span>"));
+        let styles = tst.styles.unwrap();
+        assert_eq!(styles[0].runs[0].fg.as_deref(), Some("red"));
+        assert_eq!(styles[0].runs[0].end_col, 3);
+    }
+
+    #[test]
+    fn test_input_mode() {
+        let flags = |echo, canonical, signals| {
+            Some(TermiosFlags {
+                echo,
+                canonical,
+                signals,
+            })
+        };
+
+        // Cooked shell line, `read -s`, readline, vim
+        assert_eq!(
+            input_mode(flags(true, true, true), false, "$ "),
+            Some(InputMode::Line)
+        );
+        assert_eq!(
+            input_mode(flags(false, true, true), false, "Token "),
+            Some(InputMode::Password)
+        );
+        assert_eq!(
+            input_mode(flags(false, false, true), false, "$ "),
+            Some(InputMode::Line)
+        );
+        assert_eq!(
+            input_mode(flags(false, false, false), true, ""),
+            Some(InputMode::Raw)
+        );
+        assert_eq!(
+            input_mode(flags(false, false, true), true, ""),
+            Some(InputMode::Raw)
+        );
+
+        // Prompt text decides in raw mode and without termios
+        assert_eq!(
+            input_mode(flags(false, false, true), false, "[sudo] password for me: "),
+            Some(InputMode::Password)
+        );
+        assert_eq!(
+            input_mode(None, false, "Enter PIN:"),
+            Some(InputMode::Password)
+        );
+        assert_eq!(input_mode(None, false, "$ "), None);
+        assert!(!is_secret_prompt("spinning:"));
+        assert!(!is_secret_prompt("password reset sent"));
+    }
+
+    #[test]
+    fn test_input_prompt_hides_input() {
+        let prompt = |termios| InputPrompt {
+            text: "Password: ".to_string(),
+            mode: Some(InputMode::Password),
+            termios,
+        };
+        let flags = |echo, canonical| {
+            Some(TermiosFlags {
+                echo,
+                canonical,
+                signals: true,
+            })
+        };
+
+        assert!(prompt(flags(false, true)).hides_input());
+        // Raw mode, echoing, and tmux or visual sessions without termios
+        assert!(!prompt(flags(false, false)).hides_input());
+        assert!(!prompt(flags(true, true)).hides_input());
+        assert!(!prompt(None).hides_input());
+        assert!(!prompt(None).echoes());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_snapshot_password_prompt() {
+        let session = Session::create(
+            "sh".to_string(),
+            vec![
+                "-c".to_string(),
+                "stty -echo; printf 'Password: '; sleep 2".to_string(),
+            ],
+            Dimensions::new(5, 40),
+        )
+        .unwrap();
+
+        let pipeline = DetectionPipeline::new();
+        std::thread::sleep(Duration::from_millis(300));
+
+        let tst = session
+            .snapshot(&pipeline, &SnapshotConfig::default())
+            .unwrap();
+        let termios = tst.termios.unwrap();
+        assert!(!termios.echo);
+        assert!(termios.canonical);
+        assert_eq!(tst.input_mode, Some(InputMode::Password));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_input_prompt_and_secret_entry() {
+        let session = Session::create(
+            "sh".to_string(),
+            vec![
+                "-c".to_string(),
+                "printf 'Enter passphrase for key: '; stty -echo; read key; stty echo; \
+                 echo; echo \"got ${#key} chars\"; sleep 2"
+                    .to_string(),
+            ],
+            Dimensions::new(5, 40),
+        )
+        .unwrap();
+        session.start_recording().unwrap();
+
+        let mut prompt = session.input_prompt().unwrap();
+        for _ in 0..50 {
+            if prompt.is_secret() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+            prompt = session.input_prompt().unwrap();
+        }
+        assert!(prompt.is_secret(), "{prompt:?}");
+        assert!(prompt.hides_input() && !prompt.echoes());
+        assert_eq!(prompt.text.trim_end(), "Enter passphrase for key:");
+
+        session.write_secret(b"hunter2\r").unwrap();
+        let mut text = String::new();
+        for _ in 0..50 {
+            session.process_output().unwrap();
+            text = session.parser().lock().unwrap().grid().to_plain_text();
+            if text.contains("got") {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(text.contains("got 7 chars"), "{text}");
+        assert!(!text.contains("hunter2"));
+        assert_eq!(session.last_screen_line().as_deref(), Some("got 7 chars"));
+
+        let recording = session.stop_recording().unwrap().to_string().unwrap();
+        assert!(!recording.contains("hunter2"));
+        assert!(!session.input_prompt().unwrap().is_secret());
+    }
+
     #[test]
     #[cfg(unix)]
     fn test_wait_for_idle() {
@@ -178,10 +517,11 @@ mod tests {
             idle_timeout: Duration::from_secs(2),
             idle_threshold: Duration::from_millis(100),
             max_bytes_per_iteration: 4096,
+            ..Default::default()
         };
 
         let start = Instant::now();
-        let result = session.wait_for_idle(&config);
+        let result = session.wait_for_idle(&config, &AtomicBool::new(false));
         let elapsed = start.elapsed();
 
         assert!(result.is_ok());
@@ -204,6 +544,7 @@ mod tests {
             idle_timeout: Duration::from_secs(2),
             idle_threshold: Duration::from_millis(50),
             max_bytes_per_iteration: 2048,
+            ..Default::default()
         };
 
         std::thread::sleep(Duration::from_millis(200));
@@ -212,6 +553,40 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_snapshot_cancelled() {
+        // Never idle: the screen changes every 50ms
+        let session = Session::create(
+            "sh".to_string(),
+            vec![
+                "-c".to_string(),
+                "i=0; while :; do i=$((i+1)); echo $i; sleep 0.05; done".to_string(),
+            ],
+            Dimensions::new(24, 80),
+        )
+        .unwrap();
+
+        let config = SnapshotConfig {
+            idle_timeout: Duration::from_secs(30),
+            idle_threshold: Duration::from_secs(10),
+            ..Default::default()
+        };
+        let cancelled = AtomicBool::new(false);
+
+        let start = Instant::now();
+        let result = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(Duration::from_millis(200));
+                cancelled.store(true, Ordering::Relaxed);
+            });
+            session.snapshot_cancellable(&DetectionPipeline::new(), &config, &cancelled)
+        });
+
+        assert!(matches!(result, Err(Error::Cancelled)), "{result:?}");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
     #[test]
     #[cfg(unix)]
     fn test_snapshot_with_idle_bash_no_hang() {
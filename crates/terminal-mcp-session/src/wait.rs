@@ -1,6 +1,7 @@
 //! Wait conditions and mechanisms for terminal state changes.
 
 use regex::Regex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
 use terminal_mcp_core::{Error, Result, TerminalStateTree};
@@ -24,6 +25,12 @@ pub struct WaitCondition {
     /// Wait for terminal to be idle
     pub idle: bool,
 
+    /// Wait for the bell to ring; with `notification`, either one will do
+    pub bell: bool,
+
+    /// Wait for a desktop notification (OSC 9/777)
+    pub notification: bool,
+
     /// Maximum time to wait
     pub timeout: Duration,
 
@@ -38,6 +45,8 @@ impl Default for WaitCondition {
             element_type: None,
             gone: false,
             idle: false,
+            bell: false,
+            notification: false,
             timeout: Duration::from_secs(30),
             poll_interval: Duration::from_millis(100),
         }
@@ -92,6 +101,22 @@ impl WaitCondition {
         }
     }
 
+    /// Wait for the bell to ring.
+    pub fn for_bell() -> Self {
+        Self {
+            bell: true,
+            ..Self::default()
+        }
+    }
+
+    /// Wait for a desktop notification.
+    pub fn for_notification() -> Self {
+        Self {
+            notification: true,
+            ..Self::default()
+        }
+    }
+
     /// Set timeout duration.
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
@@ -103,6 +128,21 @@ impl WaitCondition {
         self.poll_interval = interval;
         self
     }
+
+    /// Reject a bell or notification wait combined with a screen condition.
+    ///
+    /// Events are counted rather than read off the screen, so the two kinds
+    /// can't be waited for in the same call.
+    pub fn validate(&self) -> Result<()> {
+        let screen = self.text.is_some() || self.element_type.is_some() || self.idle;
+        if (self.bell || self.notification) && screen {
+            return Err(Error::InvalidInput(
+                "bell and notification can't be combined with text, element_type or idle"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// Result of a wait operation.
@@ -121,8 +161,9 @@ pub struct WaitResult {
 impl Session {
     /// Wait for a condition to be met.
     ///
-    /// Repeatedly takes snapshots and checks if the condition is satisfied.
-    /// Returns when the condition is met or timeout is reached.
+    /// Takes a snapshot and checks the condition whenever the screen has
+    /// changed since the last check. Returns when the condition is met or
+    /// timeout is reached.
     ///
     /// # Arguments
     /// * `condition` - The condition to wait for
@@ -158,22 +199,54 @@ impl Session {
         pipeline: &DetectionPipeline,
         snapshot_config: &SnapshotConfig,
     ) -> Result<WaitResult> {
+        self.wait_for_cancellable(
+            condition,
+            pipeline,
+            snapshot_config,
+            &AtomicBool::new(false),
+        )
+    }
+
+    /// Wait for a condition like [`Session::wait_for`], giving up with
+    /// [`Error::Cancelled`] once `cancelled` is set.
+    ///
+    /// The flag is checked before every poll, so cancellation takes effect
+    /// within one poll interval.
+    pub fn wait_for_cancellable(
+        &self,
+        condition: &WaitCondition,
+        pipeline: &DetectionPipeline,
+        snapshot_config: &SnapshotConfig,
+        cancelled: &AtomicBool,
+    ) -> Result<WaitResult> {
+        condition.validate()?;
         let start = Instant::now();
 
         // For idle condition, we need to wait for terminal to become idle
-        // Track the last activity time to detect when terminal stabilizes
+        // Track the last screen change to detect when terminal stabilizes
         let mut last_activity_check = if condition.idle {
             Some(Instant::now())
         } else {
             None
         };
+        let mut idle_generation = self.screen_generation();
+
+        // Generation of the last snapshot checked against the condition
+        let mut checked_generation = None;
+
+        // Only events after the wait started count
+        let events_at_start = self.terminal_events();
 
         loop {
+            if cancelled.load(Ordering::Relaxed) {
+                return Err(Error::Cancelled);
+            }
+
             // Check timeout
             let elapsed = start.elapsed();
             if elapsed >= condition.timeout {
                 // Timeout reached - take final snapshot
-                let snapshot = self.snapshot(pipeline, snapshot_config)?;
+                let snapshot = self.snapshot_cancellable(pipeline, snapshot_config, cancelled)?;
                 return Ok(WaitResult {
                     condition_met: false,
                     waited_ms: elapsed.as_millis() as u64,
@@ -181,17 +254,42 @@ impl Session {
                 });
             }
 
-            // For idle condition, check if terminal has new output
+            // Bells and notifications don't touch the screen: watch the counters
+            if condition.bell || condition.notification {
+                self.process_output()?;
+
+                let events = self.terminal_events();
+                let rang = condition.bell && events.bell_count > events_at_start.bell_count;
+                let notified = condition.notification
+                    && events.notification_count > events_at_start.notification_count;
+                if rang || notified {
+                    let snapshot =
+                        self.snapshot_cancellable(pipeline, snapshot_config, cancelled)?;
+                    return Ok(WaitResult {
+                        condition_met: true,
+                        waited_ms: elapsed.as_millis() as u64,
+                        snapshot,
+                    });
+                }
+
+                std::thread::sleep(condition.poll_interval);
+                continue;
+            }
+
+            // For idle condition, check if the screen is still changing
             if let Some(last_check) = last_activity_check {
                 // Process output without blocking
-                let bytes_read = self.process_output()?;
+                self.process_output()?;
 
-                if bytes_read > 0 {
+                let generation = self.screen_generation();
+                if generation != idle_generation {
                     // Terminal is still active, reset timer
+                    idle_generation = generation;
                     last_activity_check = Some(Instant::now());
                 } else if last_check.elapsed() >= snapshot_config.idle_threshold {
                     // Terminal has been idle long enough
-                    let snapshot = self.snapshot(pipeline, snapshot_config)?;
+                    let snapshot =
+                        self.snapshot_cancellable(pipeline, snapshot_config, cancelled)?;
                     return Ok(WaitResult {
                         condition_met: true,
                         waited_ms: elapsed.as_millis() as u64,
@@ -204,8 +302,17 @@ impl Session {
                 continue;
             }
 
+            // Nothing changed since the last check: skip detection
+            self.process_output()?;
+            if checked_generation == Some(self.screen_generation()) {
+                std::thread::sleep(condition.poll_interval);
+                continue;
+            }
+
             // Take snapshot for non-idle conditions
-            let snapshot = self.snapshot(pipeline, snapshot_config)?;
+            let (snapshot, generation) =
+                self.snapshot_with_generation(pipeline, snapshot_config, cancelled)?;
+            checked_generation = Some(generation);
 
             // Check if condition is met
             if Self::check_condition(&snapshot, condition)? {
@@ -285,6 +392,51 @@ mod tests {
         assert!(condition.idle);
     }
 
+    #[test]
+    fn test_wait_condition_for_bell_and_notification() {
+        let condition = WaitCondition::for_bell();
+        assert!(condition.bell);
+        assert!(!condition.notification);
+
+        let condition = WaitCondition::for_notification();
+        assert!(condition.notification);
+        assert!(!condition.bell);
+        assert!(condition.validate().is_ok());
+
+        let condition = WaitCondition {
+            bell: true,
+            ..WaitCondition::for_notification()
+        };
+        assert!(condition.validate().is_ok());
+    }
+
+    #[test]
+    fn test_wait_condition_rejects_bell_with_screen_condition() {
+        for condition in [
+            WaitCondition::for_text("done"),
+            WaitCondition::for_element("menu"),
+            WaitCondition::for_idle(),
+        ] {
+            let condition = WaitCondition {
+                bell: true,
+                ..condition
+            };
+            assert!(matches!(condition.validate(), Err(Error::InvalidInput(_))));
+        }
+
+        let session = Session::create("true".to_string(), vec![], Dimensions::new(24, 80)).unwrap();
+        let condition = WaitCondition {
+            notification: true,
+            ..WaitCondition::for_text("done")
+        };
+        let result = session.wait_for(
+            &condition,
+            &DetectionPipeline::new(),
+            &SnapshotConfig::default(),
+        );
+        assert!(matches!(result, Err(Error::InvalidInput(_))));
+    }
+
     #[test]
     fn test_wait_condition_with_timeout() {
         let condition = WaitCondition::for_text("test").with_timeout(Duration::from_secs(5));
@@ -316,6 +468,33 @@ mod tests {
         assert!(wait_result.condition_met);
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_wait_for_bell() {
+        let session = Session::create(
+            "sh".to_string(),
+            vec![
+                "-c".to_string(),
+                "sleep 0.2; printf 'done\\a'; sleep 1".to_string(),
+            ],
+            Dimensions::new(24, 80),
+        )
+        .unwrap();
+
+        let pipeline = DetectionPipeline::new();
+        let snapshot_config = SnapshotConfig::default();
+        let condition = WaitCondition::for_bell()
+            .with_timeout(Duration::from_secs(5))
+            .with_poll_interval(Duration::from_millis(20));
+
+        let result = session
+            .wait_for(&condition, &pipeline, &snapshot_config)
+            .unwrap();
+        assert!(result.condition_met);
+        assert_eq!(result.snapshot.events.bell_count, 1);
+        assert!(result.snapshot.raw_text.contains("done"));
+    }
+
     #[test]
     #[cfg(unix)]
     fn test_wait_for_text_appears() {
@@ -379,18 +558,59 @@ mod tests {
         assert!(wait_result.waited_ms >= 300);
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_wait_for_cancelled() {
+        let session = Session::create(
+            "sleep".to_string(),
+            vec!["5".to_string()],
+            Dimensions::new(24, 80),
+        )
+        .unwrap();
+
+        let pipeline = DetectionPipeline::new();
+        let condition = WaitCondition::for_text("never")
+            .with_timeout(Duration::from_secs(30))
+            .with_poll_interval(Duration::from_millis(20));
+        let cancelled = AtomicBool::new(false);
+
+        let start = Instant::now();
+        let result = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(Duration::from_millis(200));
+                cancelled.store(true, Ordering::Relaxed);
+            });
+            session.wait_for_cancellable(
+                &condition,
+                &pipeline,
+                &SnapshotConfig::default(),
+                &cancelled,
+            )
+        });
+
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
     #[test]
     fn test_check_condition_text_regex() {
-        use terminal_mcp_core::Position;
+        use terminal_mcp_core::{CursorStyle, Position};
 
         let snapshot = TerminalStateTree {
             session_id: "test".to_string(),
             dimensions: Dimensions::new(24, 80),
             cursor: Position::new(0, 0),
+            cursor_visible: true,
+            cursor_style: CursorStyle::Block,
             timestamp: "2025-11-30T00:00:00Z".to_string(),
             elements: vec![],
             raw_text: "Server started successfully on port 8080".to_string(),
             ansi_buffer: None,
+            html: None,
+            styles: None,
+            events: Default::default(),
+            termios: None,
+            input_mode: None,
         };
 
         // Test regex pattern matching
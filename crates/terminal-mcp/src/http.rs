@@ -0,0 +1,356 @@
+//! Streamable HTTP transport.
+//!
+//! Serves MCP over streamable HTTP (POST for requests, SSE for streamed
+//! responses and server requests) so several clients can share one
+//! long-lived server and its terminal sessions. Every request is checked
+//! for the bearer token, a `Host` naming this server and, when a browser
+//! sends one, an allowed `Origin` before it reaches the MCP service.
+
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+use rmcp::transport::{StreamableHttpServerConfig, StreamableHttpService};
+use tracing::{info, warn};
+
+use terminal_mcp_core::HttpSettings;
+
+use crate::TerminalMcpServer;
+
+/// Hosts allowed as `Origin` when no origins are configured
+const LOOPBACK_HOSTS: &[&str] = &["localhost", "127.0.0.1", "[::1]"];
+
+/// Request checks run before the MCP service.
+///
+/// Without a token every request is refused.
+#[derive(Debug, Clone)]
+struct Guard {
+    auth_token: Option<Arc<str>>,
+    bind: Option<SocketAddr>,
+    allowed_hosts: Arc<[String]>,
+    allowed_origins: Arc<[String]>,
+}
+
+impl Guard {
+    fn new(settings: &HttpSettings) -> Self {
+        Self {
+            auth_token: settings.auth_token.as_deref().map(Arc::from),
+            bind: settings.bind.parse().ok(),
+            allowed_hosts: settings.allowed_hosts.clone().into(),
+            allowed_origins: settings.allowed_origins.clone().into(),
+        }
+    }
+
+    /// Check the request headers, returning the status to refuse it with.
+    fn check(&self, headers: &HeaderMap) -> Result<(), StatusCode> {
+        // A rebound DNS name reaches a local listener under a foreign Host
+        let host = headers
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok());
+        if !host.is_some_and(|host| self.host_allowed(host)) {
+            warn!("Refused HTTP request for host {:?}", host);
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        if let Some(origin) = headers.get(header::ORIGIN) {
+            let allowed = origin
+                .to_str()
+                .is_ok_and(|origin| origin_allowed(origin, &self.allowed_origins));
+            if !allowed {
+                warn!("Refused HTTP request from origin {:?}", origin);
+                return Err(StatusCode::FORBIDDEN);
+            }
+        }
+
+        let presented = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let valid = self
+            .auth_token
+            .as_deref()
+            .is_some_and(|token| presented.is_some_and(|presented| token_eq(presented, token)));
+        if !valid {
+            warn!("Refused HTTP request without a valid bearer token");
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        Ok(())
+    }
+
+    /// Whether a `Host` header names this server.
+    ///
+    /// The bind address is always accepted. A loopback listener also answers
+    /// to `localhost` and other loopback addresses, and a wildcard listener to
+    /// any IP address; other names must be in `allowed_hosts`.
+    fn host_allowed(&self, host: &str) -> bool {
+        let name = match host.find(']') {
+            Some(end) if host.starts_with('[') => &host[1..end],
+            _ => host.split(':').next().unwrap_or_default(),
+        };
+        if self
+            .allowed_hosts
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(name))
+        {
+            return true;
+        }
+        let Some(bind) = self.bind else {
+            return false;
+        };
+        match name.parse::<IpAddr>() {
+            Ok(ip) => {
+                ip == bind.ip()
+                    || bind.ip().is_unspecified()
+                    || (bind.ip().is_loopback() && ip.is_loopback())
+            }
+            Err(_) => bind.ip().is_loopback() && name.eq_ignore_ascii_case("localhost"),
+        }
+    }
+}
+
+/// Whether a browser `Origin` may connect.
+///
+/// Without configured origins only loopback pages are allowed, which keeps
+/// other websites from reaching a local server through DNS rebinding.
+fn origin_allowed(origin: &str, allowed: &[String]) -> bool {
+    if !allowed.is_empty() {
+        return allowed
+            .iter()
+            .any(|allowed| allowed == "*" || allowed.eq_ignore_ascii_case(origin));
+    }
+    let Some((_, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    let host = match authority.find(']') {
+        Some(end) if authority.starts_with('[') => &authority[..=end],
+        _ => authority.split(':').next().unwrap_or_default(),
+    };
+    LOOPBACK_HOSTS
+        .iter()
+        .any(|loopback| host.eq_ignore_ascii_case(loopback))
+}
+
+/// Compare tokens without returning early on the first difference.
+fn token_eq(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn guard_request(State(guard): State<Guard>, request: Request, next: Next) -> Response {
+    match guard.check(request.headers()) {
+        Ok(()) => next.run(request).await,
+        Err(status) if status == StatusCode::UNAUTHORIZED => {
+            (status, [(header::WWW_AUTHENTICATE, "Bearer")]).into_response()
+        }
+        Err(status) => status.into_response(),
+    }
+}
+
+/// Build the router serving MCP at `settings.path`.
+///
+/// Each MCP session gets a [`TerminalMcpServer::connection`] of `server`,
+/// so terminal sessions are shared between clients.
+pub fn router(server: TerminalMcpServer, settings: &HttpSettings) -> Router {
+    let service = StreamableHttpService::new(
+        move || Ok(server.connection()),
+        Arc::new(LocalSessionManager::default()),
+        StreamableHttpServerConfig::default(),
+    );
+    Router::new()
+        .nest_service(&settings.path, service)
+        .layer(middleware::from_fn_with_state(
+            Guard::new(settings),
+            guard_request,
+        ))
+}
+
+/// Write a new random token to `path`, readable only by the current user.
+pub fn generate_token(path: &Path) -> std::io::Result<String> {
+    let token = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    // Replace rather than follow whatever is there
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(token.as_bytes())?;
+    Ok(token)
+}
+
+/// Serve MCP over HTTP until Ctrl+C.
+///
+/// Without a configured token, a generated one is written to
+/// [`HttpSettings::token_path`].
+pub async fn serve(server: TerminalMcpServer, settings: &HttpSettings) -> anyhow::Result<()> {
+    let mut settings = settings.clone();
+    if settings.auth_token.is_none() {
+        let path = settings.token_path()?;
+        settings.auth_token = Some(generate_token(&path)?);
+        info!("Wrote a generated bearer token to {}", path.display());
+    }
+    let listener = tokio::net::TcpListener::bind(&settings.bind).await?;
+    info!(
+        "Serving MCP on http://{}{}",
+        listener.local_addr()?,
+        settings.path
+    );
+    axum::serve(listener, router(server, &settings))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_origin_allowed() {
+        assert!(origin_allowed("http://localhost:3000", &[]));
+        assert!(origin_allowed("http://127.0.0.1", &[]));
+        assert!(origin_allowed("http://[::1]:8080", &[]));
+        assert!(!origin_allowed("http://localhost.evil.com", &[]));
+        assert!(!origin_allowed("https://evil.com", &[]));
+        assert!(!origin_allowed("null", &[]));
+
+        let allowed = vec!["https://ide.example.com".to_string()];
+        assert!(origin_allowed("https://IDE.example.com", &allowed));
+        assert!(!origin_allowed("http://localhost", &allowed));
+        assert!(origin_allowed("https://any.site", &["*".to_string()]));
+    }
+
+    #[test]
+    fn test_guard_check() {
+        let guard = Guard::new(&HttpSettings {
+            auth_token: Some("s3cret".to_string()),
+            ..Default::default()
+        });
+        let mut headers = HeaderMap::new();
+        assert_eq!(guard.check(&headers), Err(StatusCode::FORBIDDEN));
+        headers.insert(header::HOST, HeaderValue::from_static("127.0.0.1:8765"));
+        assert_eq!(guard.check(&headers), Err(StatusCode::UNAUTHORIZED));
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer nope"),
+        );
+        assert_eq!(guard.check(&headers), Err(StatusCode::UNAUTHORIZED));
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer s3cret"),
+        );
+        assert_eq!(guard.check(&headers), Ok(()));
+        headers.insert(header::ORIGIN, HeaderValue::from_static("https://evil.com"));
+        assert_eq!(guard.check(&headers), Err(StatusCode::FORBIDDEN));
+
+        // No token, no access
+        let open = Guard::new(&HttpSettings::default());
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static("localhost:8765"));
+        assert_eq!(open.check(&headers), Err(StatusCode::UNAUTHORIZED));
+    }
+
+    #[test]
+    fn test_host_allowed() {
+        let local = Guard::new(&HttpSettings::default());
+        assert!(local.host_allowed("127.0.0.1:8765"));
+        assert!(local.host_allowed("localhost:8765"));
+        assert!(local.host_allowed("[::1]:8765"));
+        assert!(!local.host_allowed("evil.example.com:8765"));
+        assert!(!local.host_allowed("192.168.1.5:8765"));
+
+        let public = Guard::new(&HttpSettings {
+            bind: "0.0.0.0:8765".to_string(),
+            allowed_hosts: vec!["mcp.example.com".to_string()],
+            ..Default::default()
+        });
+        assert!(public.host_allowed("192.168.1.5:8765"));
+        assert!(public.host_allowed("MCP.example.com"));
+        assert!(!public.host_allowed("localhost:8765"));
+        assert!(!public.host_allowed("evil.example.com"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_generate_token() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("http-token-{}", uuid::Uuid::new_v4()));
+        let token = generate_token(&path).unwrap();
+        assert_eq!(token.len(), 64);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), token);
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_ne!(generate_token(&path).unwrap(), token);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Send a raw HTTP/1.1 request and return the status code
+    async fn post(addr: std::net::SocketAddr, headers: &str, body: &str) -> u16 {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let host = if headers.contains("Host: ") {
+            String::new()
+        } else {
+            format!("Host: {addr}\r\n")
+        };
+        let request = format!(
+            "POST /mcp HTTP/1.1\r\n{host}Content-Type: application/json\r\n\
+             Accept: application/json, text/event-stream\r\n{headers}\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = vec![0; 64];
+        let read = stream.read(&mut response).await.unwrap();
+        let status_line = String::from_utf8_lossy(&response[..read]).to_string();
+        status_line
+            .split(' ')
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .unwrap_or_else(|| panic!("bad response: {status_line}"))
+    }
+
+    #[tokio::test]
+    async fn test_http_transport_requires_token() {
+        let settings = HttpSettings {
+            auth_token: Some("s3cret".to_string()),
+            ..Default::default()
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(TerminalMcpServer::with_headless_mode(true), &settings);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let initialize = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"test","version":"0"}}}"#;
+        assert_eq!(post(addr, "", initialize).await, 401);
+        let auth = "Authorization: Bearer s3cret\r\n";
+        let evil = format!("{auth}Origin: https://evil.com\r\n");
+        assert_eq!(post(addr, &evil, initialize).await, 403);
+        assert_eq!(post(addr, auth, initialize).await, 200);
+
+        // DNS rebinding: the right token can't be used under a foreign name
+        let rebound = "Authorization: Bearer s3cret\r\nHost: evil.example.com\r\n";
+        assert_eq!(post(addr, rebound, initialize).await, 403);
+    }
+}
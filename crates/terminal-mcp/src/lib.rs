@@ -3,11 +3,18 @@
 //! This library contains the MCP protocol layer types and handlers.
 //! The actual server binary is in main.rs.
 
+pub mod audit;
+pub mod http;
 pub mod protocol;
 pub mod schema;
+#[cfg(test)]
+mod test_client;
 pub mod tools;
+#[cfg(unix)]
+pub mod unix;
 
 // Re-export commonly used types
+pub use audit::{AuditEvent, AuditLog};
 pub use protocol::TerminalMcpServer;
 pub use schema::SchemaTransformer;
 pub use tools::*;
@@ -21,12 +21,26 @@
 
 use rmcp::{transport::stdio, ServiceExt};
 use terminal_mcp::TerminalMcpServer;
+use terminal_mcp_core::ServerConfig;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Parse command line arguments
     let args: Vec<String> = std::env::args().collect();
     let headless_mode = args.iter().any(|arg| arg == "--headless");
+    let flag_value = |flag: &str| {
+        args.iter()
+            .position(|arg| arg == flag)
+            .map(|idx| {
+                args.get(idx + 1)
+                    .ok_or_else(|| anyhow::anyhow!("{} requires a value", flag))
+            })
+            .transpose()
+    };
+    let config_path = flag_value("--config")?;
+    let transport = flag_value("--transport")?;
+    let bind = flag_value("--bind")?;
+    let socket = flag_value("--socket")?;
 
     // Initialize logging
     tracing_subscriber::fmt()
@@ -42,8 +56,59 @@ async fn main() -> anyhow::Result<()> {
         mode_str
     );
 
+    let mut config = match config_path {
+        Some(path) => {
+            tracing::info!("Loading config from {}", path);
+            ServerConfig::from_file(path)?
+        }
+        None => ServerConfig::default(),
+    };
+    if let Some(transport) = transport {
+        config.server.transport = transport.clone();
+    }
+    if let Some(bind) = bind {
+        config.server.http.bind = bind.clone();
+    }
+    if let Some(socket) = socket {
+        config.server.unix.path = Some(socket.clone());
+    }
+    // Kept out of argv, where other local users could read it
+    if let Ok(token) = std::env::var("TERMINAL_MCP_AUTH_TOKEN") {
+        config.server.http.auth_token = Some(token);
+    }
+    config.validate()?;
+    if config.security.namespace_sandbox() {
+        tracing::info!(
+            "Sessions run in a namespace sandbox (project_dir={:?}, network={})",
+            config.security.sandbox.project_dir,
+            config.security.sandbox.network
+        );
+    }
+
     // Create MCP server instance with headless mode flag
-    let server = TerminalMcpServer::with_headless_mode(headless_mode);
+    if let Some(path) = &config.security.policy.audit_log {
+        tracing::info!("Writing audit log to {}", path);
+    }
+    let server =
+        TerminalMcpServer::with_headless_mode(headless_mode).with_security(config.security)?;
+
+    if config.server.transport == "http" {
+        tracing::info!("Server initialized, starting HTTP transport...");
+        terminal_mcp::http::serve(server, &config.server.http).await?;
+        tracing::info!("Terminal MCP Server shutting down");
+        return Ok(());
+    }
+    if config.server.transport == "unix" {
+        #[cfg(unix)]
+        {
+            tracing::info!("Server initialized, starting unix socket transport...");
+            terminal_mcp::unix::serve(server, &config.server.unix).await?;
+            tracing::info!("Terminal MCP Server shutting down");
+            return Ok(());
+        }
+        #[cfg(not(unix))]
+        anyhow::bail!("The unix transport is only available on Unix");
+    }
 
     tracing::info!("Server initialized, starting stdio transport...");
 
@@ -1,8 +1,10 @@
 //! MCP Protocol Layer
 //!
 //! This module implements the Model Context Protocol server using rmcp 0.9.
-//! It exposes the terminal manipulation capabilities as MCP tools.
+//! It exposes the terminal manipulation capabilities as MCP tools, and each
+//! session as MCP resources.
 
+mod resources;
 pub mod server;
 
 pub use server::TerminalMcpServer;
@@ -0,0 +1,272 @@
+//! Terminal sessions as MCP resources.
+//!
+//! Every session is exposed under `terminal://sessions/{id}/…` as its screen
+//! text, detected elements, raw output and, while one is running, its
+//! recording. Subscribed clients get `notifications/resources/updated` when
+//! the session's screen changes.
+
+use std::time::Duration;
+
+use rmcp::model::{
+    AnnotateAble, RawResource, RawResourceTemplate, Resource, ResourceTemplate,
+    ResourceUpdatedNotificationParam,
+};
+use rmcp::{Peer, RoleServer};
+use tokio::sync::broadcast;
+use tracing::debug;
+
+use terminal_mcp_session::{ScreenChange, Session};
+
+/// Scheme and prefix of every session resource URI
+const URI_PREFIX: &str = "terminal://sessions/";
+
+/// How often a subscription reads pending output and sends coalesced updates
+const UPDATE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A view of a session exposed as a resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ResourceKind {
+    /// Screen text
+    Screen,
+    /// Detected UI elements as JSON
+    Elements,
+    /// Raw output with ANSI codes
+    Output,
+    /// Recording in asciicast v2 format
+    Recording,
+}
+
+impl ResourceKind {
+    pub(crate) const ALL: [ResourceKind; 4] = [
+        ResourceKind::Screen,
+        ResourceKind::Elements,
+        ResourceKind::Output,
+        ResourceKind::Recording,
+    ];
+
+    /// Last URI segment
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            ResourceKind::Screen => "screen",
+            ResourceKind::Elements => "elements",
+            ResourceKind::Output => "output",
+            ResourceKind::Recording => "recording",
+        }
+    }
+
+    pub(crate) fn mime_type(self) -> &'static str {
+        match self {
+            ResourceKind::Screen | ResourceKind::Output => "text/plain",
+            ResourceKind::Elements => "application/json",
+            ResourceKind::Recording => "application/x-asciicast",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            ResourceKind::Screen => "Current screen text",
+            ResourceKind::Elements => "UI elements detected on the current screen",
+            ResourceKind::Output => "All output so far, with ANSI escape codes",
+            ResourceKind::Recording => "Session recording in asciicast v2 format",
+        }
+    }
+}
+
+/// URI of a session resource.
+pub(crate) fn resource_uri(session_id: &str, kind: ResourceKind) -> String {
+    format!("{URI_PREFIX}{session_id}/{}", kind.name())
+}
+
+/// Split a resource URI into session ID and kind.
+pub(crate) fn parse_uri(uri: &str) -> Option<(&str, ResourceKind)> {
+    let (session_id, name) = uri.strip_prefix(URI_PREFIX)?.split_once('/')?;
+    let kind = ResourceKind::ALL
+        .into_iter()
+        .find(|kind| kind.name() == name)?;
+    (!session_id.is_empty()).then_some((session_id, kind))
+}
+
+/// Resources of one session; the recording only while one is running.
+pub(crate) fn session_resources(session_id: &str, session: &Session) -> Vec<Resource> {
+    ResourceKind::ALL
+        .into_iter()
+        .filter(|kind| *kind != ResourceKind::Recording || session.is_recording())
+        .map(|kind| {
+            let mut resource = RawResource::new(
+                resource_uri(session_id, kind),
+                format!("{} {}", session.command(), kind.name()),
+            );
+            resource.description = Some(kind.description().to_string());
+            resource.mime_type = Some(kind.mime_type().to_string());
+            resource.no_annotation()
+        })
+        .collect()
+}
+
+/// URI templates for session resources.
+pub(crate) fn resource_templates() -> Vec<ResourceTemplate> {
+    ResourceKind::ALL
+        .into_iter()
+        .map(|kind| {
+            RawResourceTemplate {
+                uri_template: format!("{URI_PREFIX}{{session_id}}/{}", kind.name()),
+                name: format!("session {}", kind.name()),
+                title: None,
+                description: Some(kind.description().to_string()),
+                mime_type: Some(kind.mime_type().to_string()),
+            }
+            .no_annotation()
+        })
+        .collect()
+}
+
+/// Notify `peer` about changes to a subscribed resource until the session
+/// goes away or the client disconnects.
+///
+/// The session's background reader publishes screen changes as output
+/// arrives; tmux sessions have no reader and publish them whenever a tool
+/// reads the pane. Changes are coalesced to at most one notification per
+/// [`UPDATE_INTERVAL`].
+pub(crate) async fn watch_resource(
+    peer: Peer<RoleServer>,
+    uri: String,
+    mut changes: broadcast::Receiver<ScreenChange>,
+) {
+    let mut tick = tokio::time::interval(UPDATE_INTERVAL);
+    let mut changed = false;
+    loop {
+        tokio::select! {
+            change = changes.recv() => match change {
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => changed = true,
+                // Session dropped
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = tick.tick() => {
+                if !std::mem::take(&mut changed) {
+                    continue;
+                }
+                let update = ResourceUpdatedNotificationParam { uri: uri.clone() };
+                if let Err(e) = peer.notify_resource_updated(update).await {
+                    debug!("Stopped watching {}: {}", uri, e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_uris() {
+        let uri = resource_uri("abc", ResourceKind::Elements);
+        assert_eq!(uri, "terminal://sessions/abc/elements");
+        assert_eq!(parse_uri(&uri), Some(("abc", ResourceKind::Elements)));
+        assert_eq!(
+            parse_uri("terminal://sessions/abc/recording"),
+            Some(("abc", ResourceKind::Recording))
+        );
+
+        assert_eq!(parse_uri("terminal://sessions/abc/cells"), None);
+        assert_eq!(parse_uri("terminal://sessions//screen"), None);
+        assert_eq!(parse_uri("terminal://sessions/abc"), None);
+        assert_eq!(parse_uri("file:///abc/screen"), None);
+    }
+
+    #[tokio::test]
+    async fn test_session_resources_and_updates() {
+        use crate::test_client::TestClient;
+        use crate::TerminalMcpServer;
+        use serde_json::json;
+
+        let mut client = TestClient::in_memory(TerminalMcpServer::with_headless_mode(true)).await;
+
+        let created = client
+            .call(
+                1,
+                "terminal_session_create",
+                json!({"command": "sh", "args": ["-c", "read x; echo got $x; sleep 5"]}),
+            )
+            .await;
+        let session_id = created["session_id"].as_str().unwrap();
+        let screen = resource_uri(session_id, ResourceKind::Screen);
+
+        let listed = client.request(2, "resources/list", json!({})).await;
+        let uris: Vec<_> = listed["result"]["resources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|resource| resource["uri"].as_str().unwrap().to_string())
+            .collect();
+        assert!(uris.contains(&screen), "{uris:?}");
+        assert!(!uris.contains(&resource_uri(session_id, ResourceKind::Recording)));
+
+        let subscribed = client
+            .request(3, "resources/subscribe", json!({"uri": screen}))
+            .await;
+        assert!(subscribed.get("error").is_none(), "{subscribed}");
+        client
+            .call(
+                4,
+                "terminal_type",
+                json!({"session_id": session_id, "text": "hello\n"}),
+            )
+            .await;
+        let updated = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.notification("notifications/resources/updated"),
+        )
+        .await
+        .expect("no resources/updated notification");
+        assert_eq!(updated["params"]["uri"], screen.as_str());
+
+        let mut text = String::new();
+        for id in 5..30 {
+            let read = client
+                .request(id, "resources/read", json!({"uri": screen}))
+                .await;
+            text = read["result"]["contents"][0]["text"]
+                .as_str()
+                .unwrap()
+                .to_string();
+            if text.contains("got hello") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(text.contains("got hello"), "{text}");
+
+        let output = client
+            .request(
+                30,
+                "resources/read",
+                json!({"uri": resource_uri(session_id, ResourceKind::Output)}),
+            )
+            .await;
+        assert!(output["result"]["contents"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("got hello"));
+
+        let missing = client
+            .request(
+                31,
+                "resources/read",
+                json!({"uri": resource_uri(session_id, ResourceKind::Recording)}),
+            )
+            .await;
+        assert!(missing.get("error").is_some(), "{missing}");
+    }
+
+    #[test]
+    fn test_resource_templates() {
+        let templates = resource_templates();
+        assert_eq!(templates.len(), 4);
+        assert_eq!(
+            templates[0].raw.uri_template,
+            "terminal://sessions/{session_id}/screen"
+        );
+    }
+}
@@ -28,7 +28,8 @@ impl SchemaTransformer {
         schema
     }
 
-    /// Transform a schema stored as a Map (used by rmcp's Tool.input_schema).
+    /// Transform a schema stored as a Map (rmcp's `Tool.input_schema` and
+    /// `Tool.output_schema`).
     ///
     /// This is a convenience wrapper around `transform` that works with
     /// serde_json::Map directly.
@@ -0,0 +1,149 @@
+//! Newline-delimited JSON-RPC client for protocol and transport tests.
+
+use std::collections::VecDeque;
+
+use rmcp::ServiceExt;
+use serde_json::{json, Value};
+use tokio::io::{
+    AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, DuplexStream, ReadHalf,
+    WriteHalf,
+};
+
+use crate::TerminalMcpServer;
+
+/// MCP client speaking raw JSON-RPC lines over any byte stream.
+pub(crate) struct TestClient<R, W> {
+    reader: BufReader<R>,
+    writer: W,
+    /// Messages read while waiting for something else
+    pending: VecDeque<Value>,
+}
+
+impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> TestClient<R, W> {
+    /// Connect and complete the initialize handshake.
+    pub(crate) async fn connect(reader: R, writer: W) -> Self {
+        Self::connect_with_capabilities(reader, writer, json!({})).await
+    }
+
+    /// Connect advertising client capabilities, e.g. `{"elicitation": {}}`.
+    pub(crate) async fn connect_with_capabilities(
+        reader: R,
+        writer: W,
+        capabilities: Value,
+    ) -> Self {
+        let mut client = Self {
+            reader: BufReader::new(reader),
+            writer,
+            pending: VecDeque::new(),
+        };
+        client
+            .request(
+                0,
+                "initialize",
+                json!({
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": capabilities,
+                    "clientInfo": {"name": "test", "version": "0"}
+                }),
+            )
+            .await;
+        client
+            .send(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .await;
+        client
+    }
+
+    pub(crate) async fn send(&mut self, message: Value) {
+        let line = format!("{message}\n");
+        self.writer.write_all(line.as_bytes()).await.unwrap();
+    }
+
+    async fn receive(&mut self) -> Value {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).await.unwrap();
+        serde_json::from_str(&line).unwrap_or_else(|e| panic!("bad message {line:?}: {e}"))
+    }
+
+    /// Send a request and wait for its response.
+    pub(crate) async fn request(&mut self, id: u64, method: &str, params: Value) -> Value {
+        self.send(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}))
+            .await;
+        self.response(id).await
+    }
+
+    /// Wait for the response to a request that was already sent.
+    pub(crate) async fn response(&mut self, id: u64) -> Value {
+        // Server requests carry ids of their own
+        let is_response = |m: &Value| m["id"] == id && m.get("method").is_none();
+        if let Some(index) = self.pending.iter().position(is_response) {
+            return self.pending.remove(index).unwrap();
+        }
+        loop {
+            let message = self.receive().await;
+            if is_response(&message) {
+                return message;
+            }
+            self.pending.push_back(message);
+        }
+    }
+
+    /// Call a tool and return the whole response, errors included.
+    pub(crate) async fn try_call(&mut self, id: u64, tool: &str, arguments: Value) -> Value {
+        self.request(
+            id,
+            "tools/call",
+            json!({"name": tool, "arguments": arguments}),
+        )
+        .await
+    }
+
+    /// Call a tool and return its structured content.
+    pub(crate) async fn call(&mut self, id: u64, tool: &str, arguments: Value) -> Value {
+        let response = self.try_call(id, tool, arguments).await;
+        match response["result"].get("structuredContent") {
+            Some(content) => content.clone(),
+            None => panic!("no structured result: {response}"),
+        }
+    }
+
+    /// Wait for a notification or server request with the given method.
+    pub(crate) async fn notification(&mut self, method: &str) -> Value {
+        if let Some(index) = self.pending.iter().position(|m| m["method"] == method) {
+            return self.pending.remove(index).unwrap();
+        }
+        loop {
+            let message = self.receive().await;
+            if message["method"] == method {
+                return message;
+            }
+            self.pending.push_back(message);
+        }
+    }
+
+    /// Answer a request the server sent, e.g. `elicitation/create`.
+    pub(crate) async fn respond(&mut self, request: &Value, result: Value) {
+        self.send(json!({"jsonrpc": "2.0", "id": request["id"], "result": result}))
+            .await;
+    }
+}
+
+impl TestClient<ReadHalf<DuplexStream>, WriteHalf<DuplexStream>> {
+    /// Serve `server` over an in-memory stream and connect to it.
+    pub(crate) async fn in_memory(server: TerminalMcpServer) -> Self {
+        Self::in_memory_with_capabilities(server, json!({})).await
+    }
+
+    /// Like [`TestClient::in_memory`], advertising client capabilities.
+    pub(crate) async fn in_memory_with_capabilities(
+        server: TerminalMcpServer,
+        capabilities: Value,
+    ) -> Self {
+        let (client_stream, server_stream) = tokio::io::duplex(1 << 16);
+        tokio::spawn(async move {
+            let service = server.serve(server_stream).await.unwrap();
+            let _ = service.waiting().await;
+        });
+        let (reader, writer) = tokio::io::split(client_stream);
+        Self::connect_with_capabilities(reader, writer, capabilities).await
+    }
+}
@@ -5,7 +5,7 @@
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use terminal_mcp_core::{Dimensions, TerminalStateTree};
+use terminal_mcp_core::{Dimensions, Position, ResourceLimits, TerminalStateTree};
 
 // =============================================================================
 // Session Management Tools
@@ -42,6 +42,11 @@ pub struct SessionCreateParams {
     /// If not specified or "auto", will use the best available terminal for the platform
     #[serde(default)]
     pub terminal_emulator: Option<String>,
+
+    /// Resource limits for this session; they can only tighten the server's
+    /// configured limits. Limited sessions are headless.
+    #[serde(default)]
+    pub limits: Option<ResourceLimits>,
 }
 
 /// Response for terminal_session_create
@@ -97,6 +102,9 @@ pub struct SessionInfo {
 
     /// Session age in seconds
     pub age_seconds: u64,
+
+    /// "running", "exited", "terminated" or "limit_exceeded: <limit>"
+    pub status: String,
 }
 
 /// Parameters for terminal_session_close
@@ -160,6 +168,18 @@ pub struct SnapshotParams {
     /// Idle threshold in milliseconds (wait for terminal to be idle)
     #[serde(default)]
     pub idle_threshold_ms: Option<u64>,
+
+    /// Include the screen re-encoded with ANSI colors in `ansi_buffer`
+    #[serde(default)]
+    pub include_ansi: bool,
+
+    /// Include the screen as a standalone HTML page in `html`
+    #[serde(default)]
+    pub include_html: bool,
+
+    /// Include per-row color and attribute runs in `styles`
+    #[serde(default)]
+    pub include_styles: bool,
 }
 
 fn default_true() -> bool {
@@ -169,6 +189,82 @@ fn default_true() -> bool {
 /// Response for terminal_snapshot (returns Terminal State Tree)
 pub type SnapshotResponse = TerminalStateTree;
 
+/// Parameters for terminal_screenshot
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScreenshotParams {
+    /// Session to capture
+    pub session_id: String,
+
+    /// Image format: "png" (default) or "svg"
+    #[serde(default)]
+    pub format: Option<String>,
+
+    /// PNG scale factor, 1-4 (default: 1)
+    #[serde(default)]
+    pub scale: Option<u32>,
+}
+
+/// Parameters for terminal_screen_history
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScreenHistoryParams {
+    /// Session to read history from
+    pub session_id: String,
+
+    /// Only return frames newer than this generation
+    #[serde(default)]
+    pub since_generation: Option<u64>,
+
+    /// Maximum number of frames, newest kept (default: 10)
+    #[serde(default)]
+    pub limit: Option<usize>,
+
+    /// Include each frame's text (default: true)
+    #[serde(default = "default_true")]
+    pub include_text: bool,
+
+    /// Diff from this frame (requires `to_generation`)
+    #[serde(default)]
+    pub from_generation: Option<u64>,
+
+    /// Diff to this frame (requires `from_generation`)
+    #[serde(default)]
+    pub to_generation: Option<u64>,
+}
+
+/// A past screen in terminal_screen_history
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HistoryFrame {
+    /// Screen generation of the frame
+    pub generation: u64,
+
+    /// When the frame was taken (RFC 3339)
+    pub timestamp: String,
+
+    /// Screen text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+
+    /// Cursor position
+    pub cursor: Position,
+}
+
+/// Response for terminal_screen_history
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScreenHistoryResponse {
+    /// Session the history belongs to
+    pub session_id: String,
+
+    /// Frames, oldest first
+    pub frames: Vec<HistoryFrame>,
+
+    /// Line diff between `from_generation` and `to_generation`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+
+    /// Frames recorded since the session started, including evicted ones
+    pub total_recorded: u64,
+}
+
 /// Parameters for terminal_read_output
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ReadOutputParams {
@@ -250,6 +346,79 @@ pub struct TypeResponse {
     pub message: String,
 }
 
+/// Parameters for terminal_paste
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PasteParams {
+    /// Session to paste into
+    pub session_id: String,
+
+    /// Text to paste (may span multiple lines)
+    pub text: String,
+}
+
+/// Response for terminal_paste
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PasteResponse {
+    /// Session that received the text
+    pub session_id: String,
+
+    /// Number of characters pasted
+    pub chars_pasted: usize,
+
+    /// Delivery method: "bracketed", "typed" (app has no bracketed paste) or "tmux"
+    pub method: String,
+
+    /// Success message
+    pub message: String,
+}
+
+/// Parameters for terminal_enter_secret
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EnterSecretParams {
+    /// Session waiting for the secret
+    pub session_id: String,
+
+    /// Extra context shown to the human, e.g. what the secret unlocks
+    #[serde(default)]
+    pub message: Option<String>,
+
+    /// Press Enter after the secret (default: true)
+    #[serde(default = "default_true")]
+    pub submit: bool,
+
+    /// Refuse unless a password or passphrase prompt is at the cursor (default: true).
+    /// When false the PTY must still report hidden input; a terminal that
+    /// echoes input is always refused
+    #[serde(default = "default_true")]
+    pub require_prompt: bool,
+
+    /// Type the secret even though the PTY can't confirm that input is hidden
+    /// (echo off in canonical mode), e.g. in tmux and visual sessions or at
+    /// raw-mode prompts that draw typed keys themselves (default: false)
+    #[serde(default)]
+    pub allow_unverified_echo: bool,
+
+    /// Seconds to wait for the human to answer (default: 300)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Response for terminal_enter_secret; never contains the secret
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EnterSecretResponse {
+    /// Session the secret was typed into
+    pub session_id: String,
+
+    /// Whether the human provided a secret and it was typed
+    pub entered: bool,
+
+    /// Prompt text at the cursor when the human was asked
+    pub prompt: String,
+
+    /// Message describing the result
+    pub message: String,
+}
+
 // =============================================================================
 // Navigation Tools
 // =============================================================================
@@ -306,6 +475,16 @@ pub struct WaitForParams {
     #[serde(default)]
     pub idle: bool,
 
+    /// Wait for the bell to ring (or a notification, if both are set); can't
+    /// be combined with text, element_type or idle
+    #[serde(default)]
+    pub bell: bool,
+
+    /// Wait for a desktop notification (OSC 9/777); can't be combined with
+    /// text, element_type or idle
+    #[serde(default)]
+    pub notification: bool,
+
     /// Timeout in milliseconds (default: 5000)
     #[serde(default = "default_timeout")]
     pub timeout_ms: u64,
@@ -0,0 +1,199 @@
+//! Unix domain socket transport.
+//!
+//! Each connection speaks newline-delimited JSON-RPC, as over stdio, and is
+//! served by its own [`TerminalMcpServer::connection`], so every client of
+//! the daemon sees the same terminal sessions. Access is controlled by the
+//! socket's file mode rather than by a token.
+
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::Path;
+
+use rmcp::ServiceExt;
+use tokio::net::{UnixListener, UnixStream};
+use tracing::{debug, info, warn};
+
+use terminal_mcp_core::UnixSocketSettings;
+
+use crate::TerminalMcpServer;
+
+/// Bind the socket, replacing a stale one left by a crashed server.
+///
+/// Fails if another server is still listening on `path`, or if `path` is
+/// not a socket of the current user. The socket is bound in a private
+/// directory and gets `mode` before it is moved to `path`, so it is never
+/// reachable with looser permissions.
+pub async fn bind(path: &Path, mode: u32) -> std::io::Result<UnixListener> {
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ));
+        }
+        // SAFETY: geteuid has no preconditions and cannot fail
+        let uid = unsafe { libc::geteuid() };
+        if metadata.uid() != uid {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!(
+                    "{} belongs to uid {}, not to this user",
+                    path.display(),
+                    metadata.uid()
+                ),
+            ));
+        }
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                format!("another server is listening on {}", path.display()),
+            ));
+        }
+        debug!("Removing stale socket {}", path.display());
+        std::fs::remove_file(path)?;
+    }
+
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let staging = parent.join(format!(".terminal-mcp-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+    let staged = staging.join("sock");
+    let bound = UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(mode))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_file(&staged);
+    std::fs::remove_dir(&staging)?;
+    bound
+}
+
+/// Accept connections until `shutdown` completes, serving each one
+/// concurrently.
+pub async fn accept_loop(
+    server: TerminalMcpServer,
+    listener: UnixListener,
+    shutdown: impl std::future::Future<Output = ()>,
+) {
+    tokio::pin!(shutdown);
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+
+        let connection = server.connection();
+        tokio::spawn(async move {
+            debug!("Client connected");
+            match connection.serve(stream).await {
+                Ok(service) => {
+                    if let Err(e) = service.waiting().await {
+                        warn!("Connection ended with an error: {}", e);
+                    }
+                }
+                Err(e) => warn!("Failed to start MCP on connection: {}", e),
+            }
+            debug!("Client disconnected");
+        });
+    }
+}
+
+/// Serve MCP on a Unix socket until Ctrl+C, then remove the socket.
+pub async fn serve(server: TerminalMcpServer, settings: &UnixSocketSettings) -> anyhow::Result<()> {
+    let path = settings.socket_path()?;
+    let mode = settings.file_mode()?;
+    let listener = bind(&path, mode).await?;
+    info!(
+        "Serving MCP on unix socket {} (mode {:o})",
+        path.display(),
+        mode
+    );
+
+    accept_loop(server, listener, async {
+        let _ = tokio::signal::ctrl_c().await;
+    })
+    .await;
+
+    if let Err(e) = std::fs::remove_file(&path) {
+        warn!("Failed to remove socket {}: {}", path.display(), e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_client::TestClient;
+
+    async fn connect(
+        path: &Path,
+    ) -> TestClient<tokio::net::unix::OwnedReadHalf, tokio::net::unix::OwnedWriteHalf> {
+        let (reader, writer) = UnixStream::connect(path).await.unwrap().into_split();
+        TestClient::connect(reader, writer).await
+    }
+
+    #[tokio::test]
+    async fn test_unix_socket_clients_share_sessions() {
+        let dir = std::env::temp_dir().join(format!("tm-unix-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&dir).unwrap();
+        let path = dir.join("mcp.sock");
+
+        let listener = bind(&path, 0o600).await.unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(bind(&path, 0o600).await.is_err());
+
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = TerminalMcpServer::with_headless_mode(true);
+        let accept = tokio::spawn(accept_loop(server, listener, async {
+            let _ = stopped.await;
+        }));
+
+        let mut first = connect(&path).await;
+        let mut second = connect(&path).await;
+        let created = first
+            .call(
+                1,
+                "terminal_session_create",
+                serde_json::json!({"command": "sleep", "args": ["5"]}),
+            )
+            .await;
+        let session_id = created["session_id"].as_str().unwrap();
+        let listed = second
+            .call(1, "terminal_session_list", serde_json::json!({}))
+            .await;
+        assert_eq!(listed["sessions"][0]["session_id"], session_id, "{listed}");
+
+        stop.send(()).unwrap();
+        accept.await.unwrap();
+        // A socket nobody listens on anymore is stale and gets replaced
+        assert!(bind(&path, 0o600).await.is_ok());
+        // Only the socket is left behind
+        let entries: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stale_socket_of_another_user_is_kept() {
+        let dir = std::env::temp_dir().join(format!("tm-unix-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&dir).unwrap();
+        let path = dir.join("mcp.sock");
+        drop(bind(&path, 0o600).await.unwrap());
+
+        // Only root can hand a file to someone else
+        if std::os::unix::fs::chown(&path, Some(65534), None).is_ok() {
+            let error = bind(&path, 0o600).await.unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::PermissionDenied);
+            assert!(path.exists());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
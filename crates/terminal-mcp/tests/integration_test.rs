@@ -13,7 +13,8 @@ fn test_full_detection_pipeline() {
         "│ Main Content    │\r\n",
         "│ More text here  │\r\n",
         "└─────────────────┘\r\n",
-        "Press q to quit | F1 Help\r\n"
+        // No trailing newline: on the last row it would scroll the screen
+        "Press q to quit | F1 Help"
     );
 
     // Grid should match the actual content: 5 rows (border rows 0-3, status bar row 4)